@@ -0,0 +1,178 @@
+//! # Migración inicial en MySQL/MariaDB
+//!
+//! `m20250717_193532_crear_catalogos` repite nombres de claves foráneas: `fk_id_estado` en
+//! `cat_municipios`, `cat_codigos_postales` y `cat_localidades`, y `fk_id_municipio` en las dos
+//! últimas. Postgres y SQLite sólo exigen nombres únicos por tabla, pero MySQL los exige en todo
+//! el esquema y rechaza la creación de `cat_codigos_postales`.
+//!
+//! Para no modificar una migración que ya se aplicó en otras instalaciones, el `Migrator` registra
+//! [`Migration`] con el mismo nombre que la migración inicial: en Postgres y SQLite corre la
+//! migración inicial sin cambios y en MySQL crea las mismas tablas con los nombres de claves
+//! foráneas que `m20261018_160000_llaves_foraneas_utf8mb4` deja en los demás motores.
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DatabaseBackend;
+
+use crate::m20250717_193532_crear_catalogos::{
+    self as crear_catalogos, cat_codigos_postales, cat_estados, cat_localidades, cat_municipios,
+};
+
+/// Migración inicial con nombres de claves foráneas únicos en MySQL/MariaDB.
+pub struct Migration;
+
+impl MigrationName for Migration {
+    /// Mismo nombre que la migración inicial, para que `seaql_migrations` la reconozca como aplicada.
+    fn name(&self) -> &str {
+        crear_catalogos::Migration.name()
+    }
+}
+
+/// Clave foránea de las tablas de catálogos: nombre, tabla y columna de origen, tabla y columna
+/// referenciadas.
+type LlaveForanea = (&'static str, DynIden, DynIden, DynIden, DynIden);
+
+/// Tablas de la migración inicial con sus claves foráneas, en el orden en que se crean.
+fn tablas() -> Vec<TableCreateStatement> {
+    let llave = |(nombre, tabla, columna, referencia, id): LlaveForanea| {
+        ForeignKey::create()
+            .name(nombre)
+            .from(tabla, columna)
+            .to(referencia, id)
+            .to_owned()
+    };
+    let entero = |columna: DynIden| ColumnDef::new(columna).integer().not_null().to_owned();
+    let clave = |columna: DynIden| {
+        ColumnDef::new(columna)
+            .integer()
+            .not_null()
+            .auto_increment()
+            .primary_key()
+            .to_owned()
+    };
+
+    vec![
+        Table::create()
+            .table(cat_estados::Table)
+            .if_not_exists()
+            .col(clave(cat_estados::id.into_iden()))
+            .col(
+                ColumnDef::new(cat_estados::estado)
+                    .string_len(50)
+                    .not_null(),
+            )
+            .to_owned(),
+        Table::create()
+            .table(cat_municipios::Table)
+            .if_not_exists()
+            .col(clave(cat_municipios::id.into_iden()))
+            .col(
+                ColumnDef::new(cat_municipios::municipio)
+                    .string_len(50)
+                    .not_null(),
+            )
+            .col(entero(cat_municipios::id_estado.into_iden()))
+            .foreign_key(&mut llave((
+                "fk_id_estado",
+                cat_municipios::Table.into_iden(),
+                cat_municipios::id_estado.into_iden(),
+                cat_estados::Table.into_iden(),
+                cat_estados::id.into_iden(),
+            )))
+            .to_owned(),
+        Table::create()
+            .table(cat_codigos_postales::Table)
+            .if_not_exists()
+            .col(clave(cat_codigos_postales::codigo_postal.into_iden()))
+            .col(entero(cat_codigos_postales::id_municipio.into_iden()))
+            .col(entero(cat_codigos_postales::id_estado.into_iden()))
+            .foreign_key(&mut llave((
+                "fk_id_municipio",
+                cat_codigos_postales::Table.into_iden(),
+                cat_codigos_postales::id_municipio.into_iden(),
+                cat_municipios::Table.into_iden(),
+                cat_municipios::id.into_iden(),
+            )))
+            .foreign_key(&mut llave((
+                "fk_cp_id_estado",
+                cat_codigos_postales::Table.into_iden(),
+                cat_codigos_postales::id_estado.into_iden(),
+                cat_estados::Table.into_iden(),
+                cat_estados::id.into_iden(),
+            )))
+            .to_owned(),
+        Table::create()
+            .table(cat_localidades::Table)
+            .if_not_exists()
+            .col(clave(cat_localidades::id.into_iden()))
+            .col(
+                ColumnDef::new(cat_localidades::localidad)
+                    .string_len(100)
+                    .not_null(),
+            )
+            .col(entero(cat_localidades::codigo_postal.into_iden()))
+            .col(entero(cat_localidades::id_municipio.into_iden()))
+            .col(entero(cat_localidades::id_estado.into_iden()))
+            .foreign_key(&mut llave((
+                "fk_codigo_postal",
+                cat_localidades::Table.into_iden(),
+                cat_localidades::codigo_postal.into_iden(),
+                cat_codigos_postales::Table.into_iden(),
+                cat_codigos_postales::codigo_postal.into_iden(),
+            )))
+            .foreign_key(&mut llave((
+                "fk_localidades_id_municipio",
+                cat_localidades::Table.into_iden(),
+                cat_localidades::id_municipio.into_iden(),
+                cat_municipios::Table.into_iden(),
+                cat_municipios::id.into_iden(),
+            )))
+            .foreign_key(&mut llave((
+                "fk_localidades_id_estado",
+                cat_localidades::Table.into_iden(),
+                cat_localidades::id_estado.into_iden(),
+                cat_estados::Table.into_iden(),
+                cat_estados::id.into_iden(),
+            )))
+            .to_owned(),
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// En MySQL crea las tablas de [`tablas`]; en los demás motores corre la migración inicial.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::MySql {
+            return crear_catalogos::Migration.up(manager).await;
+        }
+        for tabla in tablas() {
+            manager.create_table(tabla).await?;
+        }
+        Ok(())
+    }
+
+    /// Elimina las tablas igual que la migración inicial.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        crear_catalogos::Migration.down(manager).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn nombres_de_llaves_foraneas_unicos_en_el_esquema() {
+        let sql: Vec<String> = tablas()
+            .iter()
+            .map(|tabla| tabla.to_string(MysqlQueryBuilder))
+            .collect();
+        let mut nombres = HashSet::new();
+        for sentencia in &sql {
+            for parte in sentencia.split("CONSTRAINT `").skip(1) {
+                let nombre = parte.split('`').next().unwrap();
+                assert!(nombres.insert(nombre.to_string()), "{nombre} repetido");
+            }
+        }
+        assert_eq!(nombres.len(), 6);
+    }
+}
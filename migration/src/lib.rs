@@ -1,13 +1,25 @@
 pub use sea_orm_migration::prelude::*;
 
+mod crear_catalogos_mysql;
 mod m20250717_193532_crear_catalogos;
+mod m20261018_120000_indices_catalogos;
+mod m20261018_130000_llaves_naturales;
+mod m20261018_140000_coordenadas;
+mod m20261018_150000_zonas;
+mod m20261018_160000_llaves_foraneas_utf8mb4;
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
-            Box::new(m20250717_193532_crear_catalogos::Migration),
+            // Corre m20250717_193532_crear_catalogos; en MySQL con nombres de claves foráneas únicos
+            Box::new(crear_catalogos_mysql::Migration),
+            Box::new(m20261018_120000_indices_catalogos::Migration),
+            Box::new(m20261018_130000_llaves_naturales::Migration),
+            Box::new(m20261018_140000_coordenadas::Migration),
+            Box::new(m20261018_150000_zonas::Migration),
+            Box::new(m20261018_160000_llaves_foraneas_utf8mb4::Migration),
         ]
     }
 }
@@ -0,0 +1,103 @@
+//! # Índices y restricciones únicas de los catálogos
+//!
+//! La migración inicial crea las claves foráneas, pero Postgres y SQLite no crean índices
+//! para ellas, por lo que `busqueda-cp` recorría completa la tabla de localidades.
+//!
+//! ## Índices creados
+//!
+//! - `idx_localidades_codigo_postal`
+//!   Localidades de un código postal (`busqueda-cp`).
+//!
+//! - `idx_localidades_id_municipio`
+//!   Localidades de un municipio.
+//!
+//! - `idx_cp_id_municipio`
+//!   Códigos postales de un municipio.
+//!
+//! - `uq_municipios_estado_municipio`
+//!   Un nombre de municipio no se repite dentro del mismo estado. Como empieza por
+//!   `id_estado`, también sirve para buscar los municipios de un estado.
+//!
+//! - `uq_estados_estado`
+//!   Los nombres de los estados no se repiten.
+use sea_orm_migration::prelude::*;
+
+use crate::m20250717_193532_crear_catalogos::{
+    cat_codigos_postales, cat_estados, cat_localidades, cat_municipios,
+};
+
+/// Migración que agrega índices a las columnas de búsqueda y restricciones únicas de nombres.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Índice que crea la migración: nombre, tabla, columnas y si es único.
+type Indice = (&'static str, DynIden, Vec<DynIden>, bool);
+
+/// Índices de la migración, en el orden en que se crean.
+fn indices() -> Vec<Indice> {
+    vec![
+        (
+            "idx_localidades_codigo_postal",
+            cat_localidades::Table.into_iden(),
+            vec![cat_localidades::codigo_postal.into_iden()],
+            false,
+        ),
+        (
+            "idx_localidades_id_municipio",
+            cat_localidades::Table.into_iden(),
+            vec![cat_localidades::id_municipio.into_iden()],
+            false,
+        ),
+        (
+            "idx_cp_id_municipio",
+            cat_codigos_postales::Table.into_iden(),
+            vec![cat_codigos_postales::id_municipio.into_iden()],
+            false,
+        ),
+        (
+            "uq_municipios_estado_municipio",
+            cat_municipios::Table.into_iden(),
+            vec![
+                cat_municipios::id_estado.into_iden(),
+                cat_municipios::municipio.into_iden(),
+            ],
+            true,
+        ),
+        (
+            "uq_estados_estado",
+            cat_estados::Table.into_iden(),
+            vec![cat_estados::estado.into_iden()],
+            true,
+        ),
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Crea los índices. Falla si ya hay nombres de municipio o de estado repetidos.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (nombre, tabla, columnas, unico) in indices() {
+            let mut indice = Index::create();
+            indice.name(nombre).table(tabla);
+            for columna in columnas {
+                indice.col(columna);
+            }
+            if unico {
+                indice.unique();
+            }
+            manager.create_index(indice).await?;
+        }
+        Ok(())
+    }
+
+    /// Elimina los índices creados por `up`.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (nombre, tabla, _, _) in indices() {
+            // MySQL necesita la tabla del índice; Postgres y SQLite la ignoran
+            manager
+                .drop_index(Index::drop().name(nombre).table(tabla).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
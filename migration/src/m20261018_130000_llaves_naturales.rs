@@ -0,0 +1,189 @@
+//! # Llaves naturales de los catálogos
+//!
+//! La migración inicial declara todas las claves primarias con `auto_increment()`, aunque
+//! algunas reproducen claves oficiales. En esas columnas la secuencia no tiene sentido y un
+//! insert sin la clave generaría un código inventado.
+//!
+//! ## Columnas corregidas
+//!
+//! - `cat_estados.id`
+//!   Clave INEGI del estado (1 a 32).
+//!
+//! - `cat_codigos_postales.codigo_postal`
+//!   Código postal de SEPOMEX (01000 a 99999, guardado como entero).
+//!
+//! En ambas se elimina el valor por defecto (y la secuencia en Postgres o el `AUTO_INCREMENT`
+//! en MySQL) y se agrega un `CHECK` con el rango válido. SQLite no permite modificar columnas
+//! ni agregar restricciones, por lo que el rango se valida con triggers.
+//!
+//! `cat_municipios.id` y `cat_localidades.id` son ids internos de los CSV, no claves oficiales,
+//! por lo que conservan su definición.
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement, TransactionTrait};
+
+/// Migración que convierte en llaves naturales las claves primarias que reproducen claves oficiales.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Columnas con clave oficial: tabla, columna y rango válido.
+const LLAVES_OFICIALES: [(&str, &str, i32, i32); 2] = [
+    ("cat_estados", "id", 1, 32),
+    ("cat_codigos_postales", "codigo_postal", 1000, 99999),
+];
+
+/// Indica si en MySQL ya existe el `CHECK` de rango de `tabla.columna`.
+async fn existe_check_mysql<C: ConnectionTrait>(
+    db: &C,
+    tabla: &str,
+    columna: &str,
+) -> Result<bool, DbErr> {
+    let renglon = db
+        .query_one(Statement::from_sql_and_values(
+            DatabaseBackend::MySql,
+            "SELECT COUNT(*) FROM information_schema.TABLE_CONSTRAINTS \
+             WHERE CONSTRAINT_SCHEMA = DATABASE() AND TABLE_NAME = ? AND CONSTRAINT_NAME = ?",
+            [tabla.into(), format!("ck_{tabla}_{columna}").into()],
+        ))
+        .await?;
+    let total: i64 = match renglon {
+        Some(renglon) => renglon.try_get_by_index(0)?,
+        None => 0,
+    };
+    Ok(total > 0)
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Elimina las secuencias y agrega los `CHECK` de rango.
+    /// Falla si ya hay registros fuera de rango.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match manager.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                for (tabla, columna, minimo, maximo) in LLAVES_OFICIALES {
+                    db.execute_unprepared(&format!(
+                        "ALTER TABLE {tabla} ALTER COLUMN {columna} DROP DEFAULT"
+                    ))
+                    .await?;
+                    db.execute_unprepared(&format!(
+                        "DROP SEQUENCE IF EXISTS {tabla}_{columna}_seq"
+                    ))
+                    .await?;
+                    db.execute_unprepared(&format!(
+                        "ALTER TABLE {tabla} ADD CONSTRAINT ck_{tabla}_{columna} \
+                         CHECK ({columna} BETWEEN {minimo} AND {maximo})"
+                    ))
+                    .await?;
+                }
+            }
+            DatabaseBackend::MySql => {
+                // Las columnas están referenciadas por claves foráneas y `FOREIGN_KEY_CHECKS` es
+                // de la sesión: la transacción sólo asegura que todas las sentencias usen la misma
+                // conexión del pool. No hace atómico el cambio, porque MySQL confirma cada
+                // `ALTER TABLE` por separado; por eso cada paso revisa si ya se aplicó y la
+                // migración puede repetirse si falla a la mitad.
+                let txn = db.begin().await?;
+                txn.execute_unprepared("SET FOREIGN_KEY_CHECKS = 0").await?;
+                let resultado = async {
+                    for (tabla, columna, minimo, maximo) in LLAVES_OFICIALES {
+                        txn.execute_unprepared(&format!(
+                            "ALTER TABLE {tabla} MODIFY {columna} int NOT NULL"
+                        ))
+                        .await?;
+                        if !existe_check_mysql(&txn, tabla, columna).await? {
+                            txn.execute_unprepared(&format!(
+                                "ALTER TABLE {tabla} ADD CONSTRAINT ck_{tabla}_{columna} \
+                                 CHECK ({columna} BETWEEN {minimo} AND {maximo})"
+                            ))
+                            .await?;
+                        }
+                    }
+                    Ok::<_, DbErr>(())
+                }
+                .await;
+                // Se reactiva aunque algún paso falle, para no devolver al pool una conexión sin
+                // revisión de claves foráneas
+                txn.execute_unprepared("SET FOREIGN_KEY_CHECKS = 1").await?;
+                resultado?;
+                txn.commit().await?;
+            }
+            DatabaseBackend::Sqlite => {
+                for (tabla, columna, minimo, maximo) in LLAVES_OFICIALES {
+                    for evento in ["insert", "update"] {
+                        db.execute_unprepared(&format!(
+                            "CREATE TRIGGER IF NOT EXISTS ck_{tabla}_{columna}_{evento} \
+                             BEFORE {evento} ON {tabla} \
+                             WHEN NEW.{columna} IS NULL OR NEW.{columna} NOT BETWEEN {minimo} AND {maximo} \
+                             BEGIN SELECT RAISE(ABORT, '{tabla}.{columna} fuera de rango'); END"
+                        ))
+                        .await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Quita los `CHECK` y vuelve a crear las secuencias, continuando después del mayor valor.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match manager.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                for (tabla, columna, _, _) in LLAVES_OFICIALES {
+                    db.execute_unprepared(&format!(
+                        "ALTER TABLE {tabla} DROP CONSTRAINT IF EXISTS ck_{tabla}_{columna}"
+                    ))
+                    .await?;
+                    db.execute_unprepared(&format!(
+                        "CREATE SEQUENCE IF NOT EXISTS {tabla}_{columna}_seq OWNED BY {tabla}.{columna}"
+                    ))
+                    .await?;
+                    db.execute_unprepared(&format!(
+                        "SELECT setval('{tabla}_{columna}_seq', COALESCE(MAX({columna}), 0) + 1, false) FROM {tabla}"
+                    ))
+                    .await?;
+                    db.execute_unprepared(&format!(
+                        "ALTER TABLE {tabla} ALTER COLUMN {columna} \
+                         SET DEFAULT nextval('{tabla}_{columna}_seq')"
+                    ))
+                    .await?;
+                }
+            }
+            DatabaseBackend::MySql => {
+                // Misma conexión y mismos pasos repetibles que en `up`
+                let txn = db.begin().await?;
+                txn.execute_unprepared("SET FOREIGN_KEY_CHECKS = 0").await?;
+                let resultado = async {
+                    for (tabla, columna, _, _) in LLAVES_OFICIALES {
+                        if existe_check_mysql(&txn, tabla, columna).await? {
+                            txn.execute_unprepared(&format!(
+                                "ALTER TABLE {tabla} DROP CONSTRAINT ck_{tabla}_{columna}"
+                            ))
+                            .await?;
+                        }
+                        txn.execute_unprepared(&format!(
+                            "ALTER TABLE {tabla} MODIFY {columna} int NOT NULL AUTO_INCREMENT"
+                        ))
+                        .await?;
+                    }
+                    Ok::<_, DbErr>(())
+                }
+                .await;
+                txn.execute_unprepared("SET FOREIGN_KEY_CHECKS = 1").await?;
+                resultado?;
+                txn.commit().await?;
+            }
+            DatabaseBackend::Sqlite => {
+                for (tabla, columna, _, _) in LLAVES_OFICIALES {
+                    for evento in ["insert", "update"] {
+                        db.execute_unprepared(&format!(
+                            "DROP TRIGGER IF EXISTS ck_{tabla}_{columna}_{evento}"
+                        ))
+                        .await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! # Coordenadas de códigos postales y localidades
+//!
+//! Agrega el centroide (latitud y longitud en grados decimales, WGS84) a `cat_codigos_postales`
+//! y `cat_localidades`. Las columnas aceptan `NULL` porque no todos los registros tienen
+//! coordenadas en los archivos de INEGI.
+//!
+//! ## Columnas agregadas
+//!
+//! - `latitud`
+//!   `decimal(9,6)`, de -90 a 90.
+//!
+//! - `longitud`
+//!   `decimal(9,6)`, de -180 a 180.
+//!
+//! Seis decimales equivalen a unos 11 cm, más que suficiente para un centroide.
+#![allow(non_camel_case_types)]
+use sea_orm_migration::prelude::*;
+
+use crate::m20250717_193532_crear_catalogos::{cat_codigos_postales, cat_localidades};
+
+/// Migración que agrega las columnas de coordenadas a códigos postales y localidades.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Columnas de coordenadas, con el mismo nombre en ambas tablas
+#[derive(DeriveIden)]
+enum coordenadas {
+    latitud,
+    longitud,
+}
+
+/// Tablas a las que se agregan las coordenadas.
+fn tablas() -> [DynIden; 2] {
+    [
+        cat_codigos_postales::Table.into_iden(),
+        cat_localidades::Table.into_iden(),
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Agrega `latitud` y `longitud` a cada tabla.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for tabla in tablas() {
+            // SQLite sólo permite una columna por `ALTER TABLE`
+            for columna in [coordenadas::latitud, coordenadas::longitud] {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(tabla.clone())
+                            .add_column(ColumnDef::new(columna).decimal_len(9, 6).null())
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Elimina las columnas agregadas por `up`.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for tabla in tablas() {
+            for columna in [coordenadas::latitud, coordenadas::longitud] {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(tabla.clone())
+                            .drop_column(columna)
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+//! # Zonas de entrega
+//!
+//! Crea las tablas de las zonas que define logística (p. ej. "CDMX centro", "zona extendida").
+//! Una zona se compone de códigos postales sueltos, rangos de códigos postales y municipios; un
+//! código postal pertenece a la zona si cumple cualquiera de ellos.
+//!
+//! ## Tablas creadas
+//!
+//! - `zonas`
+//!   Nombre y descripción de cada zona. `clave` es el nombre sin espacios repetidos y en
+//!   minúsculas, y es única para que "CDMX centro" y "cdmx  Centro" choquen en todos los motores.
+//!
+//! - `zonas_codigos_postales`
+//!   Códigos postales de la zona.
+//!
+//! - `zonas_rangos_cp`
+//!   Rangos de códigos postales de la zona, inclusivos en ambos extremos.
+//!
+//! - `zonas_municipios`
+//!   Municipios de la zona; abarcan los códigos postales del municipio en `cat_codigos_postales`.
+//!
+//! Las tablas de miembros dependen de `zonas` y se borran con ella. No tienen claves foráneas
+//! hacia los catálogos porque éstos se vacían en cada recarga.
+#![allow(non_camel_case_types)]
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DatabaseBackend;
+
+/// Migración que crea las tablas de zonas y sus miembros.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Estructura de la tabla `zonas`
+#[derive(DeriveIden)]
+enum zonas {
+    Table,
+    id,
+    nombre,
+    clave,
+    descripcion,
+}
+
+/// Columna común a las tablas de miembros, con el id de la zona
+#[derive(DeriveIden)]
+enum miembros_zona {
+    id_zona,
+}
+
+/// Estructura de la tabla `zonas_codigos_postales`
+#[derive(DeriveIden)]
+enum zonas_codigos_postales {
+    Table,
+    codigo_postal,
+}
+
+/// Estructura de la tabla `zonas_rangos_cp`
+#[derive(DeriveIden)]
+enum zonas_rangos_cp {
+    Table,
+    cp_desde,
+    cp_hasta,
+}
+
+/// Estructura de la tabla `zonas_municipios`
+#[derive(DeriveIden)]
+enum zonas_municipios {
+    Table,
+    id_municipio,
+}
+
+/// Tabla de miembros de una zona: tabla, columnas de valor (forman la clave primaria después de
+/// `id_zona`), nombre de la clave foránea y nombre del índice para buscar por valor.
+type Miembros = (DynIden, Vec<DynIden>, &'static str, &'static str);
+
+/// Tablas de miembros, en el orden en que se crean.
+fn miembros() -> Vec<Miembros> {
+    vec![
+        (
+            zonas_codigos_postales::Table.into_iden(),
+            vec![zonas_codigos_postales::codigo_postal.into_iden()],
+            "fk_zonas_codigos_postales_id_zona",
+            "idx_zonas_codigos_postales_codigo_postal",
+        ),
+        (
+            zonas_rangos_cp::Table.into_iden(),
+            vec![
+                zonas_rangos_cp::cp_desde.into_iden(),
+                zonas_rangos_cp::cp_hasta.into_iden(),
+            ],
+            "fk_zonas_rangos_cp_id_zona",
+            "idx_zonas_rangos_cp_desde_hasta",
+        ),
+        (
+            zonas_municipios::Table.into_iden(),
+            vec![zonas_municipios::id_municipio.into_iden()],
+            "fk_zonas_municipios_id_zona",
+            "idx_zonas_municipios_id_municipio",
+        ),
+    ]
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Crea `zonas` y después las tablas de miembros con sus índices.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mysql = manager.get_database_backend() == DatabaseBackend::MySql;
+        let mut clave = ColumnDef::new(zonas::clave);
+        clave.string_len(100).not_null();
+        // La clave se compara byte a byte: `utf8mb4_unicode_ci` también igualaría acentos
+        if mysql {
+            clave.extra("COLLATE utf8mb4_bin");
+        }
+        let mut tabla = Table::create();
+        tabla
+            .table(zonas::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(zonas::id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(zonas::nombre).string_len(100).not_null())
+            .col(clave)
+            .col(ColumnDef::new(zonas::descripcion).string_len(255).null());
+        // Mismo juego de caracteres que los catálogos (ver m20261018_160000_llaves_foraneas_utf8mb4)
+        if mysql {
+            tabla.character_set("utf8mb4").collate("utf8mb4_unicode_ci");
+        }
+        manager.create_table(tabla).await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("uq_zonas_clave")
+                    .table(zonas::Table)
+                    .col(zonas::clave)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        for (tabla, columnas, llave_foranea, indice) in miembros() {
+            let mut creacion = Table::create();
+            let mut llave_primaria = Index::create();
+            creacion
+                .table(tabla.clone())
+                .if_not_exists()
+                .col(ColumnDef::new(miembros_zona::id_zona).integer().not_null());
+            llave_primaria.col(miembros_zona::id_zona);
+            for columna in &columnas {
+                creacion.col(ColumnDef::new(columna.clone()).integer().not_null());
+                llave_primaria.col(columna.clone());
+            }
+            creacion.primary_key(&mut llave_primaria).foreign_key(
+                ForeignKey::create()
+                    .name(llave_foranea)
+                    .to(zonas::Table, zonas::id)
+                    .from(tabla.clone(), miembros_zona::id_zona)
+                    .on_delete(ForeignKeyAction::Cascade),
+            );
+            manager.create_table(creacion).await?;
+
+            let mut creacion_indice = Index::create();
+            creacion_indice.name(indice).table(tabla);
+            for columna in columnas {
+                creacion_indice.col(columna);
+            }
+            manager.create_index(creacion_indice).await?;
+        }
+        Ok(())
+    }
+
+    /// Elimina las tablas de miembros y después `zonas`.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (tabla, _, _, _) in miembros() {
+            manager
+                .drop_table(Table::drop().table(tabla).to_owned())
+                .await?;
+        }
+        manager
+            .drop_table(Table::drop().table(zonas::Table).to_owned())
+            .await
+    }
+}
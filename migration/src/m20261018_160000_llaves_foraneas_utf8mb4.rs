@@ -0,0 +1,106 @@
+//! # Nombres de claves foráneas y juego de caracteres de los catálogos
+//!
+//! La migración inicial repite nombres de claves foráneas entre tablas, lo que MySQL no permite
+//! (ver `crear_catalogos_mysql`). Esta migración deja los mismos nombres en todos los motores y
+//! el juego de caracteres completo en MySQL.
+//!
+//! ## Cambios
+//!
+//! - Postgres: renombra las claves foráneas repetidas.
+//!   - `cat_codigos_postales.fk_id_estado` a `fk_cp_id_estado`
+//!   - `cat_localidades.fk_id_municipio` a `fk_localidades_id_municipio`
+//!   - `cat_localidades.fk_id_estado` a `fk_localidades_id_estado`
+//!
+//! - MySQL/MariaDB: convierte las tablas de catálogos a `utf8mb4` (`utf8mb4_unicode_ci`) para
+//!   guardar completos los nombres con acentos y ñ. Las claves foráneas ya se crearon con los
+//!   nombres nuevos.
+//!
+//! - SQLite: sin cambios. Renombrar una restricción requiere reconstruir la tabla y SQLite no
+//!   usa los nombres de las claves foráneas.
+//!
+//! Cada paso revisa el estado actual, por lo que la migración puede repetirse si se interrumpe.
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend};
+
+/// Migración que unifica los nombres de las claves foráneas y el juego de caracteres de los catálogos.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Claves foráneas renombradas en Postgres: tabla, nombre en la migración inicial y nombre nuevo.
+const RENOMBRADAS: [(&str, &str, &str); 3] = [
+    ("cat_codigos_postales", "fk_id_estado", "fk_cp_id_estado"),
+    (
+        "cat_localidades",
+        "fk_id_municipio",
+        "fk_localidades_id_municipio",
+    ),
+    (
+        "cat_localidades",
+        "fk_id_estado",
+        "fk_localidades_id_estado",
+    ),
+];
+
+/// Tablas de catálogos que se convierten a `utf8mb4` en MySQL.
+const TABLAS: [&str; 4] = [
+    "cat_estados",
+    "cat_municipios",
+    "cat_codigos_postales",
+    "cat_localidades",
+];
+
+/// Renombra en Postgres la clave foránea `desde` de `tabla` a `hacia`, si todavía existe.
+async fn renombrar<C: ConnectionTrait>(
+    db: &C,
+    tabla: &str,
+    desde: &str,
+    hacia: &str,
+) -> Result<(), DbErr> {
+    db.execute_unprepared(&format!(
+        "DO $$ BEGIN \
+         IF EXISTS (SELECT 1 FROM pg_constraint \
+                    WHERE conname = '{desde}' AND conrelid = '{tabla}'::regclass) THEN \
+         ALTER TABLE {tabla} RENAME CONSTRAINT {desde} TO {hacia}; \
+         END IF; END $$"
+    ))
+    .await?;
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Renombra las claves foráneas en Postgres y convierte las tablas a `utf8mb4` en MySQL.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match manager.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                for (tabla, desde, hacia) in RENOMBRADAS {
+                    renombrar(db, tabla, desde, hacia).await?;
+                }
+            }
+            DatabaseBackend::MySql => {
+                for tabla in TABLAS {
+                    db.execute_unprepared(&format!(
+                        "ALTER TABLE {tabla} CONVERT TO CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci"
+                    ))
+                    .await?;
+                }
+            }
+            DatabaseBackend::Sqlite => {}
+        }
+        Ok(())
+    }
+
+    /// Devuelve a las claves foráneas de Postgres los nombres de la migración inicial.
+    /// En MySQL no se revierte nada: los nombres repetidos no son válidos y `utf8mb4` sólo amplía
+    /// los caracteres que se pueden guardar.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Postgres {
+            let db = manager.get_connection();
+            for (tabla, desde, hacia) in RENOMBRADAS {
+                renombrar(db, tabla, hacia, desde).await?;
+            }
+        }
+        Ok(())
+    }
+}
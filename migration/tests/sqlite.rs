@@ -0,0 +1,137 @@
+//! Corre las migraciones sobre SQLite en memoria y revisa las restricciones que crean.
+use migration::{Migrator, MigratorTrait};
+use sea_orm_migration::sea_orm::{
+    ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+};
+
+/// BD SQLite en memoria con todas las migraciones corridas.
+async fn bd_migrada() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    Migrator::up(&db, None).await.unwrap();
+    db
+}
+
+/// Primera columna de cada renglón de `sql`, como texto.
+async fn textos(db: &DatabaseConnection, sql: &str) -> Vec<String> {
+    db.query_all(Statement::from_string(DbBackend::Sqlite, sql))
+        .await
+        .unwrap()
+        .iter()
+        .map(|renglon| renglon.try_get_by_index(0).unwrap())
+        .collect()
+}
+
+/// Claves foráneas de `tabla` como `columna -> tabla.columna ON DELETE acción`, ordenadas.
+async fn llaves_foraneas(db: &DatabaseConnection, tabla: &str) -> Vec<String> {
+    textos(
+        db,
+        &format!(
+            "SELECT \"from\" || ' -> ' || \"table\" || '.' || \"to\" || ' ON DELETE ' || on_delete \
+             FROM pragma_foreign_key_list('{tabla}') ORDER BY \"from\""
+        ),
+    )
+    .await
+}
+
+#[async_std::test]
+async fn crea_las_claves_foraneas_de_los_catalogos() {
+    let db = bd_migrada().await;
+
+    assert_eq!(
+        llaves_foraneas(&db, "cat_municipios").await,
+        ["id_estado -> cat_estados.id ON DELETE NO ACTION"]
+    );
+    assert_eq!(
+        llaves_foraneas(&db, "cat_codigos_postales").await,
+        [
+            "id_estado -> cat_estados.id ON DELETE NO ACTION",
+            "id_municipio -> cat_municipios.id ON DELETE NO ACTION",
+        ]
+    );
+    assert_eq!(
+        llaves_foraneas(&db, "cat_localidades").await,
+        [
+            "codigo_postal -> cat_codigos_postales.codigo_postal ON DELETE NO ACTION",
+            "id_estado -> cat_estados.id ON DELETE NO ACTION",
+            "id_municipio -> cat_municipios.id ON DELETE NO ACTION",
+        ]
+    );
+    for tabla in [
+        "zonas_codigos_postales",
+        "zonas_rangos_cp",
+        "zonas_municipios",
+    ] {
+        assert_eq!(
+            llaves_foraneas(&db, tabla).await,
+            ["id_zona -> zonas.id ON DELETE CASCADE"]
+        );
+    }
+
+    // Las claves foráneas se aplican: no hay municipio sin estado
+    let resultado = db
+        .execute_unprepared(
+            "INSERT INTO cat_municipios (id, municipio, id_estado) VALUES (1, 'SIN ESTADO', 9)",
+        )
+        .await;
+    assert!(resultado.is_err());
+}
+
+#[async_std::test]
+async fn crea_los_indices_y_las_validaciones_de_rango() {
+    let db = bd_migrada().await;
+
+    let indices = textos(
+        &db,
+        "SELECT name FROM sqlite_master WHERE type = 'index' AND name NOT LIKE 'sqlite_%' \
+         ORDER BY name",
+    )
+    .await;
+    assert_eq!(
+        indices,
+        [
+            "idx_cp_id_municipio",
+            "idx_localidades_codigo_postal",
+            "idx_localidades_id_municipio",
+            "idx_zonas_codigos_postales_codigo_postal",
+            "idx_zonas_municipios_id_municipio",
+            "idx_zonas_rangos_cp_desde_hasta",
+            "uq_estados_estado",
+            "uq_municipios_estado_municipio",
+            "uq_zonas_clave",
+        ]
+    );
+    // Dos nombres con la misma clave chocan
+    db.execute_unprepared(
+        "INSERT INTO zonas (nombre, clave) VALUES ('CDMX centro', 'cdmx centro')",
+    )
+    .await
+    .unwrap();
+    let resultado = db
+        .execute_unprepared(
+            "INSERT INTO zonas (nombre, clave) VALUES ('CDMX Centro', 'cdmx centro')",
+        )
+        .await;
+    assert!(resultado.is_err());
+
+    // Los rangos de las llaves naturales se validan con triggers
+    let resultado = db
+        .execute_unprepared("INSERT INTO cat_estados (id, estado) VALUES (33, 'FUERA DE RANGO')")
+        .await;
+    assert!(resultado.is_err());
+    db.execute_unprepared("INSERT INTO cat_estados (id, estado) VALUES (9, 'CIUDAD DE MEXICO')")
+        .await
+        .unwrap();
+}
+
+#[async_std::test]
+async fn revierte_todas_las_migraciones() {
+    let db = bd_migrada().await;
+    Migrator::reset(&db).await.unwrap();
+
+    let tablas = textos(
+        &db,
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .await;
+    assert_eq!(tablas, ["seaql_migrations"]);
+}
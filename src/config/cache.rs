@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// Número máximo de códigos postales en la caché de búsquedas (variable `CACHE_CP_CAPACIDAD`).
+/// Por defecto es 10000, suficiente para la tercera parte de los CP del país; 0 desactiva la caché.
+pub fn capacidad_cache_cp() -> Result<usize, String> {
+    match std::env::var("CACHE_CP_CAPACIDAD") {
+        Ok(valor) => valor
+            .parse()
+            .map_err(|_| format!("CACHE_CP_CAPACIDAD inválida: {}", valor)),
+        Err(_) => Ok(10_000),
+    }
+}
+
+/// Tiempo que una búsqueda permanece en caché (variable `CACHE_CP_TTL_SEGUNDOS`).
+/// Por defecto es una hora; la caché además se vacía en cada recarga de catálogos.
+pub fn ttl_cache_cp() -> Result<Duration, String> {
+    match std::env::var("CACHE_CP_TTL_SEGUNDOS") {
+        Ok(valor) => valor
+            .parse()
+            .map(Duration::from_secs)
+            .map_err(|_| format!("CACHE_CP_TTL_SEGUNDOS inválida: {}", valor)),
+        Err(_) => Ok(Duration::from_secs(3600)),
+    }
+}
@@ -0,0 +1,80 @@
+use std::str::FromStr;
+
+/// Directorio desde donde se leen los CSV de catálogos.
+/// Se toma de la variable `CATALOGOS_DIR` y por defecto es `./catalogos`.
+pub fn directorio_catalogos() -> String {
+    std::env::var("CATALOGOS_DIR").unwrap_or_else(|_| "./catalogos".to_string())
+}
+
+/// Política de carga de catálogos al iniciar el servicio (variable `CARGA_CATALOGOS`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoliticaCarga {
+    /// Vacía y vuelve a cargar los catálogos en cada arranque (`siempre`)
+    Siempre,
+    /// Carga únicamente las tablas vacías (`si_vacio`, por defecto)
+    SiVacio,
+    /// No lee los CSV; se usan los catálogos que ya estén en la BD (`nunca`)
+    Nunca,
+}
+
+impl FromStr for PoliticaCarga {
+    type Err = String;
+
+    fn from_str(valor: &str) -> Result<Self, Self::Err> {
+        match valor {
+            "siempre" => Ok(PoliticaCarga::Siempre),
+            "si_vacio" => Ok(PoliticaCarga::SiVacio),
+            "nunca" => Ok(PoliticaCarga::Nunca),
+            otro => Err(format!(
+                "CARGA_CATALOGOS inválida: {} (valores válidos: siempre, si_vacio, nunca)",
+                otro
+            )),
+        }
+    }
+}
+
+/// Lee la política de carga de la variable `CARGA_CATALOGOS`. Por defecto es `si_vacio`.
+pub fn politica_carga() -> Result<PoliticaCarga, String> {
+    match std::env::var("CARGA_CATALOGOS") {
+        Ok(valor) => valor.parse(),
+        Err(_) => Ok(PoliticaCarga::SiVacio),
+    }
+}
+
+/// Origen de los datos con los que se responden las búsquedas (variable `FUENTE_DATOS`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FuenteDatos {
+    /// Tablas de la BD de `DATABASE_URL` (`base_datos`, por defecto; también se acepta `postgres`)
+    BaseDatos,
+    /// Índices en memoria cargados desde los CSV o un snapshot, sin BD (`memoria`)
+    Memoria,
+}
+
+impl FromStr for FuenteDatos {
+    type Err = String;
+
+    fn from_str(valor: &str) -> Result<Self, Self::Err> {
+        match valor {
+            "base_datos" | "postgres" => Ok(FuenteDatos::BaseDatos),
+            "memoria" => Ok(FuenteDatos::Memoria),
+            otro => Err(format!(
+                "FUENTE_DATOS inválida: {} (valores válidos: base_datos, memoria)",
+                otro
+            )),
+        }
+    }
+}
+
+/// Lee la fuente de datos de la variable `FUENTE_DATOS`. Por defecto es `base_datos`.
+pub fn fuente_datos() -> Result<FuenteDatos, String> {
+    match std::env::var("FUENTE_DATOS") {
+        Ok(valor) => valor.parse(),
+        Err(_) => Ok(FuenteDatos::BaseDatos),
+    }
+}
+
+/// Snapshot JSON desde el que se cargan los catálogos en memoria (variable `SNAPSHOT_CATALOGOS`).
+/// Si no se fija, los índices se construyen desde los CSV de [`directorio_catalogos`].
+pub fn snapshot_catalogos() -> Option<String> {
+    std::env::var("SNAPSHOT_CATALOGOS").ok()
+}
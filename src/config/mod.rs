@@ -1,2 +1,5 @@
+pub mod base_datos;
+pub mod cache;
+pub mod catalogos;
 pub mod cors;
 pub mod swagger;
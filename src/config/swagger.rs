@@ -1,3 +1,4 @@
+use actix_web::{http::header, web, HttpResponse};
 use utoipa::{
     openapi::{
         security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
@@ -6,7 +7,7 @@ use utoipa::{
     Modify, OpenApi,
 };
 
-use crate::{routes, types};
+use crate::{middleware::peticion::idioma_actual, routes, types};
 
 // Documentación de la API
 #[derive(OpenApi)]
@@ -16,11 +17,49 @@ use crate::{routes, types};
     ),
     paths(
         routes::catalogos::busqueda_cp,
+        routes::catalogos::municipios_estado,
+        routes::catalogos::codigos_postales_municipio,
+        routes::catalogos::geocodificacion_inversa,
+        routes::catalogos::municipio_coordenadas,
+        routes::catalogos::distancia,
+        routes::catalogos::matriz_distancias,
+        routes::catalogos::codigos_postales_cercanos,
+        routes::zonas::zonas_cp,
+        routes::admin_catalogos::recargar,
+        routes::admin_catalogos::estado_recarga,
+        routes::admin_catalogos::estadisticas_cache,
+        routes::zonas::listar_zonas,
+        routes::zonas::crear_zona,
+        routes::zonas::consultar_zona,
+        routes::zonas::actualizar_zona,
+        routes::zonas::eliminar_zona,
         ),
     components(
         schemas(
             types::catalogos::CPPayload,
             types::catalogos::CPResponse,
+            types::geojson::ColeccionGeoJson,
+            types::geojson::EntidadGeoJson,
+            types::geojson::GeometriaGeoJson,
+            types::geojson::Formato,
+            types::catalogos::ResultadoGeocodificacion,
+            types::catalogos::MunicipioCoordenadasResponse,
+            types::catalogos::DistanciaResponse,
+            types::catalogos::MatrizDistanciasPayload,
+            types::catalogos::MatrizDistanciasResponse,
+            types::catalogos::CercanosResponse,
+            types::catalogos::OrdenCercanos,
+            types::catalogos::EstadisticasCache,
+            types::catalogos::EstadoRecarga,
+            types::catalogos::TrabajoRecarga,
+            types::zonas::RangoCP,
+            types::zonas::ZonaPayload,
+            types::zonas::Zona,
+            types::zonas::MotivoZona,
+            types::zonas::ZonaCP,
+            types::zonas::ZonasCPResponse,
+            types::errores::ProblemDetails,
+
         )
     ),
     modifiers(&SecurityAddOn)
@@ -43,3 +82,662 @@ impl Modify for SecurityAddOn {
         );
     }
 }
+
+/// Traducción al inglés de una operación de la documentación.
+struct OperacionEn {
+    /// `operationId` de la operación (el nombre de la función de la ruta)
+    operacion: &'static str,
+    descripcion: &'static str,
+    /// Descripción de cada parámetro, por nombre
+    parametros: &'static [(&'static str, &'static str)],
+    /// Descripción de cada respuesta, por estado HTTP
+    respuestas: &'static [(&'static str, &'static str)],
+}
+
+/// Traducción al inglés de un esquema de la documentación.
+struct EsquemaEn {
+    /// Nombre del esquema en `components.schemas`
+    esquema: &'static str,
+    descripcion: &'static str,
+    /// Descripciones dentro del esquema, por su ruta relativa (p. ej. `properties/cp`)
+    campos: &'static [(&'static str, &'static str)],
+}
+
+/// Respuestas que se repiten en varias operaciones
+const EN_ERROR_INTERNO: &str = "Internal server error";
+const EN_TOKEN_INVALIDO: &str = "The token is not valid or has expired";
+const EN_SIN_SCOPE_ADMIN: &str = "The token lacks the admin scope";
+const EN_CP_INVALIDO: &str = "Bad request, invalid postal code format";
+const EN_CP_NO_ENCONTRADO: &str = "Postal code not found";
+
+/// Traducciones al inglés de las etiquetas, por nombre.
+const ETIQUETAS_EN: &[(&str, &str)] = &[("Localidades API", "Mini location service")];
+
+/// Traducciones al inglés de las operaciones, por `operationId`, en el orden de [`ApiDoc`].
+const OPERACIONES_EN: &[OperacionEn] = &[
+    OperacionEn {
+        operacion: "busqueda_cp",
+        descripcion: "Get state, municipality and localities from a postal code",
+        parametros: &[
+            ("cp", "Postal code whose state, municipality and localities are requested"),
+            ("formato", "Response format (`json` or `geojson`). Defaults to the `Accept` header"),
+        ],
+        respuestas: &[
+            ("200", "The postal code is valid and has associated data"),
+            ("400", EN_CP_INVALIDO),
+            ("404", EN_CP_NO_ENCONTRADO),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "municipios_estado",
+        descripcion: "Get the municipalities of a state as a GeoJSON FeatureCollection, with their boundaries if loaded",
+        parametros: &[
+            ("id_estado", "State id (INEGI key)"),
+        ],
+        respuestas: &[
+            ("200", "Municipalities of the state"),
+            ("404", "State not found"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "codigos_postales_municipio",
+        descripcion: "Get the postal codes of a municipality as a GeoJSON FeatureCollection, with their boundaries if loaded or else their centroid",
+        parametros: &[
+            ("id_municipio", "Municipality id"),
+        ],
+        respuestas: &[
+            ("200", "Postal codes of the municipality"),
+            ("404", "Municipality not found"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "geocodificacion_inversa",
+        descripcion: "Get the postal codes and localities closest to a location (reverse geocoding)",
+        parametros: &[
+            ("lat", "Latitude in decimal degrees (WGS84)"),
+            ("lon", "Longitude in decimal degrees (WGS84)"),
+            ("n", "Number of results (default 5, at most 50)"),
+        ],
+        respuestas: &[
+            ("200", "Closest centroids, nearest first"),
+            ("400", "Coordinates out of range or not numeric"),
+        ],
+    },
+    OperacionEn {
+        operacion: "municipio_coordenadas",
+        descripcion: "Get the state and municipality (and the postal code, if postal code boundaries are loaded) whose polygon contains a location",
+        parametros: &[
+            ("lat", "Latitude in decimal degrees (WGS84)"),
+            ("lon", "Longitude in decimal degrees (WGS84)"),
+        ],
+        respuestas: &[
+            ("200", "Municipality containing the location"),
+            ("400", "Coordinates out of range or not numeric"),
+            ("404", "No municipality contains the location"),
+            ("500", EN_ERROR_INTERNO),
+            ("503", "No municipality boundaries were loaded"),
+        ],
+    },
+    OperacionEn {
+        operacion: "distancia",
+        descripcion: "Compute the great-circle distance between the centroids of two postal codes",
+        parametros: &[
+            ("origen", "Origin postal code"),
+            ("destino", "Destination postal code"),
+        ],
+        respuestas: &[
+            ("200", "Distance in meters between the centroids"),
+            ("400", EN_CP_INVALIDO),
+            ("404", EN_CP_NO_ENCONTRADO),
+            ("422", "One of the postal codes has no coordinates"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "matriz_distancias",
+        descripcion: "Compute the distance from each origin postal code to each destination postal code (up to 50 of each)",
+        parametros: &[],
+        respuestas: &[
+            ("200", "Distances in meters, one row per origin"),
+            ("400", "Empty or too large origin or destination list, or invalid postal code format"),
+            ("404", EN_CP_NO_ENCONTRADO),
+            ("422", "One of the postal codes has no coordinates"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "codigos_postales_cercanos",
+        descripcion: "Get the postal codes whose centroid is within a radius of a postal code centroid, with their distance, municipality and state",
+        parametros: &[
+            ("cp", "Center postal code"),
+            ("radio_km", "Search radius in kilometers (greater than 0 and at most 100)"),
+            ("orden", "Sort criterion (default `distancia`)"),
+            ("descendente", "If `true` the order is reversed"),
+            ("pagina", "Page to return, starting at 1 (default 1)"),
+            ("por_pagina", "Results per page (default 20, at most 100)"),
+        ],
+        respuestas: &[
+            ("200", "Page of postal codes within the radius"),
+            ("400", "Radius out of range or invalid postal code format"),
+            ("404", EN_CP_NO_ENCONTRADO),
+            ("422", "The postal code has no coordinates"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "zonas_cp",
+        descripcion: "Get the delivery zones containing a postal code, through the postal code itself, a range or its municipality",
+        parametros: &[
+            ("cp", "Postal code to look up"),
+        ],
+        respuestas: &[
+            ("200", "Zones containing the postal code; there may be none"),
+            ("400", EN_CP_INVALIDO),
+            ("404", EN_CP_NO_ENCONTRADO),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "recargar",
+        descripcion: "Empties and reloads the catalogs in the background, from a server directory or from CSV files uploaded as multipart/form-data",
+        parametros: &[
+            ("directorio", "Subdirectory of `CATALOGOS_DIR` with the CSV files, relative and without `..`; defaults to `CATALOGOS_DIR` itself"),
+        ],
+        respuestas: &[
+            ("202", "The reload started; check its progress with the job_id"),
+            ("400", "The uploaded files are not valid catalogs or the directory is not inside CATALOGOS_DIR"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("409", "A reload is already in progress"),
+        ],
+    },
+    OperacionEn {
+        operacion: "estado_recarga",
+        descripcion: "Get the status of a catalog reload",
+        parametros: &[
+            ("job_id", "Id returned when the reload started"),
+        ],
+        respuestas: &[
+            ("200", "Current status of the reload"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("404", "The job does not exist"),
+        ],
+    },
+    OperacionEn {
+        operacion: "estadisticas_cache",
+        descripcion: "Get the status of the postal code lookup cache",
+        parametros: &[],
+        respuestas: &[
+            ("200", "Cache capacity, entries and hit/miss counters"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+        ],
+    },
+    OperacionEn {
+        operacion: "listar_zonas",
+        descripcion: "List the delivery zones with their postal codes, ranges and municipalities",
+        parametros: &[],
+        respuestas: &[
+            ("200", "Zones sorted by id"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "crear_zona",
+        descripcion: "Create a delivery zone from postal codes, postal code ranges and municipalities",
+        parametros: &[],
+        respuestas: &[
+            ("201", "Zone created"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("409", "A zone with the same name already exists"),
+            ("422", "The zone data is not valid"),
+            ("500", EN_ERROR_INTERNO),
+            ("501", "Zones cannot be modified with FUENTE_DATOS=memoria"),
+        ],
+    },
+    OperacionEn {
+        operacion: "consultar_zona",
+        descripcion: "Get a delivery zone with its postal codes, ranges and municipalities",
+        parametros: &[
+            ("id_zona", "Zone id"),
+        ],
+        respuestas: &[
+            ("200", "Zone with its members"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("404", "The zone does not exist"),
+            ("500", EN_ERROR_INTERNO),
+        ],
+    },
+    OperacionEn {
+        operacion: "actualizar_zona",
+        descripcion: "Replace the name, description and members of a delivery zone",
+        parametros: &[
+            ("id_zona", "Zone id"),
+        ],
+        respuestas: &[
+            ("200", "Zone updated"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("404", "The zone does not exist"),
+            ("409", "A zone with the same name already exists"),
+            ("422", "The zone data is not valid"),
+            ("500", EN_ERROR_INTERNO),
+            ("501", "Zones cannot be modified with FUENTE_DATOS=memoria"),
+        ],
+    },
+    OperacionEn {
+        operacion: "eliminar_zona",
+        descripcion: "Delete a delivery zone with its members",
+        parametros: &[
+            ("id_zona", "Zone id"),
+        ],
+        respuestas: &[
+            ("204", "Zone deleted"),
+            ("401", EN_TOKEN_INVALIDO),
+            ("403", EN_SIN_SCOPE_ADMIN),
+            ("404", "The zone does not exist"),
+            ("500", EN_ERROR_INTERNO),
+            ("501", "Zones cannot be modified with FUENTE_DATOS=memoria"),
+        ],
+    },
+];
+
+/// Traducciones al inglés de los esquemas, por nombre.
+const ESQUEMAS_EN: &[EsquemaEn] = &[
+    EsquemaEn {
+        esquema: "CPPayload",
+        descripcion: "Payload used to request the data of a postal code.",
+        campos: &[
+            ("properties/cp", "Postal code to look up"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "CPResponse",
+        descripcion: "Data associated with a postal code: state, municipality and localities.",
+        campos: &[
+            ("properties/latitud", "Latitude of the postal code centroid (decimal degrees, `null` if unknown)"),
+            ("properties/longitud", "Longitude of the postal code centroid (decimal degrees, `null` if unknown)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "CatalogoIdCadena",
+        descripcion: "Catalog entry with an id and a text value.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "CercanosResponse",
+        descripcion: "Page of postal codes within a radius.",
+        campos: &[
+            ("properties/cp", "Center postal code"),
+            ("properties/total", "Postal codes within the radius, across all pages"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "CodigoPostalCercano",
+        descripcion: "Postal code within the search radius.",
+        campos: &[
+            ("properties/distancia_metros", "Surface distance from the center postal code centroid"),
+            ("properties/latitud", "Latitude of the centroid"),
+            ("properties/longitud", "Longitude of the centroid"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "ColeccionGeoJson",
+        descripcion: "GeoJSON `FeatureCollection`.",
+        campos: &[
+            ("properties/type", "Always `FeatureCollection`"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "DistanciaResponse",
+        descripcion: "Great-circle distance between the centroids of two postal codes.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "EntidadGeoJson",
+        descripcion: "GeoJSON `Feature`.",
+        campos: &[
+            ("properties/geometry/oneOf/1", "`null` if neither the centroid nor the boundaries are known"),
+            ("properties/properties", "Catalog fields"),
+            ("properties/type", "Always `Feature`"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "EstadisticasCache",
+        descripcion: "Status of the postal code lookup cache.",
+        campos: &[
+            ("properties/aciertos", "Lookups answered from the cache"),
+            ("properties/capacidad", "Maximum number of cached postal codes (0 if disabled)"),
+            ("properties/entradas", "Postal codes currently cached"),
+            ("properties/fallos", "Lookups that queried the database"),
+            ("properties/ttl_segundos", "Seconds a lookup stays cached"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "EstadoRecarga",
+        descripcion: "Status of a catalog reload job.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "Formato",
+        descripcion: "Response format.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "GeometriaGeoJson",
+        descripcion: "Geometry of a feature: the centroid as a point or the boundaries as a multipolygon.",
+        campos: &[
+            ("oneOf/0", "`[longitude, latitude]`"),
+            ("oneOf/1", "Polygons with their rings of `[longitude, latitude]` vertices; the first ring of each polygon is the exterior and the rest are holes"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "LocalidadCP",
+        descripcion: "Locality of a postal code, with the locality centroid.",
+        campos: &[
+            ("properties/latitud", "Latitude of the centroid (decimal degrees, `null` if unknown)"),
+            ("properties/longitud", "Longitude of the centroid (decimal degrees, `null` if unknown)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "MatrizDistanciasPayload",
+        descripcion: "Payload to compute the distances between several origins and destinations.",
+        campos: &[
+            ("properties/destinos", "Destination postal codes (matrix columns)"),
+            ("properties/origenes", "Origin postal codes (matrix rows)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "MatrizDistanciasResponse",
+        descripcion: "Distance matrix between postal codes.",
+        campos: &[
+            ("properties/distancias_metros", "Distance in meters from each origin (row) to each destination (column)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "MotivoZona",
+        descripcion: "Zone member that includes the postal code.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "MunicipioCoordenadasResponse",
+        descripcion: "Municipality whose polygon contains a location.",
+        campos: &[
+            ("properties/cp", "Postal code whose polygon contains the location; `null` if no postal code boundaries were loaded or none contains it"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "OrdenCercanos",
+        descripcion: "Sort criterion for nearby postal codes.",
+        campos: &[],
+    },
+    EsquemaEn {
+        esquema: "ProblemDetails",
+        descripcion: "Body of error responses (RFC 7807, problem details).",
+        campos: &[
+            ("properties/codigo", "Stable error code"),
+            ("properties/detail", "Description of this occurrence of the error"),
+            ("properties/request_id", "Request id, same as the `X-Request-Id` header"),
+            ("properties/status", "HTTP status of the response"),
+            ("properties/title", "Short summary of the error type; does not change between occurrences"),
+            ("properties/type", "URI identifying the error type (`urn:ubicacion:error:{codigo}`)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "RangoCP",
+        descripcion: "Postal code range, inclusive on both ends.",
+        campos: &[
+            ("properties/desde", "First postal code of the range"),
+            ("properties/hasta", "Last postal code of the range"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "ResultadoGeocodificacion",
+        descripcion: "Postal code or locality close to a location.",
+        campos: &[
+            ("properties/distancia_metros", "Surface distance from the requested location"),
+            ("properties/latitud", "Latitude of the centroid"),
+            ("properties/localidad/oneOf/1", "Locality (colonia) whose centroid is the close one; `null` for the postal code centroid"),
+            ("properties/longitud", "Longitude of the centroid"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "TrabajoRecarga",
+        descripcion: "Catalog reload job running in the background.",
+        campos: &[
+            ("properties/advertencias", "Steps after replacing the catalogs that failed; the reload completes with the new catalogs and those steps keep their previous data"),
+            ("properties/error", "Error description if the reload failed"),
+            ("properties/job_id", "Id to check the status of the job"),
+            ("properties/origen", "Directory the CSV files are read from, or `archivos subidos` (uploaded files)"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "Zona",
+        descripcion: "Delivery zone with its members, sorted.",
+        campos: &[
+            ("properties/id", "Zone id"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "ZonaCP",
+        descripcion: "Zone containing a postal code.",
+        campos: &[
+            ("properties/motivos", "Why the postal code belongs to the zone"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "ZonaPayload",
+        descripcion: "Payload to create or replace a zone. At least one postal code, range or municipality is required.",
+        campos: &[
+            ("properties/codigos_postales", "Individual postal codes"),
+            ("properties/descripcion", "Free-form zone description"),
+            ("properties/municipios", "Municipality ids; they cover all of their postal codes"),
+            ("properties/nombre", "Zone name, unique"),
+            ("properties/rangos", "Postal code ranges"),
+        ],
+    },
+    EsquemaEn {
+        esquema: "ZonasCPResponse",
+        descripcion: "Zones containing a postal code, sorted by id.",
+        campos: &[],
+    },
+];
+
+/// Documentación de la API en inglés: la misma especificación que [`ApiDoc`] con las
+/// descripciones traducidas según [`OPERACIONES_EN`], [`ESQUEMAS_EN`] y [`ETIQUETAS_EN`].
+pub fn api_doc_en() -> utoipa::openapi::OpenApi {
+    let mut valor =
+        serde_json::to_value(ApiDoc::openapi()).expect("Error al serializar la documentación");
+    for (ruta, en) in traducciones_en(&valor) {
+        if let Some(texto) = valor.pointer_mut(&ruta) {
+            *texto = serde_json::Value::from(en);
+        }
+    }
+    serde_json::from_value(valor).expect("Error al construir la documentación en inglés")
+}
+
+/// Ruta JSON (RFC 6901) de cada descripción traducida de `doc`, con su texto en inglés.
+/// Las operaciones se buscan por `operationId` y los parámetros por nombre, por lo que no
+/// dependen del texto en español.
+fn traducciones_en(doc: &serde_json::Value) -> Vec<(String, &'static str)> {
+    let escapar = |segmento: &str| segmento.replace('~', "~0").replace('/', "~1");
+    let mut traducciones = Vec::new();
+
+    if let Some(etiquetas) = doc["tags"].as_array() {
+        for (indice, etiqueta) in etiquetas.iter().enumerate() {
+            if let Some((_, en)) = ETIQUETAS_EN
+                .iter()
+                .find(|(nombre, _)| etiqueta["name"] == *nombre)
+            {
+                traducciones.push((format!("/tags/{}/description", indice), *en));
+            }
+        }
+    }
+
+    if let Some(rutas) = doc["paths"].as_object() {
+        for (ruta, metodos) in rutas {
+            for (metodo, operacion) in metodos.as_object().into_iter().flatten() {
+                let Some(traduccion) = OPERACIONES_EN
+                    .iter()
+                    .find(|traduccion| operacion["operationId"] == traduccion.operacion)
+                else {
+                    continue;
+                };
+                let base = format!("/paths/{}/{}", escapar(ruta), metodo);
+                traducciones.push((format!("{}/description", base), traduccion.descripcion));
+                if let Some(parametros) = operacion["parameters"].as_array() {
+                    for (indice, parametro) in parametros.iter().enumerate() {
+                        if let Some((_, en)) = traduccion
+                            .parametros
+                            .iter()
+                            .find(|(nombre, _)| parametro["name"] == *nombre)
+                        {
+                            traducciones
+                                .push((format!("{}/parameters/{}/description", base, indice), *en));
+                        }
+                    }
+                }
+                for (estado, en) in traduccion.respuestas {
+                    traducciones.push((format!("{}/responses/{}/description", base, estado), *en));
+                }
+            }
+        }
+    }
+
+    for esquema in ESQUEMAS_EN {
+        let base = format!("/components/schemas/{}", escapar(esquema.esquema));
+        traducciones.push((format!("{}/description", base), esquema.descripcion));
+        for (campo, en) in esquema.campos {
+            traducciones.push((format!("{}/{}/description", base, campo), *en));
+        }
+    }
+    traducciones
+}
+
+/// Documentación de la API en español y en inglés, compartida entre workers.
+pub struct DocumentacionApi {
+    pub es: utoipa::openapi::OpenApi,
+    pub en: utoipa::openapi::OpenApi,
+}
+
+impl Default for DocumentacionApi {
+    fn default() -> Self {
+        DocumentacionApi {
+            es: ApiDoc::openapi(),
+            en: api_doc_en(),
+        }
+    }
+}
+
+/// Responde la documentación en el idioma de `Accept-Language` (español por defecto).
+pub async fn documentacion_api(documentacion: web::Data<DocumentacionApi>) -> HttpResponse {
+    let idioma = idioma_actual();
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_LANGUAGE, idioma.etiqueta()))
+        .insert_header((header::VARY, "Accept-Language"))
+        .json(idioma.elegir(&documentacion.es, &documentacion.en))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Rutas JSON de todas las descripciones no vacías de `valor`.
+    fn descripciones(valor: &serde_json::Value, ruta: String, rutas: &mut BTreeSet<String>) {
+        match valor {
+            serde_json::Value::Object(campos) => {
+                for (clave, campo) in campos {
+                    let ruta = format!("{}/{}", ruta, clave.replace('~', "~0").replace('/', "~1"));
+                    match campo {
+                        serde_json::Value::String(texto)
+                            if clave == "description" && !texto.is_empty() =>
+                        {
+                            rutas.insert(ruta);
+                        }
+                        _ => descripciones(campo, ruta, rutas),
+                    }
+                }
+            }
+            serde_json::Value::Array(elementos) => {
+                for (indice, elemento) in elementos.iter().enumerate() {
+                    descripciones(elemento, format!("{}/{}", ruta, indice), rutas);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn todas_las_operaciones_tienen_traduccion() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let sin_traduccion: Vec<_> = doc["paths"]
+            .as_object()
+            .unwrap()
+            .values()
+            .flat_map(|metodos| metodos.as_object().unwrap().values())
+            .filter_map(|operacion| operacion["operationId"].as_str())
+            .filter(|id| !OPERACIONES_EN.iter().any(|t| t.operacion == *id))
+            .collect();
+        assert!(
+            sin_traduccion.is_empty(),
+            "Operaciones sin traducción: {:?}",
+            sin_traduccion
+        );
+    }
+
+    #[test]
+    fn todas_las_descripciones_tienen_traduccion() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut rutas = BTreeSet::new();
+        descripciones(&doc, String::new(), &mut rutas);
+        let traducidas: BTreeSet<String> = traducciones_en(&doc)
+            .into_iter()
+            .map(|(ruta, _)| ruta)
+            .collect();
+        let pendientes: Vec<_> = rutas.difference(&traducidas).collect();
+        assert!(
+            pendientes.is_empty(),
+            "Descripciones sin traducción: {:?}",
+            pendientes
+        );
+    }
+
+    #[test]
+    fn no_hay_traducciones_sin_descripcion() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let traducciones = traducciones_en(&doc);
+        let sobrantes: Vec<_> = traducciones
+            .iter()
+            .filter(|(ruta, _)| doc.pointer(ruta).is_none())
+            .collect();
+        assert!(
+            sobrantes.is_empty(),
+            "Traducciones sin descripción: {:?}",
+            sobrantes
+        );
+
+        let entradas = ETIQUETAS_EN.len()
+            + OPERACIONES_EN
+                .iter()
+                .map(|t| 1 + t.parametros.len() + t.respuestas.len())
+                .sum::<usize>()
+            + ESQUEMAS_EN
+                .iter()
+                .map(|t| 1 + t.campos.len())
+                .sum::<usize>();
+        assert_eq!(
+            traducciones.len(),
+            entradas,
+            "Hay traducciones de operaciones o parámetros que no existen"
+        );
+    }
+}
@@ -0,0 +1,483 @@
+//! # Controladores para endpoints administrativos de catálogos
+//! En este módulo se incluyen controladores de endpoints con las siguientes funcionalidades:
+//! - Recargar los catálogos en segundo plano (`recargar_catalogos_controller`)
+//! - Resolver el directorio de una recarga dentro de `CATALOGOS_DIR` (`directorio_recarga`)
+//! - Guardar los CSV subidos para una recarga (`guardar_catalogos_subidos`)
+//! - Consultar el estado de una recarga (`estado_recarga_controller`)
+//! - Consultar el estado de la caché de búsquedas por CP (`estadisticas_cache_controller`)
+
+use crate::{
+    services::{
+        cache_cp::CacheCodigosPostales, indice_geografico::IndiceGeografico,
+        limites_geograficos::LimitesGeograficos, recarga_catalogos::TrabajosRecarga,
+        repositorio::RepositorioUbicacion,
+    },
+    types::{catalogos::*, errores::UbicacionError},
+};
+use actix_multipart::Multipart;
+use actix_web::{rt, web};
+use futures_util::StreamExt;
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Catálogos que se aceptan al subir archivos (las coordenadas son opcionales)
+const CATALOGOS: [&str; 6] = [
+    "cat_estados",
+    "cat_municipios",
+    "cat_codigos_postales",
+    "cat_localidades",
+    "coordenadas_codigos_postales",
+    "coordenadas_localidades",
+];
+
+/// Extensiones que se aceptan al subir catálogos (sin comprimir, gzip o zstd)
+const EXTENSIONES: [&str; 3] = [".csv", ".csv.gz", ".csv.zst"];
+
+/// Límites geográficos que se aceptan al subir archivos (opcionales)
+const LIMITES: [&str; 2] = ["limites_municipios", "limites_codigos_postales"];
+
+/// Extensiones que se aceptan al subir límites (sin comprimir, gzip o zstd)
+const EXTENSIONES_LIMITES: [&str; 3] = [".geojson", ".geojson.gz", ".geojson.zst"];
+
+/// Inicia la recarga de los catálogos en segundo plano y devuelve el trabajo registrado.
+/// Si la recarga termina bien se reconstruye el índice geográfico, se vacía la caché de búsquedas por CP
+/// y se leen los límites geográficos del directorio (si no los trae, se conservan los anteriores).
+/// Si el índice no se puede reconstruir, el trabajo se completa con una advertencia y el índice
+/// conserva los centroides anteriores. Si la recarga entra en pánico, el trabajo queda `fallida`.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `trabajos`: Registro compartido de trabajos de recarga
+/// - `cache`: Caché de búsquedas por código postal
+/// - `indice`: Índice geográfico de la geocodificación inversa
+/// - `limites`: Límites geográficos de municipios y códigos postales
+/// - `directorio`: Directorio con los CSV de los catálogos
+/// - `temporal`: Si es `true`, el directorio se elimina al terminar la recarga (archivos subidos)
+///
+/// ## Retorno
+/// - [`TrabajoRecarga`]: Trabajo en estado `en_proceso`, con el id para consultar su avance
+/// - `Err(RecargaEnProceso)`: Ya hay otra recarga en proceso; si `temporal`, el directorio se elimina
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn recargar_catalogos_controller(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    trabajos: web::Data<TrabajosRecarga>,
+    cache: web::Data<CacheCodigosPostales>,
+    indice: web::Data<IndiceGeografico>,
+    limites: web::Data<LimitesGeograficos>,
+    directorio: PathBuf,
+    temporal: bool,
+) -> Result<TrabajoRecarga, UbicacionError> {
+    let origen = if temporal {
+        "archivos subidos".to_string()
+    } else {
+        directorio.display().to_string()
+    };
+    let Some(trabajo) = trabajos.registrar(origen) else {
+        if temporal {
+            let _ = std::fs::remove_dir_all(&directorio);
+        }
+        return Err(UbicacionError::RecargaEnProceso);
+    };
+
+    let job_id = trabajo.job_id;
+    let limpieza = directorio.clone();
+    let recarga = async move {
+        let ruta = directorio.to_string_lossy().to_string();
+        let resultado = repositorio.recargar(&ruta).await;
+        let mut advertencias = Vec::new();
+        if resultado.is_ok() {
+            cache.invalidar();
+            // Los catálogos ya se reemplazaron: un índice o límites desactualizados no revierten
+            // la recarga
+            if let Err(e) = indice.reconstruir(repositorio.get_ref()).await {
+                advertencias.push(format!("No se reconstruyó el índice geográfico: {}", e));
+            }
+            if let Err(e) = limites.recargar(&ruta).await {
+                advertencias.push(format!("No se leyeron los límites geográficos: {}", e));
+            }
+        }
+        (resultado, advertencias)
+    };
+    rt::spawn(async move {
+        let (resultado, advertencias) = esperar_recarga(recarga).await;
+        if temporal {
+            let _ = std::fs::remove_dir_all(&limpieza);
+        }
+        trabajos.finalizar(job_id, resultado, advertencias);
+    });
+
+    Ok(trabajo)
+}
+
+/// Corre `recarga` en su propia tarea y devuelve su resultado y advertencias. Si la tarea entra en
+/// pánico devuelve [`UbicacionError::RecargaInterrumpida`], para que el trabajo no quede
+/// `en_proceso` y bloquee las recargas siguientes.
+async fn esperar_recarga(
+    recarga: impl Future<Output = (Result<(), UbicacionError>, Vec<String>)> + 'static,
+) -> (Result<(), UbicacionError>, Vec<String>) {
+    match rt::spawn(recarga).await {
+        Ok(terminada) => terminada,
+        Err(e) => (
+            Err(UbicacionError::RecargaInterrumpida(e.to_string())),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Resuelve el directorio desde el que se recargan los catálogos. Sin `subdirectorio` se usa
+/// `catalogos_dir`; si se indica, debe ser una ruta relativa sin `..` que, después de resolver
+/// enlaces simbólicos, quede dentro de `catalogos_dir`.
+///
+/// ## Parámetros
+/// - `catalogos_dir`: Directorio de catálogos configurado (`CATALOGOS_DIR`)
+/// - `subdirectorio`: Subdirectorio pedido en `?directorio=`
+///
+/// ## Retorno
+/// - `PathBuf` del directorio a recargar
+/// - `Err(DirectorioRecargaInvalido)`: El subdirectorio es absoluto, tiene `..`, no existe o
+///   queda fuera de `catalogos_dir`
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub fn directorio_recarga(
+    catalogos_dir: &Path,
+    subdirectorio: Option<&str>,
+) -> Result<PathBuf, UbicacionError> {
+    let Some(subdirectorio) = subdirectorio else {
+        return Ok(catalogos_dir.to_path_buf());
+    };
+    let invalido = || UbicacionError::DirectorioRecargaInvalido(subdirectorio.to_string());
+
+    let relativo = Path::new(subdirectorio);
+    if !relativo
+        .components()
+        .all(|componente| matches!(componente, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(invalido());
+    }
+    let base = catalogos_dir.canonicalize().map_err(|_| invalido())?;
+    let directorio = base.join(relativo).canonicalize().map_err(|_| invalido())?;
+    if !directorio.starts_with(&base) || !directorio.is_dir() {
+        return Err(invalido());
+    }
+    Ok(directorio)
+}
+
+/// Guarda en un directorio temporal los CSV recibidos en una petición multipart.
+/// Sólo se aceptan los archivos con nombre de catálogo (`cat_estados`, `cat_municipios`,
+/// `cat_codigos_postales`, `cat_localidades` y las coordenadas `coordenadas_codigos_postales`
+/// y `coordenadas_localidades`) y extensión `.csv`, `.csv.gz` o `.csv.zst`, y los límites
+/// `limites_municipios` y `limites_codigos_postales` con extensión `.geojson` (o comprimidos).
+///
+/// ## Parámetros
+/// - `payload`: Cuerpo multipart de la petición
+///
+/// ## Retorno
+/// - `PathBuf` del directorio donde se guardaron los archivos
+/// - `Err(MultipartInvalido)`: El cuerpo no es válido
+/// - `Err(ArchivoCatalogoInvalido)`: El cuerpo contiene archivos que no son catálogos
+/// - `Err(Archivo)`: No se pudo escribir en el directorio temporal
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn guardar_catalogos_subidos(payload: Multipart) -> Result<PathBuf, UbicacionError> {
+    let marca = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    guardar_en(
+        payload,
+        std::env::temp_dir().join(format!("catalogos_recarga_{}", marca)),
+    )
+    .await
+}
+
+/// Guarda los archivos de `payload` en `directorio`, que se elimina si falla la escritura.
+async fn guardar_en(
+    mut payload: Multipart,
+    directorio: PathBuf,
+) -> Result<PathBuf, UbicacionError> {
+    std::fs::create_dir_all(&directorio)?;
+
+    // Cualquier error deja archivos a medio escribir, por lo que se elimina el directorio
+    if let Err(e) = guardar_campos(&mut payload, &directorio).await {
+        let _ = std::fs::remove_dir_all(&directorio);
+        return Err(e);
+    }
+    Ok(directorio)
+}
+
+/// Escribe en `directorio` cada archivo de `payload`, un fragmento a la vez para no tener el
+/// archivo completo en memoria.
+async fn guardar_campos(payload: &mut Multipart, directorio: &Path) -> Result<(), UbicacionError> {
+    while let Some(campo) = payload.next().await {
+        let mut campo = campo.map_err(|e| UbicacionError::MultipartInvalido(e.to_string()))?;
+        let nombre = campo
+            .content_disposition()
+            .and_then(|cd| cd.get_filename())
+            .unwrap_or_default()
+            .to_string();
+        let valido = CATALOGOS.iter().any(|catalogo| {
+            EXTENSIONES
+                .iter()
+                .any(|extension| nombre == format!("{}{}", catalogo, extension))
+        }) || LIMITES.iter().any(|limite| {
+            EXTENSIONES_LIMITES
+                .iter()
+                .any(|extension| nombre == format!("{}{}", limite, extension))
+        });
+        if !valido {
+            return Err(UbicacionError::ArchivoCatalogoInvalido(nombre));
+        }
+
+        let mut archivo = File::create(directorio.join(&nombre))?;
+        while let Some(chunk) = campo.next().await {
+            archivo
+                .write_all(&chunk.map_err(|e| UbicacionError::MultipartInvalido(e.to_string()))?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve el estado de un trabajo de recarga.
+///
+/// ## Parámetros
+/// - `trabajos`: Registro compartido de trabajos de recarga
+/// - `job_id`: Id devuelto al iniciar la recarga
+///
+/// ## Retorno
+/// - [`TrabajoRecarga`]: Estado actual del trabajo
+/// - `Err(TrabajoNoEncontrado)`: No existe un trabajo con ese id
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn estado_recarga_controller(
+    trabajos: web::Data<TrabajosRecarga>,
+    job_id: u64,
+) -> Result<TrabajoRecarga, UbicacionError> {
+    trabajos
+        .consultar(job_id)
+        .ok_or(UbicacionError::TrabajoNoEncontrado)
+}
+
+/// Devuelve el estado de la caché de búsquedas por código postal.
+///
+/// ## Parámetros
+/// - `cache`: Caché de búsquedas por código postal
+///
+/// ## Retorno
+/// - [`EstadisticasCache`]: Capacidad, entradas y contadores de aciertos y fallos
+pub async fn estadisticas_cache_controller(
+    cache: web::Data<CacheCodigosPostales>,
+) -> EstadisticasCache {
+    cache.estadisticas()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Directorio de catálogos temporal con un subdirectorio `2026-10`.
+    fn catalogos_dir(nombre: &str) -> PathBuf {
+        let directorio =
+            std::env::temp_dir().join(format!("catalogos_{}_{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(directorio.join("2026-10")).unwrap();
+        directorio
+    }
+
+    #[test]
+    fn sin_subdirectorio_usa_catalogos_dir() {
+        let base = catalogos_dir("sin_subdirectorio");
+        assert_eq!(directorio_recarga(&base, None).unwrap(), base);
+    }
+
+    #[test]
+    fn acepta_subdirectorios_relativos() {
+        let base = catalogos_dir("relativo");
+        let directorio = directorio_recarga(&base, Some("./2026-10")).unwrap();
+        assert_eq!(directorio, base.canonicalize().unwrap().join("2026-10"));
+    }
+
+    #[test]
+    fn rechaza_rutas_fuera_de_catalogos_dir() {
+        let base = catalogos_dir("fuera");
+        for subdirectorio in [
+            "/tmp",
+            "..",
+            "2026-10/../..",
+            "2026-10/../2026-10",
+            "no-existe",
+        ] {
+            assert!(
+                matches!(
+                    directorio_recarga(&base, Some(subdirectorio)),
+                    Err(UbicacionError::DirectorioRecargaInvalido(_))
+                ),
+                "{}",
+                subdirectorio
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rechaza_enlaces_que_salen_de_catalogos_dir() {
+        let base = catalogos_dir("enlace");
+        std::os::unix::fs::symlink(std::env::temp_dir(), base.join("temporal")).unwrap();
+        assert!(matches!(
+            directorio_recarga(&base, Some("temporal")),
+            Err(UbicacionError::DirectorioRecargaInvalido(_))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn limites_invalidos_no_revierten_la_recarga() {
+        use crate::pruebas::CATALOGOS_PRUEBA;
+
+        let directorio = catalogos_dir("limites_invalidos");
+        for archivo in std::fs::read_dir(CATALOGOS_PRUEBA).unwrap() {
+            let archivo = archivo.unwrap();
+            std::fs::copy(archivo.path(), directorio.join(archivo.file_name())).unwrap();
+        }
+        std::fs::write(directorio.join("limites_municipios.geojson"), "{").unwrap();
+
+        let trabajos = web::Data::new(TrabajosRecarga::default());
+        let trabajo = recargar_catalogos_controller(
+            repositorio_memoria(),
+            trabajos.clone(),
+            web::Data::new(CacheCodigosPostales::new(16, Duration::from_secs(60))),
+            web::Data::new(IndiceGeografico::default()),
+            web::Data::new(LimitesGeograficos::default()),
+            directorio,
+            false,
+        )
+        .await
+        .unwrap();
+
+        let trabajo = loop {
+            let trabajo = trabajos.consultar(trabajo.job_id).unwrap();
+            if trabajo.estado != EstadoRecarga::EnProceso {
+                break trabajo;
+            }
+            rt::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(trabajo.estado, EstadoRecarga::Completada);
+        assert_eq!(trabajo.error, None);
+        assert_eq!(trabajo.advertencias.len(), 1);
+        assert!(trabajo.advertencias[0].starts_with("No se leyeron los límites geográficos"));
+    }
+
+    /// Cuerpo multipart con un archivo por cada par nombre y contenido.
+    fn multipart(archivos: &[(&str, &str)]) -> Multipart {
+        let mut cuerpo = String::new();
+        for (nombre, contenido) in archivos {
+            cuerpo.push_str(&format!(
+                "--limite\r\nContent-Disposition: form-data; name=\"archivo\"; \
+                 filename=\"{}\"\r\nContent-Type: text/csv\r\n\r\n{}\r\n",
+                nombre, contenido
+            ));
+        }
+        cuerpo.push_str("--limite--\r\n");
+        let (peticion, payload) = actix_web::test::TestRequest::default()
+            .insert_header((
+                actix_web::http::header::CONTENT_TYPE,
+                "multipart/form-data; boundary=limite",
+            ))
+            .set_payload(cuerpo)
+            .to_http_parts();
+        Multipart::new(peticion.headers(), payload)
+    }
+
+    #[actix_web::test]
+    async fn guarda_los_archivos_subidos() {
+        let directorio = catalogos_dir("subidos").join("recarga");
+        let estados = "id_estado,estado\n9,CIUDAD DE MEXICO\n";
+        let guardado = guardar_en(
+            multipart(&[("cat_estados.csv", estados)]),
+            directorio.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(guardado, directorio);
+        assert_eq!(
+            std::fs::read_to_string(directorio.join("cat_estados.csv")).unwrap(),
+            estados
+        );
+    }
+
+    #[actix_web::test]
+    async fn elimina_el_directorio_si_un_archivo_no_es_catalogo() {
+        let directorio = catalogos_dir("no_catalogo").join("recarga");
+        let resultado = guardar_en(
+            multipart(&[("cat_estados.csv", "id_estado,estado"), ("otro.csv", "x")]),
+            directorio.clone(),
+        )
+        .await;
+        assert!(matches!(
+            resultado,
+            Err(UbicacionError::ArchivoCatalogoInvalido(nombre)) if nombre == "otro.csv"
+        ));
+        assert!(!directorio.exists());
+    }
+
+    /// Repositorio en memoria con los catálogos de prueba.
+    fn repositorio_memoria() -> web::Data<dyn RepositorioUbicacion> {
+        use crate::pruebas::CATALOGOS_PRUEBA;
+        use crate::services::repositorio::memoria::{CatalogosMemoria, RepositorioMemoria};
+        use std::sync::Arc;
+
+        let catalogos = CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap();
+        let repositorio: Arc<dyn RepositorioUbicacion> =
+            Arc::new(RepositorioMemoria::new(catalogos));
+        web::Data::from(repositorio)
+    }
+
+    #[actix_web::test]
+    async fn elimina_los_archivos_subidos_si_hay_otra_recarga() {
+        let trabajos = web::Data::new(TrabajosRecarga::default());
+        trabajos.registrar("otra".to_string()).unwrap();
+        let directorio = catalogos_dir("recarga_en_proceso");
+
+        let resultado = recargar_catalogos_controller(
+            repositorio_memoria(),
+            trabajos,
+            web::Data::new(CacheCodigosPostales::new(16, Duration::from_secs(60))),
+            web::Data::new(IndiceGeografico::default()),
+            web::Data::new(LimitesGeograficos::default()),
+            directorio.clone(),
+            true,
+        )
+        .await;
+        assert!(matches!(resultado, Err(UbicacionError::RecargaEnProceso)));
+        assert!(!directorio.exists());
+    }
+
+    #[actix_web::test]
+    async fn una_recarga_con_panico_termina_fallida() {
+        let trabajos = TrabajosRecarga::default();
+        let trabajo = trabajos.registrar("prueba".to_string()).unwrap();
+
+        let (resultado, advertencias) = esperar_recarga(async {
+            panic!("fallo de prueba");
+        })
+        .await;
+        assert!(matches!(
+            resultado,
+            Err(UbicacionError::RecargaInterrumpida(_))
+        ));
+        trabajos.finalizar(trabajo.job_id, resultado, advertencias);
+
+        let trabajo = trabajos.consultar(trabajo.job_id).unwrap();
+        assert_eq!(trabajo.estado, EstadoRecarga::Fallida);
+        assert!(trabajo.error.unwrap().contains("fallo de prueba"));
+        // Las recargas siguientes ya no responden 409
+        assert!(trabajos.registrar("siguiente".to_string()).is_some());
+    }
+}
@@ -1,76 +1,643 @@
 //! # Controladores para endpoints de ubicación
 //! En este módulo se incluyen controladores de endpoints con las siguientes funcionalidades:
 //! - Obtener estado, municipio y localidades a partir de un código postal (`busqueda_cp_controller`)
+//! - Obtener los códigos postales y localidades más cercanos a unas coordenadas (`geocodificacion_inversa_controller`)
+//! - Calcular la distancia entre códigos postales (`distancia_controller` y `matriz_distancias_controller`)
+//! - Obtener los códigos postales dentro de un radio de otro (`cercanos_controller`)
+//! - Obtener el municipio que contiene unas coordenadas (`municipio_coordenadas_controller`)
+//! - Responder código postal, municipios de un estado y códigos postales de un municipio como
+//!   GeoJSON (`busqueda_cp_geojson_controller`, `municipios_estado_controller` y
+//!   `codigos_postales_municipio_controller`)
 
 use crate::{
-    entities::{prelude::*, *},
-    types::catalogos::*,
-    utils::conversores::*,
+    services::{
+        cache_cp::CacheCodigosPostales, indice_geografico::IndiceGeografico,
+        limites_geograficos::LimitesGeograficos, repositorio::RepositorioUbicacion,
+    },
+    types::{
+        catalogos::*,
+        errores::UbicacionError,
+        geojson::{ColeccionGeoJson, EntidadGeoJson, GeometriaGeoJson},
+    },
+    utils::geodesia::distancia_metros,
 };
-use actix_web::{error, web, Error};
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde_json::json;
+use std::collections::{hash_map::Entry, HashMap};
 
-/// Dado un código postal, devuelve los ids y nombres del estado, municipio y localidades correspondientes
+/// Resultados de la geocodificación inversa si no se indica `n`
+const RESULTADOS_POR_DEFECTO: usize = 5;
+/// Máximo de resultados de la geocodificación inversa
+const RESULTADOS_MAXIMOS: usize = 50;
+/// Máximo de orígenes y de destinos de la matriz de distancias
+const CODIGOS_POSTALES_MATRIZ: usize = 50;
+/// Radio máximo, en km, de la búsqueda de códigos postales cercanos
+const RADIO_MAXIMO_KM: u32 = 100;
+/// Resultados por página de la búsqueda de cercanos si no se indica `por_pagina`
+const POR_PAGINA_DEFECTO: usize = 20;
+/// Máximo de resultados por página de la búsqueda de cercanos
+const POR_PAGINA_MAXIMO: usize = 100;
+
+/// Dado un código postal, devuelve los ids y nombres del estado, municipio y localidades correspondientes.
+/// Las respuestas se guardan en caché; sólo se consulta el repositorio si el CP no está en ella o ya expiró.
+/// No depende de Actix ni de la BD: basta cualquier implementación de [`RepositorioUbicacion`].
 ///
 /// ## Parámetros
-/// - `db`: Conexión a la base de datos
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `cache`: Caché compartida de búsquedas por código postal
 /// - `cp`: Código postal del que se quieren obtener los datos
 ///
 /// ## Retorno
 /// - [`CPResponse`]: Contiene estructuras para el estado y municipio correspondientes y un vector para las localidades
-/// - `Err(BadRequest)`: El formato de CP no es válido
-/// - `Err(NotFound)`: El CP introducido no fue encontrado
-/// - `Err(InternalServerError)`: Si ocurre un error inesperado durante la consulta a la base de datos
+/// - `Err(CodigoPostalInvalido)`: El formato de CP no es válido
+/// - `Err(CodigoPostalNoEncontrado)`: El CP introducido no fue encontrado
+/// - `Err(CatalogoInconsistente)`: No existe el estado o municipio del CP
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado durante la consulta a la base de datos (sólo con BD)
 ///
 /// ## Errores
-/// Devuelve [`actix_web::Error`] en los casos antes mencionados.
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
 pub async fn busqueda_cp_controller(
-    db: web::Data<DatabaseConnection>,
+    repositorio: &dyn RepositorioUbicacion,
+    cache: &CacheCodigosPostales,
     cp: i32,
-) -> Result<CPResponse, Error> {
+) -> Result<CPResponse, UbicacionError> {
     // Valida el formato del CP
-    if cp > 99999 || cp < 1000 {
-        return Err(error::ErrorBadRequest("Formato de código postal inválido"));
-    }
-
-    // Busca el CP y sus relacionados en la BD
-    let resultado: Vec<cat_localidades::Model> = CatLocalidades::find()
-        .filter(cat_localidades::Column::CodigoPostal.eq(cp))
-        .all(db.get_ref())
-        .await
-        .map_err(|e| error::ErrorInternalServerError(e))?;
-
-    if resultado.is_empty() {
-        return Err(error::ErrorNotFound("Código postal no encontrado"));
-    }
-
-    // Obtenemos estado y municipio de la primera localidad
-    let primera_localidad = &resultado[0];
-    let estado = registro_estructura(
-        db.get_ref(),
-        CatEstados::find_by_id(primera_localidad.id_estado),
-        "Error en el catalogo de estados en la base de datos",
-    )
-    .await?;
-    let municipio = registro_estructura(
-        db.get_ref(),
-        CatMunicipios::find_by_id(primera_localidad.id_municipio),
-        "Error en el catalogo de municipios en la base de datos",
-    )
-    .await?;
-
-    // Crea vector de localidades
-    let localidades: Vec<CatalogoIdCadena> = resultado
+    if !(1000..=99999).contains(&cp) {
+        return Err(UbicacionError::CodigoPostalInvalido);
+    }
+
+    if let Some(respuesta) = cache.obtener(cp) {
+        return Ok(respuesta);
+    }
+
+    // La generación se lee antes de consultar: si una recarga invalida la caché mientras tanto,
+    // la respuesta puede ser de los catálogos anteriores y no se guarda
+    let generacion = cache.generacion();
+    let respuesta = repositorio.buscar_cp(cp).await?;
+    cache.guardar(cp, &respuesta, generacion);
+    Ok(respuesta)
+}
+
+/// Dadas unas coordenadas, devuelve los centroides de códigos postales y localidades más cercanos,
+/// con su distancia en metros. Se consulta el índice en memoria, no el repositorio.
+///
+/// ## Parámetros
+/// - `indice`: Índice geográfico de centroides
+/// - `latitud`, `longitud`: Coordenadas en grados decimales
+/// - `n`: Número de resultados; por defecto [`RESULTADOS_POR_DEFECTO`] y como máximo [`RESULTADOS_MAXIMOS`]
+///
+/// ## Retorno
+/// - Vector de [`ResultadoGeocodificacion`] del más cercano al más lejano; vacío si no hay coordenadas cargadas
+/// - `Err(CoordenadasInvalidas)`: La latitud o la longitud están fuera de rango
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub fn geocodificacion_inversa_controller(
+    indice: &IndiceGeografico,
+    latitud: f64,
+    longitud: f64,
+    n: Option<usize>,
+) -> Result<Vec<ResultadoGeocodificacion>, UbicacionError> {
+    validar_coordenadas(latitud, longitud)?;
+    let n = n
+        .unwrap_or(RESULTADOS_POR_DEFECTO)
+        .clamp(1, RESULTADOS_MAXIMOS);
+    Ok(indice.cercanos(latitud, longitud, n))
+}
+
+/// Calcula la distancia de círculo máximo entre los centroides de dos códigos postales.
+/// Los centroides se obtienen con la misma búsqueda (y caché) que `busqueda-cp`.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `cache`: Caché compartida de búsquedas por código postal
+/// - `origen`, `destino`: Códigos postales
+///
+/// ## Retorno
+/// - [`DistanciaResponse`]: Distancia en metros, redondeada a decímetros
+/// - `Err(CodigoPostalInvalido)`: El formato de alguno de los CPs no es válido
+/// - `Err(CodigoPostalNoEncontrado)`: Alguno de los CPs no fue encontrado
+/// - `Err(CodigoPostalSinCoordenadas)`: Alguno de los CPs no tiene centroide
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn distancia_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    cache: &CacheCodigosPostales,
+    origen: i32,
+    destino: i32,
+) -> Result<DistanciaResponse, UbicacionError> {
+    let coordenadas_origen = centroide_cp(repositorio, cache, origen).await?;
+    let coordenadas_destino = centroide_cp(repositorio, cache, destino).await?;
+    Ok(DistanciaResponse {
+        origen,
+        destino,
+        distancia_metros: distancia_metros(coordenadas_origen, coordenadas_destino),
+    })
+}
+
+/// Calcula la distancia de cada origen a cada destino. Los centroides de todos los CPs se
+/// obtienen en una sola consulta al repositorio.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `payload`: Códigos postales de origen y de destino
+///
+/// ## Retorno
+/// - [`MatrizDistanciasResponse`]: Un renglón por origen con la distancia en metros a cada destino
+/// - `Err(MatrizDistanciasInvalida)`: No hay orígenes o destinos, o hay más de [`CODIGOS_POSTALES_MATRIZ`]
+/// - `Err(CodigoPostalInvalido)`, `Err(CodigoPostalNoEncontrado)` o `Err(CodigoPostalSinCoordenadas)`: Igual que en [`distancia_controller`]
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn matriz_distancias_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    payload: MatrizDistanciasPayload,
+) -> Result<MatrizDistanciasResponse, UbicacionError> {
+    let tamano_valido = |cps: &[i32]| (1..=CODIGOS_POSTALES_MATRIZ).contains(&cps.len());
+    if !tamano_valido(&payload.origenes) || !tamano_valido(&payload.destinos) {
+        return Err(UbicacionError::MatrizDistanciasInvalida(
+            CODIGOS_POSTALES_MATRIZ,
+        ));
+    }
+
+    let cps: Vec<i32> = payload
+        .origenes
+        .iter()
+        .chain(&payload.destinos)
+        .copied()
+        .collect();
+    let encontrados: HashMap<i32, CentroideCodigoPostal> = repositorio
+        .centroides_codigos_postales(&cps)
+        .await?
         .into_iter()
-        .map(|l| CatalogoIdCadena {
-            id: l.id,
-            value: l.localidad,
+        .map(|centroide| (centroide.cp, centroide))
+        .collect();
+    // Los errores se reportan en el orden de los CPs, como si se buscaran uno por uno
+    let mut centroides = HashMap::new();
+    for cp in cps {
+        if let Entry::Vacant(entrada) = centroides.entry(cp) {
+            if !(1000..=99999).contains(&cp) {
+                return Err(UbicacionError::CodigoPostalInvalido);
+            }
+            let centroide = encontrados
+                .get(&cp)
+                .ok_or(UbicacionError::CodigoPostalNoEncontrado)?;
+            entrada.insert(coordenadas_cp(cp, centroide.latitud, centroide.longitud)?);
+        }
+    }
+
+    let distancias_metros = payload
+        .origenes
+        .iter()
+        .map(|origen| {
+            payload
+                .destinos
+                .iter()
+                .map(|destino| distancia_metros(centroides[origen], centroides[destino]))
+                .collect()
         })
         .collect();
+    Ok(MatrizDistanciasResponse {
+        origenes: payload.origenes,
+        destinos: payload.destinos,
+        distancias_metros,
+    })
+}
+
+/// Devuelve los códigos postales cuyo centroide está dentro de un radio del centroide de `cp`
+/// (incluido el mismo `cp`), ordenados y paginados. Se consulta el índice geográfico.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `cache`: Caché compartida de búsquedas por código postal
+/// - `indice`: Índice geográfico de centroides
+/// - `query`: CP central, radio en km, orden y página
+///
+/// ## Retorno
+/// - [`CercanosResponse`]: Total de CPs en el radio y los de la página pedida; la página puede
+///   quedar vacía si es mayor que la última. `pagina` y `por_pagina` se ajustan a su rango válido
+/// - `Err(RadioInvalido)`: El radio no es mayor que 0 o excede [`RADIO_MAXIMO_KM`]
+/// - `Err(CodigoPostalInvalido)`, `Err(CodigoPostalNoEncontrado)` o `Err(CodigoPostalSinCoordenadas)`: Igual que en [`distancia_controller`]
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn cercanos_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    cache: &CacheCodigosPostales,
+    indice: &IndiceGeografico,
+    query: CercanosQuery,
+) -> Result<CercanosResponse, UbicacionError> {
+    if !(query.radio_km > 0.0 && query.radio_km <= f64::from(RADIO_MAXIMO_KM)) {
+        return Err(UbicacionError::RadioInvalido(RADIO_MAXIMO_KM));
+    }
+    let (latitud, longitud) = centroide_cp(repositorio, cache, query.cp).await?;
+
+    let mut cercanos = indice.codigos_postales_en_radio(latitud, longitud, query.radio_km * 1000.0);
+    match query.orden.unwrap_or_default() {
+        OrdenCercanos::Distancia => cercanos.sort_by(|a, b| {
+            a.distancia_metros
+                .total_cmp(&b.distancia_metros)
+                .then(a.cp.cmp(&b.cp))
+        }),
+        OrdenCercanos::Cp => cercanos.sort_by_key(|c| c.cp),
+    }
+    if query.descendente.unwrap_or(false) {
+        cercanos.reverse();
+    }
+
+    let pagina = query.pagina.unwrap_or(1).max(1);
+    let por_pagina = query
+        .por_pagina
+        .unwrap_or(POR_PAGINA_DEFECTO)
+        .clamp(1, POR_PAGINA_MAXIMO);
+    let total = cercanos.len();
+    let resultados = cercanos
+        .into_iter()
+        .skip((pagina - 1).saturating_mul(por_pagina))
+        .take(por_pagina)
+        .collect();
+    Ok(CercanosResponse {
+        cp: query.cp,
+        radio_km: query.radio_km,
+        total,
+        pagina,
+        por_pagina,
+        resultados,
+    })
+}
+
+/// Dadas unas coordenadas, devuelve el estado y municipio cuyo polígono las contiene y, si se
+/// cargaron límites de códigos postales, también el código postal. A diferencia de la
+/// geocodificación inversa, el resultado es exacto también cerca de los límites.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `limites`: Límites geográficos de municipios y códigos postales
+/// - `latitud`, `longitud`: Coordenadas en grados decimales
+///
+/// ## Retorno
+/// - [`MunicipioCoordenadasResponse`]: Estado, municipio y código postal (si se conoce)
+/// - `Err(CoordenadasInvalidas)`: La latitud o la longitud están fuera de rango
+/// - `Err(LimitesNoCargados)`: No se cargaron límites de municipios
+/// - `Err(CoordenadasSinMunicipio)`: Ningún municipio contiene las coordenadas
+/// - `Err(CatalogoInconsistente)`: El municipio del polígono no está en el catálogo
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn municipio_coordenadas_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    limites: &LimitesGeograficos,
+    latitud: f64,
+    longitud: f64,
+) -> Result<MunicipioCoordenadasResponse, UbicacionError> {
+    validar_coordenadas(latitud, longitud)?;
+    if !limites.tiene_municipios() {
+        return Err(UbicacionError::LimitesNoCargados);
+    }
+    let id_municipio = limites
+        .municipio(latitud, longitud)
+        .ok_or(UbicacionError::CoordenadasSinMunicipio)?;
+    let encontrado = repositorio
+        .buscar_municipio(id_municipio)
+        .await?
+        .ok_or(UbicacionError::CatalogoInconsistente("municipios"))?;
+
+    Ok(MunicipioCoordenadasResponse {
+        estado: encontrado.estado,
+        municipio: encontrado.municipio,
+        cp: limites.codigo_postal(latitud, longitud),
+    })
+}
+
+/// Devuelve la búsqueda por código postal como `FeatureCollection` de GeoJSON: una entidad para
+/// el código postal y una por localidad, distinguidas por la propiedad `tipo`. El código postal
+/// tiene como geometría sus límites, si se cargaron, o su centroide; las localidades, su centroide.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `cache`: Caché compartida de búsquedas por código postal
+/// - `limites`: Límites geográficos de municipios y códigos postales
+/// - `cp`: Código postal del que se quieren obtener los datos
+///
+/// ## Retorno
+/// - [`ColeccionGeoJson`] con el código postal y sus localidades
+/// - Los mismos errores que [`busqueda_cp_controller`]
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn busqueda_cp_geojson_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    cache: &CacheCodigosPostales,
+    limites: &LimitesGeograficos,
+    cp: i32,
+) -> Result<ColeccionGeoJson, UbicacionError> {
+    let respuesta = busqueda_cp_controller(repositorio, cache, cp).await?;
+
+    let mut entidades = Vec::with_capacity(respuesta.localidades.len() + 1);
+    entidades.push(EntidadGeoJson::new(
+        limites
+            .geometria_codigo_postal(cp)
+            .or_else(|| GeometriaGeoJson::punto(respuesta.latitud, respuesta.longitud)),
+        json!({
+            "tipo": "codigo_postal",
+            "cp": cp,
+            "id_estado": respuesta.estado.id,
+            "estado": respuesta.estado.value,
+            "id_municipio": respuesta.municipio.id,
+            "municipio": respuesta.municipio.value,
+        }),
+    ));
+    entidades.extend(respuesta.localidades.into_iter().map(|localidad| {
+        EntidadGeoJson::new(
+            GeometriaGeoJson::punto(localidad.latitud, localidad.longitud),
+            json!({
+                "tipo": "localidad",
+                "cp": cp,
+                "id_localidad": localidad.id,
+                "localidad": localidad.value,
+            }),
+        )
+    }));
+    Ok(ColeccionGeoJson::new(entidades))
+}
+
+/// Devuelve los municipios de un estado como `FeatureCollection` de GeoJSON, ordenados por id.
+/// La geometría de cada municipio son sus límites, o `null` si no se cargaron.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `limites`: Límites geográficos de municipios y códigos postales
+/// - `id_estado`: Id (clave INEGI) del estado
+///
+/// ## Retorno
+/// - [`ColeccionGeoJson`] con una entidad por municipio
+/// - `Err(EstadoNoEncontrado)`: El estado no está en el catálogo
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn municipios_estado_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    limites: &LimitesGeograficos,
+    id_estado: i32,
+) -> Result<ColeccionGeoJson, UbicacionError> {
+    let MunicipiosEstado { estado, municipios } = repositorio
+        .municipios_estado(id_estado)
+        .await?
+        .ok_or(UbicacionError::EstadoNoEncontrado)?;
+
+    Ok(ColeccionGeoJson::new(
+        municipios
+            .into_iter()
+            .map(|municipio| {
+                EntidadGeoJson::new(
+                    limites.geometria_municipio(municipio.id),
+                    json!({
+                        "id_municipio": municipio.id,
+                        "municipio": municipio.value,
+                        "id_estado": estado.id,
+                        "estado": estado.value,
+                    }),
+                )
+            })
+            .collect(),
+    ))
+}
 
-    Ok(CPResponse {
+/// Devuelve los códigos postales de un municipio como `FeatureCollection` de GeoJSON, ordenados.
+/// La geometría de cada código postal son sus límites, si se cargaron, o su centroide.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos de ubicación (BD o memoria, según `FUENTE_DATOS`)
+/// - `limites`: Límites geográficos de municipios y códigos postales
+/// - `id_municipio`: Id del municipio
+///
+/// ## Retorno
+/// - [`ColeccionGeoJson`] con una entidad por código postal
+/// - `Err(MunicipioNoEncontrado)`: El municipio no está en el catálogo
+/// - `Err(CatalogoInconsistente)`: No existe el estado del municipio
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn codigos_postales_municipio_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    limites: &LimitesGeograficos,
+    id_municipio: i32,
+) -> Result<ColeccionGeoJson, UbicacionError> {
+    let CodigosPostalesMunicipio {
         estado,
         municipio,
-        localidades,
-    })
+        codigos_postales,
+    } = repositorio
+        .codigos_postales_municipio(id_municipio)
+        .await?
+        .ok_or(UbicacionError::MunicipioNoEncontrado)?;
+
+    Ok(ColeccionGeoJson::new(
+        codigos_postales
+            .into_iter()
+            .map(|codigo_postal| {
+                EntidadGeoJson::new(
+                    limites
+                        .geometria_codigo_postal(codigo_postal.cp)
+                        .or_else(|| {
+                            GeometriaGeoJson::punto(codigo_postal.latitud, codigo_postal.longitud)
+                        }),
+                    json!({
+                        "cp": codigo_postal.cp,
+                        "id_municipio": municipio.id,
+                        "municipio": municipio.value,
+                        "id_estado": estado.id,
+                        "estado": estado.value,
+                    }),
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// Verifica que la latitud esté entre -90 y 90 y la longitud entre -180 y 180.
+fn validar_coordenadas(latitud: f64, longitud: f64) -> Result<(), UbicacionError> {
+    if !(-90.0..=90.0).contains(&latitud) || !(-180.0..=180.0).contains(&longitud) {
+        return Err(UbicacionError::CoordenadasInvalidas);
+    }
+    Ok(())
+}
+
+/// Latitud y longitud del centroide de un código postal.
+async fn centroide_cp(
+    repositorio: &dyn RepositorioUbicacion,
+    cache: &CacheCodigosPostales,
+    cp: i32,
+) -> Result<(f64, f64), UbicacionError> {
+    let respuesta = busqueda_cp_controller(repositorio, cache, cp).await?;
+    coordenadas_cp(cp, respuesta.latitud, respuesta.longitud)
+}
+
+/// Convierte a grados el centroide de un código postal.
+///
+/// ## Errores
+/// `CodigoPostalSinCoordenadas` si el CP no tiene centroide.
+fn coordenadas_cp(
+    cp: i32,
+    latitud: Option<Decimal>,
+    longitud: Option<Decimal>,
+) -> Result<(f64, f64), UbicacionError> {
+    match (latitud, longitud) {
+        (Some(latitud), Some(longitud)) => latitud
+            .to_f64()
+            .zip(longitud.to_f64())
+            .ok_or(UbicacionError::CodigoPostalSinCoordenadas(cp)),
+        _ => Err(UbicacionError::CodigoPostalSinCoordenadas(cp)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::CATALOGOS_PRUEBA;
+    use crate::services::repositorio::memoria::{CatalogosMemoria, RepositorioMemoria};
+    use std::time::Duration;
+
+    /// Repositorio en memoria con los catálogos de prueba, su caché y su índice geográfico.
+    async fn catalogos_prueba() -> (RepositorioMemoria, CacheCodigosPostales, IndiceGeografico) {
+        let repositorio =
+            RepositorioMemoria::new(CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap());
+        let indice = IndiceGeografico::default();
+        indice.reconstruir(&repositorio).await.unwrap();
+        (
+            repositorio,
+            CacheCodigosPostales::new(16, Duration::from_secs(60)),
+            indice,
+        )
+    }
+
+    /// Búsqueda de cercanos a 6600 en 10 km (incluye los cuatro CPs de CDMX).
+    fn consulta(
+        orden: Option<OrdenCercanos>,
+        descendente: Option<bool>,
+        pagina: Option<usize>,
+        por_pagina: Option<usize>,
+    ) -> CercanosQuery {
+        CercanosQuery {
+            cp: 6600,
+            radio_km: 10.0,
+            orden,
+            descendente,
+            pagina,
+            por_pagina,
+        }
+    }
+
+    /// CPs de la página devuelta.
+    fn codigos_postales(respuesta: &CercanosResponse) -> Vec<i32> {
+        respuesta.resultados.iter().map(|c| c.cp).collect()
+    }
+
+    #[actix_web::test]
+    async fn cercanos_ordenados() {
+        let (repositorio, cache, indice) = catalogos_prueba().await;
+        let cercanos = |query| cercanos_controller(&repositorio, &cache, &indice, query);
+
+        let respuesta = cercanos(consulta(None, None, None, None)).await.unwrap();
+        assert_eq!(respuesta.total, 4);
+        assert_eq!(codigos_postales(&respuesta), vec![6600, 6700, 3100, 4000]);
+        assert_eq!(respuesta.resultados[0].distancia_metros, 0.0);
+        assert!(respuesta
+            .resultados
+            .windows(2)
+            .all(|par| par[0].distancia_metros <= par[1].distancia_metros));
+
+        let respuesta = cercanos(consulta(None, Some(true), None, None))
+            .await
+            .unwrap();
+        assert_eq!(codigos_postales(&respuesta), vec![4000, 3100, 6700, 6600]);
+
+        let respuesta = cercanos(consulta(Some(OrdenCercanos::Cp), None, None, None))
+            .await
+            .unwrap();
+        assert_eq!(codigos_postales(&respuesta), vec![3100, 4000, 6600, 6700]);
+
+        let respuesta = cercanos(consulta(Some(OrdenCercanos::Cp), Some(true), None, None))
+            .await
+            .unwrap();
+        assert_eq!(codigos_postales(&respuesta), vec![6700, 6600, 4000, 3100]);
+    }
+
+    #[actix_web::test]
+    async fn cercanos_paginados() {
+        let (repositorio, cache, indice) = catalogos_prueba().await;
+        let pagina = |pagina, por_pagina| {
+            cercanos_controller(
+                &repositorio,
+                &cache,
+                &indice,
+                consulta(Some(OrdenCercanos::Cp), None, pagina, por_pagina),
+            )
+        };
+
+        let respuesta = pagina(Some(1), Some(3)).await.unwrap();
+        assert_eq!(codigos_postales(&respuesta), vec![3100, 4000, 6600]);
+        let respuesta = pagina(Some(2), Some(3)).await.unwrap();
+        assert_eq!(codigos_postales(&respuesta), vec![6700]);
+        assert_eq!((respuesta.total, respuesta.pagina), (4, 2));
+
+        // Una página después de la última queda vacía, pero conserva el total
+        let respuesta = pagina(Some(3), Some(3)).await.unwrap();
+        assert!(respuesta.resultados.is_empty());
+        assert_eq!(respuesta.total, 4);
+        let respuesta = pagina(Some(usize::MAX), Some(100)).await.unwrap();
+        assert!(respuesta.resultados.is_empty());
+
+        // `pagina` y `por_pagina` se ajustan a su rango válido
+        let respuesta = pagina(Some(0), Some(0)).await.unwrap();
+        assert_eq!((respuesta.pagina, respuesta.por_pagina), (1, 1));
+        assert_eq!(codigos_postales(&respuesta), vec![3100]);
+        let respuesta = pagina(None, Some(1_000)).await.unwrap();
+        assert_eq!(
+            (respuesta.pagina, respuesta.por_pagina),
+            (1, POR_PAGINA_MAXIMO)
+        );
+        let respuesta = pagina(None, None).await.unwrap();
+        assert_eq!(respuesta.por_pagina, POR_PAGINA_DEFECTO);
+        assert_eq!(respuesta.resultados.len(), 4);
+    }
+
+    #[actix_web::test]
+    async fn cercanos_rechaza_radios_y_cps_invalidos() {
+        let (repositorio, cache, indice) = catalogos_prueba().await;
+        let radio = |radio_km| {
+            cercanos_controller(
+                &repositorio,
+                &cache,
+                &indice,
+                CercanosQuery {
+                    radio_km,
+                    ..consulta(None, None, None, None)
+                },
+            )
+        };
+
+        for radio_km in [0.0, -1.0, f64::NAN, 100.001, f64::INFINITY] {
+            assert!(
+                matches!(
+                    radio(radio_km).await,
+                    Err(UbicacionError::RadioInvalido(100))
+                ),
+                "{}",
+                radio_km
+            );
+        }
+        // El radio máximo es válido y alcanza sólo a CDMX
+        assert_eq!(radio(100.0).await.unwrap().total, 4);
+
+        let desconocido = CercanosQuery {
+            cp: 99999,
+            ..consulta(None, None, None, None)
+        };
+        assert!(matches!(
+            cercanos_controller(&repositorio, &cache, &indice, desconocido).await,
+            Err(UbicacionError::CodigoPostalNoEncontrado)
+        ));
+    }
 }
@@ -1 +1,3 @@
+pub mod admin_catalogos;
 pub mod catalogos;
+pub mod zonas;
@@ -0,0 +1,398 @@
+//! # Controladores para endpoints de zonas de entrega
+//! En este módulo se incluyen controladores de endpoints con las siguientes funcionalidades:
+//! - Obtener las zonas que contienen un código postal (`zonas_cp_controller`)
+//! - Consultar, crear, reemplazar y eliminar zonas (`zonas_controller`, `zona_controller`,
+//!   `crear_zona_controller`, `actualizar_zona_controller` y `eliminar_zona_controller`)
+
+use crate::{
+    services::repositorio::RepositorioUbicacion,
+    types::{
+        errores::UbicacionError,
+        zonas::{CausaZonaInvalida, MiembrosFaltantes, Zona, ZonaPayload, ZonasCPResponse},
+    },
+};
+
+/// Longitud máxima, en caracteres, del nombre de una zona (`zonas.nombre`)
+const LONGITUD_NOMBRE: usize = 100;
+/// Longitud máxima, en caracteres, de la descripción de una zona (`zonas.descripcion`)
+const LONGITUD_DESCRIPCION: usize = 255;
+
+/// Dado un código postal, devuelve las zonas que lo contienen, ya sea por el mismo código postal,
+/// por un rango o por su municipio (según `cat_codigos_postales`).
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+/// - `cp`: Código postal a consultar
+///
+/// ## Retorno
+/// - [`ZonasCPResponse`]: Zonas ordenadas por id, con los motivos por los que contienen al CP; puede no haber ninguna
+/// - `Err(CodigoPostalInvalido)`: El formato de CP no es válido
+/// - `Err(CodigoPostalNoEncontrado)`: El CP no está en el catálogo
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado durante la consulta a la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn zonas_cp_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    cp: i32,
+) -> Result<ZonasCPResponse, UbicacionError> {
+    if !(1000..=99999).contains(&cp) {
+        return Err(UbicacionError::CodigoPostalInvalido);
+    }
+    let zonas = repositorio.zonas_cp(cp).await?;
+    Ok(ZonasCPResponse { cp, zonas })
+}
+
+/// Devuelve todas las zonas con sus miembros.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+///
+/// ## Retorno
+/// - Vector de [`Zona`] ordenado por id
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado durante la consulta a la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn zonas_controller(
+    repositorio: &dyn RepositorioUbicacion,
+) -> Result<Vec<Zona>, UbicacionError> {
+    repositorio.zonas().await
+}
+
+/// Devuelve una zona con sus miembros.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+/// - `id_zona`: Id de la zona
+///
+/// ## Retorno
+/// - [`Zona`]: Zona con sus códigos postales, rangos y municipios
+/// - `Err(ZonaNoEncontrada)`: No existe una zona con ese id
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado durante la consulta a la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn zona_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    id_zona: i32,
+) -> Result<Zona, UbicacionError> {
+    repositorio
+        .zona(id_zona)
+        .await?
+        .ok_or(UbicacionError::ZonaNoEncontrada)
+}
+
+/// Crea una zona después de validar sus datos (ver [`validar_zona`]).
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+/// - `payload`: Nombre, descripción y miembros de la zona
+///
+/// ## Retorno
+/// - [`Zona`]: Zona creada, con su id y sus miembros ordenados
+/// - `Err(ZonasNoPersistentes)`: Con `FUENTE_DATOS=memoria`
+/// - `Err(ZonaInvalida)`: Los datos de la zona no son válidos
+/// - `Err(ZonaDuplicada)`: Ya existe una zona con el mismo nombre
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado al modificar la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn crear_zona_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    payload: ZonaPayload,
+) -> Result<Zona, UbicacionError> {
+    exigir_zonas_persistentes(repositorio)?;
+    let payload = validar_zona(repositorio, payload).await?;
+    repositorio.crear_zona(payload).await
+}
+
+/// Reemplaza el nombre, la descripción y los miembros de una zona después de validarlos
+/// (ver [`validar_zona`]).
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+/// - `id_zona`: Id de la zona
+/// - `payload`: Nuevos nombre, descripción y miembros de la zona
+///
+/// ## Retorno
+/// - [`Zona`]: Zona actualizada, con sus miembros ordenados
+/// - `Err(ZonasNoPersistentes)`: Con `FUENTE_DATOS=memoria`
+/// - `Err(ZonaInvalida)`: Los datos de la zona no son válidos
+/// - `Err(ZonaNoEncontrada)`: No existe una zona con ese id
+/// - `Err(ZonaDuplicada)`: Otra zona tiene el mismo nombre
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado al modificar la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn actualizar_zona_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    id_zona: i32,
+    payload: ZonaPayload,
+) -> Result<Zona, UbicacionError> {
+    exigir_zonas_persistentes(repositorio)?;
+    let payload = validar_zona(repositorio, payload).await?;
+    repositorio
+        .actualizar_zona(id_zona, payload)
+        .await?
+        .ok_or(UbicacionError::ZonaNoEncontrada)
+}
+
+/// Elimina una zona con sus miembros.
+///
+/// ## Parámetros
+/// - `repositorio`: Catálogos y zonas (BD o memoria, según `FUENTE_DATOS`)
+/// - `id_zona`: Id de la zona
+///
+/// ## Retorno
+/// - `()`: La zona se eliminó
+/// - `Err(ZonasNoPersistentes)`: Con `FUENTE_DATOS=memoria`
+/// - `Err(ZonaNoEncontrada)`: No existe una zona con ese id
+/// - `Err(BaseDatos)`: Si ocurre un error inesperado al modificar la base de datos (sólo con BD)
+///
+/// ## Errores
+/// Devuelve [`UbicacionError`] en los casos antes mencionados.
+pub async fn eliminar_zona_controller(
+    repositorio: &dyn RepositorioUbicacion,
+    id_zona: i32,
+) -> Result<(), UbicacionError> {
+    exigir_zonas_persistentes(repositorio)?;
+    if !repositorio.eliminar_zona(id_zona).await? {
+        return Err(UbicacionError::ZonaNoEncontrada);
+    }
+    Ok(())
+}
+
+/// Falla con `ZonasNoPersistentes` si el repositorio no guarda las zonas (`FUENTE_DATOS=memoria`),
+/// antes de validar los datos.
+fn exigir_zonas_persistentes(repositorio: &dyn RepositorioUbicacion) -> Result<(), UbicacionError> {
+    if !repositorio.zonas_persistentes() {
+        return Err(UbicacionError::ZonasNoPersistentes);
+    }
+    Ok(())
+}
+
+/// Valida y normaliza los datos de una zona:
+/// - El nombre no puede quedar vacío sin los espacios de los extremos y tiene hasta [`LONGITUD_NOMBRE`] caracteres
+/// - La descripción tiene hasta [`LONGITUD_DESCRIPCION`] caracteres; si queda vacía se guarda `null`
+/// - Debe haber al menos un código postal, rango o municipio
+/// - Los códigos postales y los extremos de los rangos están entre 1000 y 99999, y cada rango empieza
+///   en un CP menor o igual que en el que termina
+/// - Los códigos postales y los municipios existen en el catálogo, y cada rango incluye al menos un
+///   código postal del catálogo
+///
+/// Los miembros se ordenan y se quitan los repetidos.
+///
+/// ## Errores
+/// - `ZonaInvalida`: Con la primera regla que no se cumple; si hay miembros fuera del catálogo,
+///   los enumera todos
+/// - `BaseDatos`: Error inesperado al consultar los catálogos (sólo con BD)
+async fn validar_zona(
+    repositorio: &dyn RepositorioUbicacion,
+    mut payload: ZonaPayload,
+) -> Result<ZonaPayload, UbicacionError> {
+    let invalida = |causa| Err(UbicacionError::ZonaInvalida(causa));
+
+    payload.nombre = payload.nombre.trim().to_string();
+    if payload.nombre.is_empty() {
+        return invalida(CausaZonaInvalida::NombreVacio);
+    }
+    if payload.nombre.chars().count() > LONGITUD_NOMBRE {
+        return invalida(CausaZonaInvalida::NombreLargo(LONGITUD_NOMBRE));
+    }
+    payload.descripcion = payload
+        .descripcion
+        .map(|descripcion| descripcion.trim().to_string())
+        .filter(|descripcion| !descripcion.is_empty());
+    if payload
+        .descripcion
+        .as_ref()
+        .is_some_and(|descripcion| descripcion.chars().count() > LONGITUD_DESCRIPCION)
+    {
+        return invalida(CausaZonaInvalida::DescripcionLarga(LONGITUD_DESCRIPCION));
+    }
+
+    if payload.codigos_postales.is_empty()
+        && payload.rangos.is_empty()
+        && payload.municipios.is_empty()
+    {
+        return invalida(CausaZonaInvalida::SinMiembros);
+    }
+    let cp_valido = |cp: &i32| (1000..=99999).contains(cp);
+    if let Some(cp) = payload.codigos_postales.iter().find(|cp| !cp_valido(cp)) {
+        return invalida(CausaZonaInvalida::CodigoPostalInvalido(*cp));
+    }
+    if let Some(rango) = payload.rangos.iter().find(|rango| {
+        !cp_valido(&rango.desde) || !cp_valido(&rango.hasta) || rango.desde > rango.hasta
+    }) {
+        return invalida(CausaZonaInvalida::RangoInvalido(*rango));
+    }
+
+    payload.codigos_postales.sort_unstable();
+    payload.codigos_postales.dedup();
+    payload.rangos.sort_unstable();
+    payload.rangos.dedup();
+    payload.municipios.sort_unstable();
+    payload.municipios.dedup();
+
+    let faltantes = repositorio.miembros_faltantes(&payload).await?;
+    if faltantes != MiembrosFaltantes::default() {
+        return invalida(CausaZonaInvalida::FueraDelCatalogo(faltantes));
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::idioma::Idioma;
+    use crate::{
+        pruebas::{bd_sqlite, CATALOGOS_PRUEBA},
+        services::repositorio::{
+            base_datos::RepositorioBaseDatos,
+            memoria::{CatalogosMemoria, RepositorioMemoria},
+        },
+        types::zonas::RangoCP,
+    };
+    use actix_web::{http::StatusCode, ResponseError};
+
+    /// Zona con los miembros dados.
+    fn zona(codigos_postales: Vec<i32>, rangos: Vec<RangoCP>, municipios: Vec<i32>) -> ZonaPayload {
+        ZonaPayload {
+            nombre: " CDMX centro ".to_string(),
+            descripcion: None,
+            codigos_postales,
+            rangos,
+            municipios,
+        }
+    }
+
+    #[actix_web::test]
+    async fn memoria_no_modifica_zonas() {
+        let repositorio =
+            RepositorioMemoria::new(CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap());
+        // Responde 501 antes de validar los datos
+        let error = crear_zona_controller(&repositorio, zona(vec![], vec![], vec![]))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::NOT_IMPLEMENTED);
+        assert!(matches!(
+            actualizar_zona_controller(&repositorio, 1, zona(vec![6600], vec![], vec![])).await,
+            Err(UbicacionError::ZonasNoPersistentes)
+        ));
+        assert!(matches!(
+            eliminar_zona_controller(&repositorio, 1).await,
+            Err(UbicacionError::ZonasNoPersistentes)
+        ));
+        assert!(zonas_controller(&repositorio).await.unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn rechaza_miembros_fuera_del_catalogo() {
+        let repositorio = RepositorioBaseDatos::new(bd_sqlite().await);
+        let payload = zona(
+            vec![99999, 6600, 9999],
+            vec![RangoCP {
+                desde: 7000,
+                hasta: 7999,
+            }],
+            vec![999, 6],
+        );
+        let error = crear_zona_controller(&repositorio, payload)
+            .await
+            .unwrap_err();
+        let UbicacionError::ZonaInvalida(causa) = &error else {
+            panic!("la zona debía ser inválida");
+        };
+        assert_eq!(
+            *causa,
+            CausaZonaInvalida::FueraDelCatalogo(MiembrosFaltantes {
+                codigos_postales: vec![9999, 99999],
+                rangos: vec![RangoCP {
+                    desde: 7000,
+                    hasta: 7999
+                }],
+                municipios: vec![999],
+            })
+        );
+        assert_eq!(
+            error.detalle(Idioma::Es),
+            "Zona inválida: los códigos postales 09999, 99999 no están en el catálogo; \
+             los rangos 07000-07999 no incluyen códigos postales del catálogo; \
+             los municipios 999 no están en el catálogo"
+        );
+        assert_eq!(
+            error.detalle(Idioma::En),
+            "Invalid zone: postal codes 09999, 99999 are not in the catalog; \
+             ranges 07000-07999 do not include any postal code from the catalog; \
+             municipalities 999 are not in the catalog"
+        );
+
+        let creada = crear_zona_controller(&repositorio, zona(vec![6700, 6600], vec![], vec![6]))
+            .await
+            .unwrap();
+        assert_eq!(creada.nombre, "CDMX centro");
+        assert_eq!(creada.codigos_postales, [6600, 6700]);
+    }
+
+    #[actix_web::test]
+    async fn describe_las_reglas_en_ambos_idiomas() {
+        let repositorio = RepositorioBaseDatos::new(bd_sqlite().await);
+        let nombrada = |nombre: &str, descripcion: Option<String>| ZonaPayload {
+            nombre: nombre.to_string(),
+            descripcion,
+            ..zona(vec![6600], vec![], vec![])
+        };
+        let rango = RangoCP {
+            desde: 6999,
+            hasta: 6000,
+        };
+        for (payload, causa, es, en) in [
+            (
+                nombrada("   ", None),
+                CausaZonaInvalida::NombreVacio,
+                "el nombre no puede estar vacío",
+                "the name cannot be empty",
+            ),
+            (
+                nombrada(&"x".repeat(LONGITUD_NOMBRE + 1), None),
+                CausaZonaInvalida::NombreLargo(LONGITUD_NOMBRE),
+                "el nombre tiene más de 100 caracteres",
+                "the name is longer than 100 characters",
+            ),
+            (
+                nombrada("CDMX", Some("x".repeat(LONGITUD_DESCRIPCION + 1))),
+                CausaZonaInvalida::DescripcionLarga(LONGITUD_DESCRIPCION),
+                "la descripción tiene más de 255 caracteres",
+                "the description is longer than 255 characters",
+            ),
+            (
+                zona(vec![], vec![], vec![]),
+                CausaZonaInvalida::SinMiembros,
+                "la zona no tiene códigos postales, rangos ni municipios",
+                "the zone has no postal codes, ranges or municipalities",
+            ),
+            (
+                zona(vec![6600, 100000], vec![], vec![]),
+                CausaZonaInvalida::CodigoPostalInvalido(100000),
+                "el código postal 100000 no es válido",
+                "postal code 100000 is not valid",
+            ),
+            (
+                zona(vec![], vec![rango], vec![]),
+                CausaZonaInvalida::RangoInvalido(rango),
+                "el rango de 6999 a 6000 no es válido",
+                "the range from 6999 to 6000 is not valid",
+            ),
+        ] {
+            let error = crear_zona_controller(&repositorio, payload)
+                .await
+                .unwrap_err();
+            assert_eq!(error.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(matches!(&error, UbicacionError::ZonaInvalida(c) if *c == causa));
+            assert_eq!(error.detalle(Idioma::Es), format!("Zona inválida: {}", es));
+            assert_eq!(error.detalle(Idioma::En), format!("Invalid zone: {}", en));
+        }
+    }
+}
@@ -5,10 +5,14 @@ use sea_orm::entity::prelude::*;
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "cat_codigos_postales")]
 pub struct Model {
-    #[sea_orm(primary_key)]
+    #[sea_orm(primary_key, auto_increment = false)]
     pub codigo_postal: i32,
     pub id_municipio: i32,
     pub id_estado: i32,
+    #[sea_orm(column_type = "Decimal(Some((9, 6)))", nullable)]
+    pub latitud: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((9, 6)))", nullable)]
+    pub longitud: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
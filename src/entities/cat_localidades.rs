@@ -11,6 +11,10 @@ pub struct Model {
     pub codigo_postal: i32,
     pub id_municipio: i32,
     pub id_estado: i32,
+    #[sea_orm(column_type = "Decimal(Some((9, 6)))", nullable)]
+    pub latitud: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((9, 6)))", nullable)]
+    pub longitud: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
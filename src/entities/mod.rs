@@ -6,3 +6,7 @@ pub mod cat_codigos_postales;
 pub mod cat_estados;
 pub mod cat_localidades;
 pub mod cat_municipios;
+pub mod zonas;
+pub mod zonas_codigos_postales;
+pub mod zonas_municipios;
+pub mod zonas_rangos_cp;
@@ -4,3 +4,7 @@ pub use super::cat_codigos_postales::Entity as CatCodigosPostales;
 pub use super::cat_estados::Entity as CatEstados;
 pub use super::cat_localidades::Entity as CatLocalidades;
 pub use super::cat_municipios::Entity as CatMunicipios;
+pub use super::zonas::Entity as Zonas;
+pub use super::zonas_codigos_postales::Entity as ZonasCodigosPostales;
+pub use super::zonas_municipios::Entity as ZonasMunicipios;
+pub use super::zonas_rangos_cp::Entity as ZonasRangosCp;
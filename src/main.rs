@@ -1,79 +1,465 @@
 mod config;
 mod controllers;
 mod middleware;
+#[cfg(test)]
+mod pruebas;
 mod routes;
+#[allow(clippy::enum_variant_names)]
 mod entities;
 mod services;
 mod types;
 mod utils;
-use crate::services::catalogos_ubicacion::{
-    llenar_catalogos_cps, llenar_catalogos_estados_municipios, llenar_catalogos_localidades,
+use crate::config::base_datos::url_base_datos;
+use crate::config::cache::{capacidad_cache_cp, ttl_cache_cp};
+use crate::config::catalogos::{
+    directorio_catalogos, fuente_datos, politica_carga, snapshot_catalogos, FuenteDatos,
+    PoliticaCarga,
+};
+use crate::services::catalogos_ubicacion::{llenar_catalogos, recargar_catalogos};
+use crate::services::diferencias_catalogos::{
+    comparar_catalogos, escribir_reporte, verificar_migraciones,
+};
+use crate::services::cache_cp::CacheCodigosPostales;
+use crate::services::indice_geografico::IndiceGeografico;
+use crate::services::limites_geograficos::LimitesGeograficos;
+use crate::services::recarga_catalogos::TrabajosRecarga;
+use crate::services::repositorio::{
+    memoria::{CatalogosMemoria, RepositorioMemoria},
+    base_datos::RepositorioBaseDatos,
+    RepositorioUbicacion,
 };
 
-use actix_web::middleware::Logger;
+use actix_web::middleware::{from_fn, Logger};
 use actix_web::{web, App, HttpServer};
 use actix_web_httpauth::extractors::bearer::Config as BearerConfig;
 use actix_web_httpauth::middleware::HttpAuthentication;
 use dotenv::dotenv;
 use migration::{Migrator, MigratorTrait};
-use sea_orm::Database;
+use sea_orm::{Database, DatabaseConnection};
 use std::env;
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
+use std::sync::Arc;
+use utoipa_swagger_ui::{Config, SwaggerUi, Url};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // Carga variables de entorno desde .env
     dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+    let catalogos_dir = directorio_catalogos();
+
+    // Subcomando `diferencias <directorio> [salida]`: compara un nuevo juego de catálogos
+    // contra los cargados en la BD, escribe el reporte y termina sin levantar el servidor.
+    // No corre las migraciones: el reporte no debe modificar la BD que se compara
+    if args.get(1).map(String::as_str) == Some("diferencias") {
+        let database_url = url_base_datos().expect("Variable DATABASE_URL inválida");
+        let db = Database::connect(&database_url)
+            .await
+            .expect("Error al conectarse a la base de datos");
+        if let Err(e) = verificar_migraciones(&db).await {
+            panic!("{}. Corre las migraciones antes de comparar los catálogos", e);
+        }
+        let nuevos = args
+            .get(2)
+            .expect("Uso: ubicacion-servicio diferencias <directorio> [salida]");
+        let salida = args
+            .get(3)
+            .map(String::as_str)
+            .unwrap_or("diferencias_catalogos");
+        let reporte = comparar_catalogos(&db, nuevos)
+            .await
+            .expect("Error al comparar los catálogos");
+        escribir_reporte(&reporte, salida).expect("Error al escribir el reporte de diferencias");
+        println!(
+            "Agregados: {}, eliminados: {}, renombrados: {}, reasignados: {}. Reporte en {}.json y {}.csv",
+            reporte.agregados,
+            reporte.eliminados,
+            reporte.renombrados,
+            reporte.reasignados,
+            salida,
+            salida
+        );
+        return Ok(());
+    }
+
+    // Subcomando `snapshot <salida>`: valida los CSV de CATALOGOS_DIR y escribe el snapshot
+    // que se usa con FUENTE_DATOS=memoria, sin necesidad de BD
+    if args.get(1).map(String::as_str) == Some("snapshot") {
+        let salida = args
+            .get(2)
+            .expect("Uso: ubicacion-servicio snapshot <salida>");
+        let catalogos =
+            CatalogosMemoria::desde_csv(&catalogos_dir).expect("Error al leer los catalogos");
+        catalogos
+            .guardar_snapshot(salida)
+            .expect("Error al escribir el snapshot");
+        println!(
+            "Snapshot con {} códigos postales escrito en {}",
+            catalogos.codigos_postales(),
+            salida
+        );
+        return Ok(());
+    }
+
+    // Repositorio de catálogos según FUENTE_DATOS
+    let fuente = fuente_datos().expect("Variable FUENTE_DATOS debe ser base_datos o memoria");
+    let mut carga_omitida = false;
+    let repositorio: Arc<dyn RepositorioUbicacion> = match fuente {
+        FuenteDatos::BaseDatos => {
+            let db = conectar_bd().await;
+
+            //Poblar catálogos según CARGA_CATALOGOS. Los cuatro se cargan en una transacción: si alguno falla no se inserta nada
+            let politica = politica_carga()
+                .expect("Variable CARGA_CATALOGOS debe ser siempre, si_vacio o nunca");
+            match politica {
+                PoliticaCarga::Siempre => recargar_catalogos(&db, &catalogos_dir)
+                    .await
+                    .expect("Error al recargar los catalogos"),
+                PoliticaCarga::SiVacio => llenar_catalogos(&db, &catalogos_dir)
+                    .await
+                    .expect("Error al llenar los catalogos"),
+                PoliticaCarga::Nunca => carga_omitida = true,
+            }
+            Arc::new(RepositorioBaseDatos::new(db))
+        }
+        // Sin BD: los índices se construyen desde el snapshot o desde los CSV
+        FuenteDatos::Memoria => {
+            Arc::new(RepositorioMemoria::new(catalogos_memoria(&catalogos_dir)))
+        }
+    };
+    let repositorio = web::Data::from(repositorio);
+
+    // Con `--solo-cargar` el proceso termina después de la carga (p. ej. como init job de un contenedor)
+    if args.iter().any(|a| a == "--solo-cargar") {
+        if carga_omitida {
+            println!("Carga de catálogos omitida (CARGA_CATALOGOS=nunca)");
+        } else {
+            println!("Catálogos cargados desde {} ({:?})", catalogos_dir, fuente);
+        }
+        return Ok(());
+    }
+
     let ip = env::var("IP").expect("Variable IP debe ser fijada");
     let port: u16 = env::var("PORT")
         .expect("Variable PORT debe ser fijada")
         .parse()
         .expect("Variable PORT debe ser de tipo u16");
 
-    // Crea pool de conexiones a la base donde se crearán las tablas con los datos de ubicación
-    let database_url = env::var("DATABASE_URL").expect("Variable DATABASE_URL debe ser fijada");
-    let db = Database::connect(&database_url)
-        .await
-        .expect("Error al conectarse a la base de datos");
-    Migrator::up(&db, None)
-        .await
-        .expect("Error al correr las migraciones");
+    // Inicializa Swagger, en español y en inglés
+    let documentacion = web::Data::new(config::swagger::DocumentacionApi::default());
 
-    //Poblar catálogos
-    llenar_catalogos_estados_municipios(&db, "./catalogos")
-        .await
-        .expect("Error al llenar los catalogos de estados o municipios");
-    llenar_catalogos_cps(&db, "./catalogos")
+    // Índice de centroides para la geocodificación inversa, compartido entre workers
+    let indice_geografico = web::Data::new(IndiceGeografico::default());
+    indice_geografico
+        .reconstruir(repositorio.get_ref())
         .await
-        .expect("Error al llenar los catalogos de código postal");
-    llenar_catalogos_localidades(&db, "./catalogos")
+        .expect("Error al construir el índice geográfico");
+
+    // Límites de municipios y CPs (GeoJSON opcionales en CATALOGOS_DIR), compartidos entre workers
+    let limites_geograficos = web::Data::new(LimitesGeograficos::default());
+    limites_geograficos
+        .recargar(&catalogos_dir)
         .await
-        .expect("Error al llenar los catalogos de localidades");
+        .expect("Error al leer los límites geográficos");
+
+    // Registro de recargas de catálogos, compartido entre workers
+    let trabajos_recarga = web::Data::new(TrabajosRecarga::default());
 
-    // Inicializa Swagger
-    let openapi = config::swagger::ApiDoc::openapi();
+    // Caché de búsquedas por código postal, compartida entre workers
+    let cache_cp = web::Data::new(CacheCodigosPostales::new(
+        capacidad_cache_cp().expect("Variable CACHE_CP_CAPACIDAD debe ser de tipo usize"),
+        ttl_cache_cp().expect("Variable CACHE_CP_TTL_SEGUNDOS debe ser de tipo u64"),
+    ));
 
     // Inicializa el servidor HTTP
     HttpServer::new(move || {
-        let auth = HttpAuthentication::with_fn(middleware::jwt::validador_jwt);
-
         App::new()
             .wrap(config::cors::cors_config()) // CORS
+            .wrap(from_fn(middleware::peticion::contexto_peticion)) // Id de petición y errores problem+json
             .wrap(Logger::default()) // Logging
             .app_data(BearerConfig::default().realm("Area privada")) // Configuración de Extractor
-            .app_data(web::Data::new(db.clone())) // Pool de conexiones a la bd
+            .app_data(repositorio.clone()) // Catálogos y zonas de entrega (BD o memoria)
+            .app_data(trabajos_recarga.clone()) // Recargas de catálogos en segundo plano
+            .app_data(cache_cp.clone()) // Caché de búsquedas por CP
+            .app_data(indice_geografico.clone()) // Índice de geocodificación inversa
+            .app_data(limites_geograficos.clone()) // Polígonos de municipios y CPs
+            .app_data(documentacion.clone()) // Documentación en español y en inglés
+            // Documentación en el idioma de Accept-Language
+            .route(
+                "/api-docs/openapi.json",
+                web::get().to(config::swagger::documentacion_api),
+            )
             .service(
-                SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
+                SwaggerUi::new("/swagger-ui/{_:.*}")
+                    .urls(vec![
+                        (
+                            Url::new("Español", "/api-docs/openapi.es.json"),
+                            documentacion.es.clone(),
+                        ),
+                        (
+                            Url::new("English", "/api-docs/openapi.en.json"),
+                            documentacion.en.clone(),
+                        ),
+                    ])
+                    .config(Config::new([
+                        Url::new("Accept-Language", "/api-docs/openapi.json"),
+                        Url::new("Español", "/api-docs/openapi.es.json"),
+                        Url::new("English", "/api-docs/openapi.en.json"),
+                    ])),
             ) // UI de Swagger
-            // Servicios privados
-            .service(
-                web::scope("/api")
-                    .wrap(auth)
-                    .service(routes::catalogos::busqueda_cp)
-            )
+            .configure(rutas_api) // Servicios privados
     })
     .bind((ip, port))?
     .run()
     .await
 }
+
+/// Crea el pool de conexiones a la base de `DATABASE_URL` (Postgres o SQLite) y corre las migraciones.
+async fn conectar_bd() -> DatabaseConnection {
+    let database_url = url_base_datos().expect("Variable DATABASE_URL inválida");
+    let db = Database::connect(&database_url)
+        .await
+        .expect("Error al conectarse a la base de datos");
+    Migrator::up(&db, None)
+        .await
+        .expect("Error al correr las migraciones");
+    db
+}
+
+/// Catálogos en memoria desde `SNAPSHOT_CATALOGOS` o, si no se fijó, desde los CSV de `catalogos_dir`.
+fn catalogos_memoria(catalogos_dir: &str) -> CatalogosMemoria {
+    match snapshot_catalogos() {
+        Some(snapshot) => CatalogosMemoria::desde_snapshot(&snapshot)
+            .expect("Error al leer el snapshot de catalogos"),
+        None => CatalogosMemoria::desde_csv(catalogos_dir).expect("Error al leer los catalogos"),
+    }
+}
+
+/// Registra los servicios privados de `/api` y los administrativos de `/api/admin`, cada uno con
+/// su validación de token.
+fn rutas_api(cfg: &mut web::ServiceConfig) {
+    let auth = HttpAuthentication::with_fn(middleware::jwt::validador_jwt);
+    let auth_admin = HttpAuthentication::with_fn(middleware::jwt::validador_jwt_admin);
+
+    cfg.service(
+        web::scope("/api")
+            .wrap(auth)
+            .service(routes::catalogos::busqueda_cp)
+            .service(routes::catalogos::municipios_estado)
+            .service(routes::catalogos::codigos_postales_municipio)
+            .service(routes::catalogos::geocodificacion_inversa)
+            .service(routes::catalogos::municipio_coordenadas)
+            .service(routes::catalogos::distancia)
+            .service(routes::catalogos::matriz_distancias)
+            .service(routes::catalogos::codigos_postales_cercanos)
+            .service(routes::zonas::zonas_cp)
+            // Servicios administrativos
+            .service(
+                web::scope("/admin")
+                    .wrap(auth_admin)
+                    .service(routes::admin_catalogos::recargar)
+                    .service(routes::admin_catalogos::estado_recarga)
+                    .service(routes::admin_catalogos::estadisticas_cache)
+                    .service(routes::zonas::listar_zonas)
+                    .service(routes::zonas::crear_zona)
+                    .service(routes::zonas::consultar_zona)
+                    .service(routes::zonas::actualizar_zona)
+                    .service(routes::zonas::eliminar_zona),
+            ),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::CATALOGOS_PRUEBA;
+    use actix_web::http::{header, StatusCode};
+    use actix_web::test;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    /// Levanta las rutas de `/api` como con `FUENTE_DATOS=memoria`: sin `DATABASE_URL`, con los
+    /// catálogos de prueba en memoria y el índice geográfico construido con ellos.
+    async fn servicio_memoria() -> impl actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse,
+        Error = actix_web::Error,
+    > {
+        let fuente: FuenteDatos = "memoria".parse().unwrap();
+        assert_eq!(fuente, FuenteDatos::Memoria);
+        let catalogos = CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap();
+        let repositorio: Arc<dyn RepositorioUbicacion> =
+            Arc::new(RepositorioMemoria::new(catalogos));
+        let indice_geografico = IndiceGeografico::default();
+        indice_geografico
+            .reconstruir(repositorio.as_ref())
+            .await
+            .unwrap();
+        test::init_service(
+            App::new()
+                .wrap(from_fn(middleware::peticion::contexto_peticion))
+                .app_data(BearerConfig::default().realm("Area privada"))
+                .app_data(web::Data::from(repositorio))
+                .app_data(web::Data::new(CacheCodigosPostales::new(
+                    16,
+                    Duration::from_secs(60),
+                )))
+                .app_data(web::Data::new(indice_geografico))
+                .app_data(web::Data::new(LimitesGeograficos::default()))
+                .configure(rutas_api),
+        )
+        .await
+    }
+
+    /// `GET` con un token cualquiera (las rutas de `/api` sólo exigen que venga uno).
+    async fn consultar(
+        servicio: &impl actix_web::dev::Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse,
+            Error = actix_web::Error,
+        >,
+        uri: &str,
+    ) -> (StatusCode, Value) {
+        let peticion = test::TestRequest::get()
+            .uri(uri)
+            .insert_header((header::AUTHORIZATION, "Bearer prueba"))
+            .to_request();
+        let respuesta = test::call_service(servicio, peticion).await;
+        let status = respuesta.status();
+        (status, test::read_body_json(respuesta).await)
+    }
+
+    /// Valores enteros de `propiedad` en cada feature de una colección GeoJSON.
+    fn propiedades(coleccion: &Value, propiedad: &str) -> Vec<i64> {
+        coleccion["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|feature| feature["properties"][propiedad].as_i64().unwrap())
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn busqueda_cp_en_memoria() {
+        let servicio = servicio_memoria().await;
+
+        let (status, cuerpo) = consultar(&servicio, "/api/busqueda-cp/6600").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cuerpo["estado"]["value"], "CIUDAD DE MEXICO");
+        assert_eq!(cuerpo["municipio"]["id"], 6);
+        assert_eq!(cuerpo["localidades"][0]["value"], "JUAREZ");
+        assert_eq!(cuerpo["latitud"], "19.427000");
+
+        let (status, cuerpo) = consultar(&servicio, "/api/busqueda-cp/99999").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(cuerpo["codigo"], "codigo_postal_no_encontrado");
+
+        let (status, cuerpo) = consultar(&servicio, "/api/busqueda-cp/12").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(cuerpo["codigo"], "codigo_postal_invalido");
+    }
+
+    #[actix_web::test]
+    async fn busqueda_cp_sin_token() {
+        let servicio = servicio_memoria().await;
+        let peticion = test::TestRequest::get()
+            .uri("/api/busqueda-cp/6600")
+            .to_request();
+        let respuesta = test::call_service(&servicio, peticion).await;
+        assert_eq!(respuesta.status(), StatusCode::BAD_REQUEST);
+        let cuerpo: Value = test::read_body_json(respuesta).await;
+        assert_eq!(cuerpo["codigo"], "token_requerido");
+    }
+
+    #[actix_web::test]
+    async fn municipios_de_estado_en_memoria() {
+        let servicio = servicio_memoria().await;
+
+        let (status, cuerpo) = consultar(&servicio, "/api/estados/9/municipios").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cuerpo["type"], "FeatureCollection");
+        assert_eq!(propiedades(&cuerpo, "id_municipio"), vec![3, 4, 6]);
+
+        let (status, cuerpo) = consultar(&servicio, "/api/estados/40/municipios").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(cuerpo["codigo"], "estado_no_encontrado");
+    }
+
+    #[actix_web::test]
+    async fn codigos_postales_de_municipio_en_memoria() {
+        let servicio = servicio_memoria().await;
+
+        let (status, cuerpo) = consultar(&servicio, "/api/municipios/6/codigos-postales").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(propiedades(&cuerpo, "cp"), vec![6600, 6700]);
+        // Sin límites cargados, cada CP se dibuja en su centroide
+        assert_eq!(cuerpo["features"][0]["geometry"]["type"], "Point");
+
+        let (status, cuerpo) = consultar(&servicio, "/api/municipios/9999/codigos-postales").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(cuerpo["codigo"], "municipio_no_encontrado");
+    }
+
+    #[actix_web::test]
+    async fn matriz_distancias_en_memoria() {
+        let servicio = servicio_memoria().await;
+        let matriz = |origenes: &[i32], destinos: &[i32]| {
+            test::TestRequest::post()
+                .uri("/api/distancia/matriz")
+                .insert_header((header::AUTHORIZATION, "Bearer prueba"))
+                .set_json(serde_json::json!({"origenes": origenes, "destinos": destinos}))
+                .to_request()
+        };
+
+        let respuesta = test::call_service(&servicio, matriz(&[6600, 3100], &[6600, 44100])).await;
+        assert_eq!(respuesta.status(), StatusCode::OK);
+        let cuerpo: Value = test::read_body_json(respuesta).await;
+        let distancias = &cuerpo["distancias_metros"];
+        assert_eq!(distancias[0][0], 0.0);
+        assert!(distancias[1][0].as_f64().unwrap() > 0.0);
+        // CDMX a Guadalajara, unos 460 km
+        assert!((400_000.0..500_000.0).contains(&distancias[0][1].as_f64().unwrap()));
+
+        // El primer CP que falla determina el error
+        let respuesta = test::call_service(&servicio, matriz(&[99999, 12], &[6600])).await;
+        assert_eq!(respuesta.status(), StatusCode::NOT_FOUND);
+        let respuesta = test::call_service(&servicio, matriz(&[12, 99999], &[6600])).await;
+        assert_eq!(respuesta.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn geocodificacion_inversa_en_memoria() {
+        let servicio = servicio_memoria().await;
+        let cercanos = |n: &str| format!("/api/geocodificacion-inversa?lat=19.427&lon=-99.16{}", n);
+
+        let (status, cuerpo) = consultar(&servicio, &cercanos("")).await;
+        assert_eq!(status, StatusCode::OK);
+        let resultados = cuerpo.as_array().unwrap();
+        assert_eq!(resultados.len(), 5);
+        assert_eq!(resultados[0]["cp"], 6600);
+        assert_eq!(resultados[0]["distancia_metros"], 0.0);
+
+        // `n` se ajusta a [1, 50]; con 10 centroides cargados se devuelven todos
+        let (_, cuerpo) = consultar(&servicio, &cercanos("&n=0")).await;
+        assert_eq!(cuerpo.as_array().unwrap().len(), 1);
+        let (_, cuerpo) = consultar(&servicio, &cercanos("&n=500")).await;
+        assert_eq!(cuerpo.as_array().unwrap().len(), 10);
+
+        for coordenadas in [
+            "lat=90.5&lon=-99.16",
+            "lat=-91&lon=-99.16",
+            "lat=19.427&lon=180.1",
+            "lat=19.427&lon=-181",
+            "lat=NaN&lon=-99.16",
+            "lat=19.427&lon=NaN",
+        ] {
+            let (status, cuerpo) = consultar(
+                &servicio,
+                &format!("/api/geocodificacion-inversa?{}", coordenadas),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", coordenadas);
+            assert_eq!(cuerpo["codigo"], "coordenadas_invalidas");
+        }
+    }
+}
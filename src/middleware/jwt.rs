@@ -1,7 +1,10 @@
-use actix_web::{dev::ServiceRequest, error, Error};
+use actix_web::{dev::ServiceRequest, Error};
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 
-use crate::utils::jwt::validar_token;
+use crate::{
+    types::errores::UbicacionError,
+    utils::jwt::{validar_token, validar_token_admin},
+};
 
 // Middleware para validar el token JWT
 // Sólo valida la ecuación del token, pero no valida el contenido
@@ -10,7 +13,7 @@ pub async fn validador_jwt(
     auth: Option<BearerAuth>,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
     let Some(auth) = auth else {
-        return Err((error::ErrorBadRequest("Token is required"), req));
+        return Err((UbicacionError::TokenRequerido.into(), req));
     };
 
     let token = auth.token();
@@ -18,6 +21,25 @@ pub async fn validador_jwt(
 
     match claims {
         Ok(()) => Ok(req),
-        Err(_e) => Err((error::ErrorForbidden("Invalid token"), req)),
+        Err(_e) => Err((UbicacionError::TokenInvalido.into(), req)),
+    }
+}
+
+// Middleware para las rutas administrativas
+// Valida la firma del token (`TokenInvalido`) y que incluya el scope `admin` (`ScopeAdminRequerido`)
+pub async fn validador_jwt_admin(
+    req: ServiceRequest,
+    auth: Option<BearerAuth>,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let Some(auth) = auth else {
+        return Err((UbicacionError::TokenRequerido.into(), req));
+    };
+
+    let token = auth.token();
+    let claims = validar_token_admin(token.to_string());
+
+    match claims {
+        Ok(()) => Ok(req),
+        Err(e) => Err((e.into(), req)),
     }
 }
@@ -0,0 +1,119 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    error::InternalError,
+    http::header::{self, HeaderName, HeaderValue},
+    middleware::Next,
+    Error, HttpResponse,
+};
+
+use crate::{
+    types::errores::{ProblemDetails, TIPO_CONTENIDO_PROBLEMA},
+    utils::idioma::Idioma,
+};
+
+/// Encabezado con el id de la petición, tanto de entrada (si el cliente lo envía) como de salida
+pub const ENCABEZADO_ID_PETICION: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Datos de la petición que se está atendiendo, disponibles para construir los errores.
+struct ContextoPeticion {
+    id: String,
+    idioma: Idioma,
+}
+
+tokio::task_local! {
+    static CONTEXTO: ContextoPeticion;
+}
+
+/// Devuelve el id de la petición en curso, si se llama dentro de [`contexto_peticion`].
+pub fn id_peticion_actual() -> Option<String> {
+    CONTEXTO.try_with(|c| c.id.clone()).ok()
+}
+
+/// Devuelve el idioma de la petición en curso (español fuera de [`contexto_peticion`]).
+pub fn idioma_actual() -> Idioma {
+    CONTEXTO.try_with(|c| c.idioma).unwrap_or_default()
+}
+
+// Middleware que asigna un id a cada petición y lo devuelve en `X-Request-Id`
+// También elige el idioma de los mensajes según `Accept-Language`
+// Además garantiza que todos los errores (de handlers, extractores o de otros middlewares,
+// como el de JWT) se respondan como `application/problem+json` con ese id
+pub async fn contexto_peticion(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    // Respeta el id enviado por el cliente o un proxy, si es razonable
+    let id = req
+        .headers()
+        .get(&ENCABEZADO_ID_PETICION)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let valor_id = HeaderValue::from_str(&id).ok();
+    let idioma = req
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(Idioma::desde_accept_language)
+        .unwrap_or_default();
+
+    // Las respuestas de error se construyen dentro del alcance para que incluyan el id
+    CONTEXTO
+        .scope(ContextoPeticion { id, idioma }, async move {
+            match next.call(req).await {
+                Ok(res) => {
+                    let (req, respuesta) = res.map_into_boxed_body().into_parts();
+                    let mut respuesta = formato_problema(respuesta);
+                    if let Some(valor) = valor_id {
+                        respuesta
+                            .headers_mut()
+                            .insert(ENCABEZADO_ID_PETICION, valor);
+                    }
+                    Ok(ServiceResponse::new(req, respuesta))
+                }
+                // Errores que otro middleware no convirtió en respuesta
+                Err(e) => {
+                    let mut respuesta = formato_problema(e.error_response());
+                    if let Some(valor) = valor_id {
+                        respuesta
+                            .headers_mut()
+                            .insert(ENCABEZADO_ID_PETICION, valor);
+                    }
+                    Err(InternalError::from_response(e, respuesta).into())
+                }
+            }
+        })
+        .await
+}
+
+/// Convierte en problem+json las respuestas de error que no lo son, como las de los extractores
+/// de Actix (p. ej. un parámetro de ruta que no es numérico).
+fn formato_problema(respuesta: HttpResponse) -> HttpResponse {
+    let Some(error) = respuesta.error() else {
+        return respuesta;
+    };
+    let es_problema = respuesta
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes() == TIPO_CONTENIDO_PROBLEMA.as_bytes());
+    if es_problema {
+        return respuesta;
+    }
+
+    let status = respuesta.status();
+    let idioma = idioma_actual();
+    let (codigo, titulo) = if status.is_client_error() {
+        (
+            "peticion_invalida",
+            idioma.elegir("Petición inválida", "Invalid request"),
+        )
+    } else {
+        (
+            "error_interno",
+            idioma.elegir("Error interno", "Internal error"),
+        )
+    };
+    ProblemDetails::new(codigo, titulo, status.as_u16(), error.to_string()).respuesta()
+}
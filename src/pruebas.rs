@@ -0,0 +1,29 @@
+//! Datos y conexiones compartidos por las pruebas.
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{Database, DatabaseConnection};
+
+use crate::services::catalogos_ubicacion::llenar_catalogos;
+
+/// Catálogos de prueba: CDMX (Benito Juárez, Coyoacán y Cuauhtémoc) y Guadalajara, con centroides
+pub const CATALOGOS_PRUEBA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/catalogos");
+
+/// BD SQLite en memoria con las migraciones corridas y los catálogos vacíos.
+pub async fn bd_sqlite_migrada() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:")
+        .await
+        .expect("Error al abrir SQLite en memoria");
+    Migrator::up(&db, None)
+        .await
+        .expect("Error al correr las migraciones");
+    db
+}
+
+/// BD SQLite en memoria con las migraciones corridas y los catálogos de prueba cargados.
+pub async fn bd_sqlite() -> DatabaseConnection {
+    let db = bd_sqlite_migrada().await;
+    llenar_catalogos(&db, CATALOGOS_PRUEBA)
+        .await
+        .expect("Error al llenar los catalogos");
+    db
+}
@@ -0,0 +1,120 @@
+//! Endpoints administrativos de catálogos (requieren token con scope `admin`)
+//! Ver documentación en Swagger
+use actix_multipart::Multipart;
+use actix_web::{get, http::header, post, web, HttpRequest, HttpResponse};
+use std::path::Path;
+use utoipa;
+
+use crate::{
+    config::catalogos::directorio_catalogos,
+    controllers::admin_catalogos::*,
+    services::{
+        cache_cp::CacheCodigosPostales, indice_geografico::IndiceGeografico,
+        limites_geograficos::LimitesGeograficos, recarga_catalogos::TrabajosRecarga,
+        repositorio::RepositorioUbicacion,
+    },
+    types::{
+        catalogos::*,
+        errores::{ProblemDetails, UbicacionError},
+    },
+};
+
+// Ruta para recargar los catálogos en segundo plano
+#[utoipa::path(
+    description = "Vacía y vuelve a cargar los catálogos en segundo plano, desde un directorio del servidor o desde CSV subidos como multipart/form-data",
+    path = "/api/admin/catalogos/recargar",
+    params(RecargaQuery),
+    responses(
+        (status = 202, description = "Se inició la recarga; consultar su avance con el job_id", body = TrabajoRecarga),
+        (status = 400, description = "Los archivos subidos no son catálogos válidos o el directorio no está dentro de CATALOGOS_DIR", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Ya hay una recarga en proceso", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[post("/catalogos/recargar")]
+#[allow(clippy::too_many_arguments)] // Un extractor por cada dato compartido que usa la recarga
+async fn recargar(
+    req: HttpRequest,
+    payload: web::Payload,
+    query: web::Query<RecargaQuery>,
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    trabajos: web::Data<TrabajosRecarga>,
+    cache: web::Data<CacheCodigosPostales>,
+    indice: web::Data<IndiceGeografico>,
+    limites: web::Data<LimitesGeograficos>,
+) -> Result<HttpResponse, UbicacionError> {
+    let es_multipart = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+
+    // Se rechaza antes de recibir los archivos subidos
+    if trabajos.en_proceso() {
+        return Err(UbicacionError::RecargaEnProceso);
+    }
+    let (directorio, temporal) = if es_multipart {
+        let directorio = guardar_catalogos_subidos(Multipart::new(req.headers(), payload)).await?;
+        (directorio, true)
+    } else {
+        let directorio = directorio_recarga(
+            Path::new(&directorio_catalogos()),
+            query.directorio.as_deref(),
+        )?;
+        (directorio, false)
+    };
+
+    let trabajo = recargar_catalogos_controller(
+        repositorio,
+        trabajos,
+        cache,
+        indice,
+        limites,
+        directorio,
+        temporal,
+    )
+    .await?;
+    Ok(HttpResponse::Accepted().json(trabajo))
+}
+
+// Ruta para consultar el estado de una recarga
+#[utoipa::path(
+    description = "Consultar el estado de una recarga de catálogos",
+    path = "/api/admin/catalogos/recargar/{job_id}",
+    params(
+        ("job_id" = u64, Path, description = "Id devuelto al iniciar la recarga", example = 1),
+    ),
+    responses(
+        (status = 200, description = "Estado actual de la recarga", body = TrabajoRecarga),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No existe el trabajo", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/catalogos/recargar/{job_id}")]
+async fn estado_recarga(
+    trabajos: web::Data<TrabajosRecarga>,
+    path_params: web::Path<TrabajoPayload>,
+) -> Result<HttpResponse, UbicacionError> {
+    let trabajo = estado_recarga_controller(trabajos, path_params.job_id).await?;
+    Ok(HttpResponse::Ok().json(trabajo))
+}
+
+// Ruta para consultar el estado de la caché de búsquedas por código postal
+#[utoipa::path(
+    description = "Consultar el estado de la caché de búsquedas por código postal",
+    path = "/api/admin/cache/cp",
+    responses(
+        (status = 200, description = "Capacidad, entradas y contadores de aciertos y fallos de la caché", body = EstadisticasCache),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/cache/cp")]
+async fn estadisticas_cache(cache: web::Data<CacheCodigosPostales>) -> HttpResponse {
+    HttpResponse::Ok().json(estadisticas_cache_controller(cache).await)
+}
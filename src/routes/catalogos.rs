@@ -1,10 +1,20 @@
 //! Endpoints relacionados con catálogos
 //! Ver documentación en Swagger
-use actix_web::{get, web, HttpResponse};
-use sea_orm::DatabaseConnection;
+use actix_web::{get, http::header, post, web, HttpRequest, HttpResponse};
 use utoipa;
 
-use crate::{controllers::catalogos::*, types::catalogos::*};
+use crate::{
+    controllers::catalogos::*,
+    services::{
+        cache_cp::CacheCodigosPostales, indice_geografico::IndiceGeografico,
+        limites_geograficos::LimitesGeograficos, repositorio::RepositorioUbicacion,
+    },
+    types::{
+        catalogos::*,
+        errores::{ProblemDetails, UbicacionError},
+        geojson::{ColeccionGeoJson, Formato, FormatoQuery, TIPO_CONTENIDO_GEOJSON},
+    },
+};
 
 // Ruta para buscar estado, municipio y localidades por código postal
 #[utoipa::path(
@@ -12,28 +22,232 @@ use crate::{controllers::catalogos::*, types::catalogos::*};
     path = "/api/busqueda-cp/{cp}",
     params(
         ("cp" = i32, Path, description = "Ruta para buscar estado, municipio y localidades por código postal", example = 14390),
+        FormatoQuery,
     ),
     responses(
-        (status = 200, description = "Se validó el CP y se encontraron datos vinculados a éste", body = CPResponse),
-        (status = 400, description = "Error en la petición, formato incorrecto del CP", body = String, example = "Formato de código postal inválido"),
-        (status = 404, description = "No se encontró el CP introducida", body = String, example = "Código postal no encontrado"),
-        (status = 500, description = "Error interno del servidor", body = String, example = "Error en la base de datos")
+        (status = 200, description = "Se validó el CP y se encontraron datos vinculados a éste", content(
+            (CPResponse = "application/json"),
+            (ColeccionGeoJson = "application/geo+json")
+        )),
+        (status = 400, description = "Error en la petición, formato incorrecto del CP", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No se encontró el CP introducida", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
     ),
     security(("bearer_auth"=[]))
 )]
 #[get("/busqueda-cp/{cp}")]
 async fn busqueda_cp(
-    db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    cache: web::Data<CacheCodigosPostales>,
+    limites: web::Data<LimitesGeograficos>,
     path_params: web::Path<CPPayload>,
-) -> HttpResponse {
-    match busqueda_cp_controller(db, path_params.cp).await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(e) if e.to_string().contains("inválido") => {
-            HttpResponse::BadRequest().body(e.to_string())
+    query: web::Query<FormatoQuery>,
+) -> Result<HttpResponse, UbicacionError> {
+    let accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok());
+    // El formato puede depender de `Accept`, por lo que las cachés intermedias deben distinguirlo
+    match Formato::elegir(query.formato, accept) {
+        Formato::Json => {
+            let result =
+                busqueda_cp_controller(repositorio.get_ref(), &cache, path_params.cp).await?;
+            Ok(HttpResponse::Ok()
+                .append_header((header::VARY, "Accept"))
+                .json(result))
         }
-        Err(e) if e.to_string().contains("no encontrado") => {
-            HttpResponse::NotFound().body(e.to_string())
+        Formato::Geojson => {
+            let result = busqueda_cp_geojson_controller(
+                repositorio.get_ref(),
+                &cache,
+                &limites,
+                path_params.cp,
+            )
+            .await?;
+            Ok(HttpResponse::Ok()
+                .append_header((header::VARY, "Accept"))
+                .content_type(TIPO_CONTENIDO_GEOJSON)
+                .json(result))
         }
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
     }
 }
+
+// Ruta para obtener los municipios de un estado como GeoJSON
+#[utoipa::path(
+    description = "Obtener los municipios de un estado como FeatureCollection de GeoJSON, con sus límites si se cargaron",
+    path = "/api/estados/{id_estado}/municipios",
+    params(
+        ("id_estado" = i32, Path, description = "Id (clave INEGI) del estado", example = 9),
+    ),
+    responses(
+        (status = 200, description = "Municipios del estado", body = ColeccionGeoJson, content_type = "application/geo+json"),
+        (status = 404, description = "No se encontró el estado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/estados/{id_estado}/municipios")]
+async fn municipios_estado(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    limites: web::Data<LimitesGeograficos>,
+    id_estado: web::Path<i32>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result =
+        municipios_estado_controller(repositorio.get_ref(), &limites, id_estado.into_inner())
+            .await?;
+    Ok(HttpResponse::Ok()
+        .content_type(TIPO_CONTENIDO_GEOJSON)
+        .json(result))
+}
+
+// Ruta para obtener los códigos postales de un municipio como GeoJSON
+#[utoipa::path(
+    description = "Obtener los códigos postales de un municipio como FeatureCollection de GeoJSON, con sus límites si se cargaron o su centroide",
+    path = "/api/municipios/{id_municipio}/codigos-postales",
+    params(
+        ("id_municipio" = i32, Path, description = "Id del municipio", example = 1),
+    ),
+    responses(
+        (status = 200, description = "Códigos postales del municipio", body = ColeccionGeoJson, content_type = "application/geo+json"),
+        (status = 404, description = "No se encontró el municipio", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/municipios/{id_municipio}/codigos-postales")]
+async fn codigos_postales_municipio(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    limites: web::Data<LimitesGeograficos>,
+    id_municipio: web::Path<i32>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = codigos_postales_municipio_controller(
+        repositorio.get_ref(),
+        &limites,
+        id_municipio.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .content_type(TIPO_CONTENIDO_GEOJSON)
+        .json(result))
+}
+
+// Ruta para buscar los códigos postales y localidades más cercanos a unas coordenadas
+#[utoipa::path(
+    description = "Obtener los códigos postales y localidades más cercanos a unas coordenadas (geocodificación inversa)",
+    path = "/api/geocodificacion-inversa",
+    params(GeocodificacionQuery),
+    responses(
+        (status = 200, description = "Centroides más cercanos, del más cercano al más lejano", body = Vec<ResultadoGeocodificacion>),
+        (status = 400, description = "Coordenadas fuera de rango o no numéricas", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/geocodificacion-inversa")]
+async fn geocodificacion_inversa(
+    indice: web::Data<IndiceGeografico>,
+    query: web::Query<GeocodificacionQuery>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = geocodificacion_inversa_controller(&indice, query.lat, query.lon, query.n)?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para obtener el municipio que contiene unas coordenadas
+#[utoipa::path(
+    description = "Obtener el estado y municipio (y el código postal, si hay límites de CPs) cuyo polígono contiene unas coordenadas",
+    path = "/api/geocodificacion-inversa/municipio",
+    params(CoordenadasQuery),
+    responses(
+        (status = 200, description = "Municipio que contiene las coordenadas", body = MunicipioCoordenadasResponse),
+        (status = 400, description = "Coordenadas fuera de rango o no numéricas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Ningún municipio contiene las coordenadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 503, description = "No se cargaron límites de municipios", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/geocodificacion-inversa/municipio")]
+async fn municipio_coordenadas(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    limites: web::Data<LimitesGeograficos>,
+    query: web::Query<CoordenadasQuery>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result =
+        municipio_coordenadas_controller(repositorio.get_ref(), &limites, query.lat, query.lon)
+            .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para calcular la distancia entre dos códigos postales
+#[utoipa::path(
+    description = "Calcular la distancia de círculo máximo entre los centroides de dos códigos postales",
+    path = "/api/distancia",
+    params(DistanciaQuery),
+    responses(
+        (status = 200, description = "Distancia en metros entre los centroides", body = DistanciaResponse),
+        (status = 400, description = "Error en la petición, formato incorrecto del CP", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No se encontró el CP introducida", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Alguno de los códigos postales no tiene coordenadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/distancia")]
+async fn distancia(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    cache: web::Data<CacheCodigosPostales>,
+    query: web::Query<DistanciaQuery>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result =
+        distancia_controller(repositorio.get_ref(), &cache, query.origen, query.destino).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para calcular la matriz de distancias entre varios códigos postales
+#[utoipa::path(
+    description = "Calcular la distancia entre cada código postal de origen y cada código postal de destino (hasta 50 de cada uno)",
+    path = "/api/distancia/matriz",
+    request_body = MatrizDistanciasPayload,
+    responses(
+        (status = 200, description = "Distancias en metros, un renglón por origen", body = MatrizDistanciasResponse),
+        (status = 400, description = "Lista de orígenes o destinos vacía o demasiado grande, o CP con formato incorrecto", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No se encontró el CP introducida", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Alguno de los códigos postales no tiene coordenadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[post("/distancia/matriz")]
+async fn matriz_distancias(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    payload: web::Json<MatrizDistanciasPayload>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = matriz_distancias_controller(repositorio.get_ref(), payload.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para buscar los códigos postales dentro de un radio de otro código postal
+#[utoipa::path(
+    description = "Obtener los códigos postales cuyo centroide está dentro de un radio del centroide de un código postal, con su distancia, municipio y estado",
+    path = "/api/codigos-postales/cercanos",
+    params(CercanosQuery),
+    responses(
+        (status = 200, description = "Página de códigos postales dentro del radio", body = CercanosResponse),
+        (status = 400, description = "Radio fuera de rango o formato incorrecto del CP", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No se encontró el CP introducida", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "El código postal no tiene coordenadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/codigos-postales/cercanos")]
+async fn codigos_postales_cercanos(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    cache: web::Data<CacheCodigosPostales>,
+    indice: web::Data<IndiceGeografico>,
+    query: web::Query<CercanosQuery>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result =
+        cercanos_controller(repositorio.get_ref(), &cache, &indice, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -1 +1,3 @@
-pub mod catalogos;
\ No newline at end of file
+pub mod admin_catalogos;
+pub mod catalogos;
+pub mod zonas;
\ No newline at end of file
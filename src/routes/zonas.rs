@@ -0,0 +1,169 @@
+//! Endpoints de zonas de entrega: consulta por código postal y administración (requiere token con scope `admin`)
+//! Ver documentación en Swagger
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use utoipa;
+
+use crate::{
+    controllers::zonas::*,
+    services::repositorio::RepositorioUbicacion,
+    types::{
+        catalogos::CPPayload,
+        errores::{ProblemDetails, UbicacionError},
+        zonas::*,
+    },
+};
+
+// Ruta para obtener las zonas que contienen un código postal
+#[utoipa::path(
+    description = "Obtener las zonas de entrega que contienen un código postal, por el mismo código postal, por un rango o por su municipio",
+    path = "/api/busqueda-cp/{cp}/zonas",
+    params(
+        ("cp" = i32, Path, description = "Código postal a consultar", example = 6600),
+    ),
+    responses(
+        (status = 200, description = "Zonas que contienen el código postal; puede no haber ninguna", body = ZonasCPResponse),
+        (status = 400, description = "Error en la petición, formato incorrecto del CP", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No se encontró el CP introducida", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/busqueda-cp/{cp}/zonas")]
+async fn zonas_cp(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    path_params: web::Path<CPPayload>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = zonas_cp_controller(repositorio.get_ref(), path_params.cp).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para listar las zonas
+#[utoipa::path(
+    description = "Listar las zonas de entrega con sus códigos postales, rangos y municipios",
+    path = "/api/admin/zonas",
+    responses(
+        (status = 200, description = "Zonas ordenadas por id", body = Vec<Zona>),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/zonas")]
+async fn listar_zonas(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = zonas_controller(repositorio.get_ref()).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para crear una zona
+#[utoipa::path(
+    description = "Crear una zona de entrega a partir de códigos postales, rangos de códigos postales y municipios",
+    path = "/api/admin/zonas",
+    request_body = ZonaPayload,
+    responses(
+        (status = 201, description = "Zona creada", body = Zona),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Ya existe una zona con el mismo nombre", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Los datos de la zona no son válidos", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 501, description = "Con FUENTE_DATOS=memoria las zonas no se pueden modificar", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[post("/zonas")]
+async fn crear_zona(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    payload: web::Json<ZonaPayload>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = crear_zona_controller(repositorio.get_ref(), payload.into_inner()).await?;
+    Ok(HttpResponse::Created().json(result))
+}
+
+// Ruta para consultar una zona
+#[utoipa::path(
+    description = "Consultar una zona de entrega con sus códigos postales, rangos y municipios",
+    path = "/api/admin/zonas/{id_zona}",
+    params(
+        ("id_zona" = i32, Path, description = "Id de la zona", example = 1),
+    ),
+    responses(
+        (status = 200, description = "Zona con sus miembros", body = Zona),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No existe la zona", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[get("/zonas/{id_zona}")]
+async fn consultar_zona(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    id_zona: web::Path<i32>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = zona_controller(repositorio.get_ref(), id_zona.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para reemplazar una zona
+#[utoipa::path(
+    description = "Reemplazar el nombre, la descripción y los miembros de una zona de entrega",
+    path = "/api/admin/zonas/{id_zona}",
+    params(
+        ("id_zona" = i32, Path, description = "Id de la zona", example = 1),
+    ),
+    request_body = ZonaPayload,
+    responses(
+        (status = 200, description = "Zona actualizada", body = Zona),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No existe la zona", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Ya existe una zona con el mismo nombre", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Los datos de la zona no son válidos", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 501, description = "Con FUENTE_DATOS=memoria las zonas no se pueden modificar", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[put("/zonas/{id_zona}")]
+async fn actualizar_zona(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    id_zona: web::Path<i32>,
+    payload: web::Json<ZonaPayload>,
+) -> Result<HttpResponse, UbicacionError> {
+    let result = actualizar_zona_controller(
+        repositorio.get_ref(),
+        id_zona.into_inner(),
+        payload.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Ruta para eliminar una zona
+#[utoipa::path(
+    description = "Eliminar una zona de entrega con sus miembros",
+    path = "/api/admin/zonas/{id_zona}",
+    params(
+        ("id_zona" = i32, Path, description = "Id de la zona", example = 1),
+    ),
+    responses(
+        (status = 204, description = "Zona eliminada"),
+        (status = 401, description = "El token no es válido o expiró", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El token no tiene scope admin", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No existe la zona", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 501, description = "Con FUENTE_DATOS=memoria las zonas no se pueden modificar", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("bearer_auth"=[]))
+)]
+#[delete("/zonas/{id_zona}")]
+async fn eliminar_zona(
+    repositorio: web::Data<dyn RepositorioUbicacion>,
+    id_zona: web::Path<i32>,
+) -> Result<HttpResponse, UbicacionError> {
+    eliminar_zona_controller(repositorio.get_ref(), id_zona.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
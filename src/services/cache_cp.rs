@@ -0,0 +1,176 @@
+//! # Caché de búsquedas por código postal
+//! En este módulo se incluye la caché LRU con expiración de las respuestas de `busqueda-cp`.
+//! Los catálogos cambian pocas veces al año, por lo que la mayoría de las búsquedas
+//! se pueden responder sin consultar la base de datos.
+use crate::types::catalogos::{CPResponse, EstadisticasCache};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Caché de [`CPResponse`] por código postal, con capacidad acotada (LRU) y tiempo de vida.
+/// Se comparte entre workers con `web::Data`, por lo que debe crearse fuera de `HttpServer::new`.
+///
+/// Cada [`invalidar`](Self::invalidar) avanza la generación de la caché. Una búsqueda que empezó
+/// antes de invalidar puede haber leído los catálogos anteriores, por lo que [`guardar`](Self::guardar)
+/// descarta las respuestas de una generación que ya no es la actual.
+#[derive(Debug)]
+pub struct CacheCodigosPostales {
+    /// `None` si la caché está desactivada (capacidad 0)
+    entradas: Option<Mutex<Entradas>>,
+    capacidad: usize,
+    ttl: Duration,
+    aciertos: AtomicU64,
+    fallos: AtomicU64,
+}
+
+/// Respuestas guardadas y generación a la que pertenecen; se modifican juntas bajo el mismo `Mutex`.
+#[derive(Debug)]
+struct Entradas {
+    respuestas: LruCache<i32, (Instant, CPResponse)>,
+    generacion: u64,
+}
+
+impl CacheCodigosPostales {
+    /// Crea la caché. Con `capacidad` 0 no se guarda nada y todas las búsquedas cuentan como fallo.
+    pub fn new(capacidad: usize, ttl: Duration) -> Self {
+        CacheCodigosPostales {
+            entradas: NonZeroUsize::new(capacidad).map(|c| {
+                Mutex::new(Entradas {
+                    respuestas: LruCache::new(c),
+                    generacion: 0,
+                })
+            }),
+            capacidad,
+            ttl,
+            aciertos: AtomicU64::new(0),
+            fallos: AtomicU64::new(0),
+        }
+    }
+
+    /// Devuelve la respuesta guardada para `cp` si existe y no ha expirado.
+    pub fn obtener(&self, cp: i32) -> Option<CPResponse> {
+        let respuesta = self.entradas.as_ref().and_then(|entradas| {
+            let mut entradas = entradas.lock().unwrap();
+            match entradas.respuestas.get(&cp) {
+                Some((guardada, respuesta)) if guardada.elapsed() < self.ttl => {
+                    Some(respuesta.clone())
+                }
+                Some(_) => {
+                    entradas.respuestas.pop(&cp);
+                    None
+                }
+                None => None,
+            }
+        });
+
+        let contador = if respuesta.is_some() {
+            &self.aciertos
+        } else {
+            &self.fallos
+        };
+        contador.fetch_add(1, Ordering::Relaxed);
+        respuesta
+    }
+
+    /// Generación actual de la caché. Se lee antes de consultar el repositorio y se pasa a
+    /// [`guardar`](Self::guardar) junto con la respuesta.
+    pub fn generacion(&self) -> u64 {
+        self.entradas
+            .as_ref()
+            .map_or(0, |entradas| entradas.lock().unwrap().generacion)
+    }
+
+    /// Guarda la respuesta de `cp`, consultada en la generación `generacion`; si la caché está
+    /// llena se descarta la menos usada. Si la caché se invalidó mientras tanto, no se guarda.
+    pub fn guardar(&self, cp: i32, respuesta: &CPResponse, generacion: u64) {
+        if let Some(entradas) = &self.entradas {
+            let mut entradas = entradas.lock().unwrap();
+            if entradas.generacion == generacion {
+                entradas
+                    .respuestas
+                    .put(cp, (Instant::now(), respuesta.clone()));
+            }
+        }
+    }
+
+    /// Vacía la caché y avanza su generación, p. ej. después de recargar los catálogos.
+    /// Conserva los contadores.
+    pub fn invalidar(&self) {
+        if let Some(entradas) = &self.entradas {
+            let mut entradas = entradas.lock().unwrap();
+            entradas.respuestas.clear();
+            entradas.generacion += 1;
+        }
+    }
+
+    /// Estado actual de la caché y contadores de aciertos y fallos desde que arrancó el servicio.
+    pub fn estadisticas(&self) -> EstadisticasCache {
+        EstadisticasCache {
+            capacidad: self.capacidad,
+            entradas: self
+                .entradas
+                .as_ref()
+                .map_or(0, |entradas| entradas.lock().unwrap().respuestas.len()),
+            ttl_segundos: self.ttl.as_secs(),
+            aciertos: self.aciertos.load(Ordering::Relaxed),
+            fallos: self.fallos.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::conversores::CatalogoIdCadena;
+
+    fn respuesta(municipio: &str) -> CPResponse {
+        CPResponse {
+            estado: CatalogoIdCadena {
+                id: 9,
+                value: "Ciudad de México".to_string(),
+            },
+            municipio: CatalogoIdCadena {
+                id: 15,
+                value: municipio.to_string(),
+            },
+            localidades: Vec::new(),
+            latitud: None,
+            longitud: None,
+        }
+    }
+
+    #[test]
+    fn guarda_y_cuenta_aciertos_y_fallos() {
+        let cache = CacheCodigosPostales::new(10, Duration::from_secs(60));
+        assert!(cache.obtener(6600).is_none());
+        cache.guardar(6600, &respuesta("Cuauhtémoc"), cache.generacion());
+        assert_eq!(cache.obtener(6600).unwrap().municipio.value, "Cuauhtémoc");
+
+        let estadisticas = cache.estadisticas();
+        assert_eq!((estadisticas.aciertos, estadisticas.fallos), (1, 1));
+        assert_eq!(estadisticas.entradas, 1);
+    }
+
+    #[test]
+    fn descarta_respuestas_consultadas_antes_de_invalidar() {
+        let cache = CacheCodigosPostales::new(10, Duration::from_secs(60));
+        // La búsqueda lee la generación, la recarga invalida y después la búsqueda guarda
+        let generacion = cache.generacion();
+        cache.invalidar();
+        cache.guardar(6600, &respuesta("Catálogo anterior"), generacion);
+        assert!(cache.obtener(6600).is_none());
+
+        cache.guardar(6600, &respuesta("Cuauhtémoc"), cache.generacion());
+        assert_eq!(cache.obtener(6600).unwrap().municipio.value, "Cuauhtémoc");
+    }
+
+    #[test]
+    fn sin_capacidad_no_guarda() {
+        let cache = CacheCodigosPostales::new(0, Duration::from_secs(60));
+        cache.guardar(6600, &respuesta("Cuauhtémoc"), cache.generacion());
+        assert!(cache.obtener(6600).is_none());
+        assert_eq!(cache.estadisticas().entradas, 0);
+    }
+}
@@ -2,188 +2,567 @@
 //! En este módulo se incluyen estructuras y funciones con los siguientes fines:
 //! - Estructuras para deserializar los CSV con información de localidades, municipios, etc. 
 //! - Funciones para poblar las tablas (catálogos de estado, municipio, etc) en la base de datos
-use crate::{entities::prelude::*, entities::*, utils::conversores::leer_catalogo};
-use sea_orm::{DatabaseConnection, EntityTrait, Set};
-use actix_web::{error, Error};
+//! - Lectura de las coordenadas (centroides) opcionales de códigos postales y localidades
+//!
+//! Cada carga se ejecuta dentro de una transacción: si un renglón es inválido o falla una
+//! inserción, la base queda intacta y el error indica el archivo y la línea del CSV.
+use crate::{
+    entities::prelude::*, entities::*, services::validacion_catalogos::validar_catalogos,
+    types::errores::UbicacionError,
+    utils::conversores::{existe_catalogo, leer_catalogo, linea_renglon},
+};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Iterable, Set,
+    TransactionTrait,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+/// Máximo de registros por lote
 const BATCH_SIZE: usize = 5000;
+/// Máximo de parámetros por sentencia que acepta SQLite (`SQLITE_MAX_VARIABLE_NUMBER`);
+/// Postgres y MySQL aceptan 65535
+const MAXIMO_PARAMETROS: usize = 32766;
+/// Decimales que se guardan de cada coordenada (columnas `decimal(9,6)`)
+const DECIMALES_COORDENADA: u32 = 6;
 
 /// Estructura para deserializar el csv de estados. 
 /// Lee únicamente Id (que coincide con los ids políticos) y nombre del estado.
 #[derive(Debug, serde::Deserialize)]
-struct Estado {
-    id_estado: i32,
-    estado: String,
+pub(crate) struct Estado {
+    pub id_estado: i32,
+    pub estado: String,
 }
 /// Estructura para deserializar el csv de municipios. 
 /// Lee id, nombre del municipio y id del estado al que pertenece el municipio.
 #[derive(Debug, serde::Deserialize)]
-struct Municipio {
-    id_municipio: i32,
-    municipio: String,
-    id_estado: i32,
+pub(crate) struct Municipio {
+    pub id_municipio: i32,
+    pub municipio: String,
+    pub id_estado: i32,
 }
 /// Estructura para deserializar el csv de códigos postales. 
 /// Lee código postal (que actúa como su propio id) y id del municipio y del estado al que pertenece.
 #[derive(Debug, serde::Deserialize)]
-struct CodigoPostal {
-    cp: i32,
-    id_municipio: i32,
-    id_estado: i32,
+pub(crate) struct CodigoPostal {
+    pub cp: i32,
+    pub id_municipio: i32,
+    pub id_estado: i32,
 }
 /// Estructura para deserializar el csv de municipios. 
 /// Lee id, nombre de la localidad y código postal y ids del estado y muninicipio al que pertenece.
 #[derive(Debug, serde::Deserialize)]
-struct Localidad {
+pub(crate) struct Localidad {
     pub id_localidad: i32,
     pub localidad: String,
     pub cp: i32,
     pub id_municipio: i32,
     pub id_estado: i32,
 }
+/// Estructura para deserializar los csv de coordenadas (`coordenadas_codigos_postales` y `coordenadas_localidades`).
+/// Lee el código postal o el id de la localidad y su centroide en grados decimales.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Coordenada {
+    #[serde(alias = "cp", alias = "id_localidad")]
+    pub id: i32,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub latitud: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub longitud: Decimal,
+}
+
+/// Carga los cuatro catálogos (estados, municipios, códigos postales y localidades) en una
+/// sola transacción. Si cualquiera de las cargas falla, no se inserta ningún registro.
+/// - Si las cuatro tablas ya tienen registros, no se leen los archivos.
+/// - Antes de insertar se validan todos los CSV ([`validar_catalogos`]).
+/// - Cada tabla se llena únicamente si está vacía, igual que en las cargas individuales.
+///
+/// ## Argumentos
+/// * `db` - Conexión activa a la base de datos.
+/// * `catalogos_path` - Ruta base donde se encuentran los archivos CSV de los catálogos.
+///
+/// ## Errores
+/// Retorna [`UbicacionError::CatalogosInvalidos`] con el reporte de validación si hay renglones inválidos,
+/// o con el archivo y la línea del renglón que provocó el error al insertar.
+///
+/// ## Ejemplo
+/// ```rust
+/// llenar_catalogos(&db, "./catalogos").await?;
+/// ```
+pub async fn llenar_catalogos(
+    db: &DatabaseConnection,
+    catalogos_path: &str,
+) -> Result<(), UbicacionError> {
+    // Si los cuatro catálogos ya tienen registros no hay nada que validar ni cargar
+    if !tabla_vacia::<CatEstados, _>(db).await?
+        && !tabla_vacia::<CatMunicipios, _>(db).await?
+        && !tabla_vacia::<CatCodigosPostales, _>(db).await?
+        && !tabla_vacia::<CatLocalidades, _>(db).await?
+    {
+        return Ok(());
+    }
+    validar(catalogos_path)?;
+
+    let txn = db.begin().await?;
+    llenar_catalogos_estados_municipios(&txn, catalogos_path).await?;
+    llenar_catalogos_cps(&txn, catalogos_path).await?;
+    llenar_catalogos_localidades(&txn, catalogos_path).await?;
+    Ok(txn.commit().await?)
+}
+
+/// Vacía los cuatro catálogos y los vuelve a cargar desde `catalogos_path`.
+/// - Antes de vaciar las tablas se validan todos los CSV ([`validar_catalogos`]).
+/// - Las tablas se vacían en orden inverso a sus dependencias (localidades, códigos postales, municipios, estados).
+/// - Después se ejecutan las mismas cargas que se usan al iniciar el servicio.
+/// - Todo ocurre en una transacción, por lo que si la carga falla se conservan los catálogos anteriores.
+///
+/// ## Argumentos
+/// * `db` - Conexión activa a la base de datos.
+/// * `catalogos_path` - Ruta base donde se encuentran los archivos CSV de los catálogos.
+///
+/// ## Errores
+/// Retorna [`UbicacionError::CatalogosInvalidos`] con el reporte de validación si hay renglones inválidos,
+/// si ocurre algún problema al vaciar las tablas, o con el archivo y la línea del renglón que provocó el error al insertar.
+///
+/// ## Ejemplo
+/// ```rust
+/// recargar_catalogos(&db, "./catalogos").await?;
+/// ```
+pub async fn recargar_catalogos(
+    db: &DatabaseConnection,
+    catalogos_path: &str,
+) -> Result<(), UbicacionError> {
+    validar(catalogos_path)?;
+
+    let txn = db.begin().await?;
+    CatLocalidades::delete_many().exec(&txn).await?;
+    CatCodigosPostales::delete_many().exec(&txn).await?;
+    CatMunicipios::delete_many().exec(&txn).await?;
+    CatEstados::delete_many().exec(&txn).await?;
+
+    llenar_catalogos_estados_municipios(&txn, catalogos_path).await?;
+    llenar_catalogos_cps(&txn, catalogos_path).await?;
+    llenar_catalogos_localidades(&txn, catalogos_path).await?;
+    Ok(txn.commit().await?)
+}
 
 /// Carga todas los estado y municipios desde un archivo CSV y los inserta en la base de datos.
 /// - Si la tabla `cat_estados` ya contiene registros, no se realiza ninguna inserción.
 /// - Si la tabla está vacía, se leen los registros desde `cat_estados.csv` y se insertan en lotes.
 /// - Repite lo mismo para municipios.
+/// - Ambas tablas se cargan en una misma transacción.
 ///
 /// ## Argumentos
-/// * `db` - Conexión activa a la base de datos.
+/// * `db` - Conexión activa a la base de datos, o transacción en curso (en cuyo caso la carga usa un savepoint).
 /// * `catalogos_path` - Ruta base donde se encuentran los archivos CSV de estados y municipios.
 ///
 /// ## Errores
-/// Retorna un InternalServerError ([`actix_web::Error`]) si ocurre algún problema al leer los archivos CSV o insertar en la base de datos.
+/// Retorna un [`UbicacionError`] con el archivo y la línea del renglón que provocó el error.
 ///
 /// ## Ejemplo
 /// ```rust
 /// llenar_catalogos_municipios(&db, "./catalogos").await?;
 /// ```
-pub async fn llenar_catalogos_estados_municipios(
-    db: &DatabaseConnection,
+pub async fn llenar_catalogos_estados_municipios<C>(
+    db: &C,
     catalogos_path: &str,
-) -> Result<(), Error> {
+) -> Result<(), UbicacionError>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let txn = db.begin().await?;
     // Verifica si ya existen registros en la tabla de estados
-    let registros_edos = CatEstados::find().all(db).await.map_err(error::ErrorInternalServerError)?;
-    if registros_edos.is_empty() {
-        // Carga los registros desde CSV
-        let mut estados = Vec::new();
-        let mut rdr = leer_catalogo(catalogos_path, "cat_estados");
-        for result in rdr.deserialize() {
-            let record: Estado = result.map_err(error::ErrorInternalServerError)?;
+    if tabla_vacia::<CatEstados, _>(&txn).await? {
+        // Inserta los registros conforme se leen del CSV
+        let mut estados = Lote::new("cat_estados");
+        for registro in registros::<Estado>(catalogos_path, "cat_estados")? {
+            let (linea, record) = registro?;
             let modelo = cat_estados::ActiveModel {
                 id: Set(record.id_estado),
                 estado: Set(record.estado),
             };
-            estados.push(modelo);
+            estados.agregar(&txn, linea, modelo).await?;
         }
-        // Inserta los registros. Como son pocos, no es necesario insertalos por chunks.
-        CatEstados::insert_many(estados).exec(db).await.map_err(error::ErrorInternalServerError)?;
+        estados.insertar(&txn).await?;
     }
 
     // Verifica si ya existen registros en la tabla de municipios
-    let registros_muns = CatMunicipios::find().all(db).await.map_err(error::ErrorInternalServerError)?;
-    if registros_muns.is_empty() {
-        // Carga los registros desde CSV
-        let mut municipios = Vec::new();
-        let mut rdr = leer_catalogo(catalogos_path, "cat_municipios");
-        for result in rdr.deserialize() {
-            let record: Municipio = result.map_err(error::ErrorInternalServerError)?;
+    if tabla_vacia::<CatMunicipios, _>(&txn).await? {
+        // Inserta los registros conforme se leen del CSV
+        let mut municipios = Lote::new("cat_municipios");
+        for registro in registros::<Municipio>(catalogos_path, "cat_municipios")? {
+            let (linea, record) = registro?;
             let modelo = cat_municipios::ActiveModel {
                 id: Set(record.id_municipio),
                 municipio: Set(record.municipio),
                 id_estado: Set(record.id_estado),
             };
-            municipios.push(modelo);
+            municipios.agregar(&txn, linea, modelo).await?;
         }
-        // Inserta los registros. Como son "pocos", no es necesario insertalos por chunks.
-        CatMunicipios::insert_many(municipios).exec(db).await.map_err(error::ErrorInternalServerError)?;
+        municipios.insertar(&txn).await?;
     }
-    Ok(())
+    Ok(txn.commit().await?)
 }
 
 /// Carga todos los códigos postales desde un archivo CSV y los inserta en la base de datos.
 /// - Si la tabla `cat_codigos_postales` ya contiene registros, no se realiza ninguna inserción.
-/// - Si la tabla está vacía, se leen los registros desde `cat_codigos_postales.csv` y se insertan en lotes.
+/// - Si la tabla está vacía, se leen los registros desde `cat_codigos_postales.csv` y se insertan
+///   en lotes conforme se leen, por lo que nunca hay más de `BATCH_SIZE` registros en memoria.
+/// - La carga se ejecuta en una transacción.
 ///
 /// ## Argumentos
-/// * `db` - Conexión activa a la base de datos.
+/// * `db` - Conexión activa a la base de datos, o transacción en curso (en cuyo caso la carga usa un savepoint).
 /// * `catalogos_path` - Ruta base donde se encuentra el archivo CSV de códigos postales.
 ///
 /// ## Errores
-/// Retorna un InternalServerError ([`actix_web::Error`]) si ocurre algún problema al leer el archivo CSV o insertar en la base de datos.
+/// Retorna un [`UbicacionError`] con el archivo y la línea del renglón que provocó el error.
 ///
 /// ## Ejemplo
 /// ```rust
 /// llenar_catalogos_cps(&db, "./catalogos").await?;
 /// ```
-pub async fn llenar_catalogos_cps(
-    db: &DatabaseConnection,
-    catalogos_path: &str,
-) -> Result<(), Error> {
+pub async fn llenar_catalogos_cps<C>(db: &C, catalogos_path: &str) -> Result<(), UbicacionError>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let txn = db.begin().await?;
     // Verifica si ya existen registros en la tabla
-    let registros = CatCodigosPostales::find().all(db).await.map_err(error::ErrorInternalServerError)?;
-    if registros.is_empty() {
-        // Carga los registros desde CSV
-        let mut rdr = leer_catalogo(catalogos_path, "cat_codigos_postales");
-        let mut codigos_postales = Vec::new();
-        for result in rdr.deserialize() {
-            let record: CodigoPostal = result.map_err(error::ErrorInternalServerError)?;
+    if tabla_vacia::<CatCodigosPostales, _>(&txn).await? {
+        // Inserta los registros conforme se leen del CSV
+        let coordenadas = coordenadas(catalogos_path, "coordenadas_codigos_postales")?;
+        let mut codigos_postales = Lote::new("cat_codigos_postales");
+        for registro in registros::<CodigoPostal>(catalogos_path, "cat_codigos_postales")? {
+            let (linea, record) = registro?;
+            let (latitud, longitud) = coordenadas.get(&record.cp).copied().unzip();
             let modelo = cat_codigos_postales::ActiveModel {
                 codigo_postal: Set(record.cp),
                 id_municipio: Set(record.id_municipio),
                 id_estado: Set(record.id_estado),
+                latitud: Set(latitud),
+                longitud: Set(longitud),
             };
-            codigos_postales.push(modelo);
-        }
-        // Inserta los registros en lotes
-        for chunk in codigos_postales.chunks(BATCH_SIZE) {
-            CatCodigosPostales::insert_many(chunk.to_vec())
-                .exec(db)
-                .await.map_err(error::ErrorInternalServerError)?;
+            codigos_postales.agregar(&txn, linea, modelo).await?;
         }
+        codigos_postales.insertar(&txn).await?;
     }
-    Ok(())
+    Ok(txn.commit().await?)
 }
 
 /// Carga todas las localidades desde un archivo CSV y las inserta en la base de datos.
 /// - Si la tabla `cat_localidades` ya contiene registros, no se realiza ninguna inserción.
-/// - Si la tabla está vacía, se leen los registros desde `cat_localidades.csv` y se insertan en lotes.
+/// - Si la tabla está vacía, se leen los registros desde `cat_localidades.csv` y se insertan
+///   en lotes conforme se leen, por lo que nunca hay más de `BATCH_SIZE` registros en memoria.
+/// - La carga se ejecuta en una transacción.
 ///
 /// ## Argumentos
-/// * `db` - Conexión activa a la base de datos.
+/// * `db` - Conexión activa a la base de datos, o transacción en curso (en cuyo caso la carga usa un savepoint).
 /// * `catalogos_path` - Ruta base donde se encuentra el archivo CSV de localidades.
 ///
 /// ## Errores
-/// Retorna un InternalServerError ([`actix_web::Error`]) si ocurre algún problema al leer el archivo CSV o insertar en la base de datos.
+/// Retorna un [`UbicacionError`] con el archivo y la línea del renglón que provocó el error.
 ///
 /// ## Ejemplo
 /// ```rust
 /// llenar_catalogos_localidades(&db, "./catalogos").await?;
 /// ```
-pub async fn llenar_catalogos_localidades(
-    db: &DatabaseConnection,
+pub async fn llenar_catalogos_localidades<C>(
+    db: &C,
     catalogos_path: &str,
-) -> Result<(), Error> {
+) -> Result<(), UbicacionError>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let txn = db.begin().await?;
     // Verifica si ya existen registros en la tabla
-    let registros = CatLocalidades::find().all(db).await.map_err(error::ErrorInternalServerError)?;
-    if registros.is_empty() {
-        // Carga los registros desde CSV
-        let mut rdr = leer_catalogo(catalogos_path, "cat_localidades");
-        let mut localidades = Vec::new();
-        for result in rdr.deserialize() {
-            let record: Localidad = result.map_err(error::ErrorInternalServerError)?;
+    if tabla_vacia::<CatLocalidades, _>(&txn).await? {
+        // Inserta los registros conforme se leen del CSV
+        let coordenadas = coordenadas(catalogos_path, "coordenadas_localidades")?;
+        let mut localidades = Lote::new("cat_localidades");
+        for registro in registros::<Localidad>(catalogos_path, "cat_localidades")? {
+            let (linea, record) = registro?;
+            let (latitud, longitud) = coordenadas.get(&record.id_localidad).copied().unzip();
             let modelo = cat_localidades::ActiveModel {
                 id: Set(record.id_localidad),
                 localidad: Set(record.localidad),
                 codigo_postal: Set(record.cp),
                 id_municipio: Set(record.id_municipio),
                 id_estado: Set(record.id_estado),
+                latitud: Set(latitud),
+                longitud: Set(longitud),
             };
-            localidades.push(modelo);
+            localidades.agregar(&txn, linea, modelo).await?;
+        }
+        localidades.insertar(&txn).await?;
+    }
+    Ok(txn.commit().await?)
+}
+
+/// Valida los CSV de `catalogos_path` y devuelve el reporte completo como error si hay renglones inválidos.
+pub(crate) fn validar(catalogos_path: &str) -> Result<(), UbicacionError> {
+    let reporte = validar_catalogos(catalogos_path);
+    if reporte.es_valido() {
+        Ok(())
+    } else {
+        Err(UbicacionError::CatalogosInvalidos(reporte))
+    }
+}
+
+/// Lee el CSV de coordenadas `catalogo` y devuelve latitud y longitud por id, con
+/// [`DECIMALES_COORDENADA`] decimales, como las devuelve la BD. El archivo es opcional: si no
+/// existe el mapa queda vacío y los registros se cargan sin coordenadas.
+///
+/// ## Argumentos
+/// * `catalogos_path` - Ruta base donde se encuentran los archivos CSV de los catálogos.
+/// * `catalogo` - `coordenadas_codigos_postales` o `coordenadas_localidades`.
+///
+/// ## Errores
+/// Retorna un [`UbicacionError`] con el archivo y la línea del renglón que no se pudo leer.
+pub(crate) fn coordenadas(
+    catalogos_path: &str,
+    catalogo: &'static str,
+) -> Result<HashMap<i32, (Decimal, Decimal)>, UbicacionError> {
+    let mut coordenadas = HashMap::new();
+    if !existe_catalogo(catalogos_path, catalogo) {
+        return Ok(coordenadas);
+    }
+    for registro in registros::<Coordenada>(catalogos_path, catalogo)? {
+        let (_, mut coordenada) = registro?;
+        coordenada.latitud.rescale(DECIMALES_COORDENADA);
+        coordenada.longitud.rescale(DECIMALES_COORDENADA);
+        coordenadas.insert(coordenada.id, (coordenada.latitud, coordenada.longitud));
+    }
+    Ok(coordenadas)
+}
+
+/// Indica si la tabla de la entidad `E` no tiene registros.
+/// Sólo consulta un registro para no traer toda la tabla a memoria.
+async fn tabla_vacia<E: EntityTrait, C: ConnectionTrait>(
+    conn: &C,
+) -> Result<bool, UbicacionError> {
+    Ok(E::find().one(conn).await?.is_none())
+}
+
+/// Itera los renglones del CSV de `catalogo` junto con su número de línea.
+/// Los errores de lectura o deserialización indican el archivo y la línea del renglón.
+pub(crate) fn registros<T: DeserializeOwned>(
+    catalogos_path: &str,
+    catalogo: &'static str,
+) -> Result<impl Iterator<Item = Result<(u64, T), UbicacionError>>, UbicacionError> {
+    let mut rdr = leer_catalogo(catalogos_path, catalogo)?;
+    let encabezados = rdr
+        .headers()
+        .map_err(|e| UbicacionError::renglon(catalogo, 1, e))?
+        .clone();
+
+    Ok(rdr
+        .into_records()
+        .enumerate()
+        .map(move |(indice, resultado)| {
+            let linea = linea_renglon(indice);
+            let renglon = resultado.map_err(|e| UbicacionError::renglon(catalogo, linea, e))?;
+            let record = renglon
+                .deserialize(Some(&encabezados))
+                .map_err(|e| UbicacionError::renglon(catalogo, linea, e))?;
+            Ok((linea, record))
+        }))
+}
+
+/// Lote de registros pendientes de insertar.
+/// Conserva el rango de líneas del CSV para reportarlo si la inserción falla.
+struct Lote<A> {
+    catalogo: &'static str,
+    /// Registros por `INSERT`: [`BATCH_SIZE`], o menos si la tabla tiene tantas columnas que
+    /// el lote pasaría de [`MAXIMO_PARAMETROS`]
+    tamano: usize,
+    registros: Vec<A>,
+    primera_linea: u64,
+    ultima_linea: u64,
+}
+
+impl<A: ActiveModelTrait + Send> Lote<A> {
+    fn new(catalogo: &'static str) -> Self {
+        let columnas = <A::Entity as EntityTrait>::Column::iter().count();
+        let tamano = BATCH_SIZE.min(MAXIMO_PARAMETROS / columnas);
+        Lote {
+            catalogo,
+            tamano,
+            registros: Vec::with_capacity(tamano),
+            primera_linea: 0,
+            ultima_linea: 0,
+        }
+    }
+
+    /// Agrega un registro al lote y lo inserta cuando se llena.
+    async fn agregar<C: ConnectionTrait>(
+        &mut self,
+        conn: &C,
+        linea: u64,
+        modelo: A,
+    ) -> Result<(), UbicacionError> {
+        if self.registros.is_empty() {
+            self.primera_linea = linea;
         }
-        // Inserta los registros en lotes
-        for chunk in localidades.chunks(BATCH_SIZE) {
-            CatLocalidades::insert_many(chunk.to_vec()).exec(db).await.map_err(error::ErrorInternalServerError)?;
+        self.ultima_linea = linea;
+        self.registros.push(modelo);
+        if self.registros.len() == self.tamano {
+            self.insertar(conn).await?;
         }
+        Ok(())
+    }
+
+    /// Inserta y vacía los registros pendientes. No hace nada si el lote está vacío.
+    async fn insertar<C: ConnectionTrait>(&mut self, conn: &C) -> Result<(), UbicacionError> {
+        if self.registros.is_empty() {
+            return Ok(());
+        }
+        A::Entity::insert_many(self.registros.drain(..))
+            .exec(conn)
+            .await
+            .map_err(|e| UbicacionError::RenglonCatalogo {
+                catalogo: self.catalogo,
+                primera_linea: self.primera_linea,
+                ultima_linea: self.ultima_linea,
+                causa: e.to_string(),
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::{bd_sqlite, bd_sqlite_migrada, CATALOGOS_PRUEBA};
+    use crate::utils::idioma::Idioma;
+    use sea_orm::{PaginatorTrait, Statement};
+    use std::{fmt::Write, fs, path::Path, path::PathBuf};
+
+    /// Copia los catálogos de prueba a un directorio temporal y reemplaza `cat_localidades.csv`
+    /// con `localidades`.
+    fn catalogos_con_localidades(nombre: &str, localidades: &str) -> PathBuf {
+        let directorio =
+            std::env::temp_dir().join(format!("catalogos_{}_{}", nombre, std::process::id()));
+        fs::create_dir_all(&directorio).unwrap();
+        for entrada in fs::read_dir(CATALOGOS_PRUEBA).unwrap() {
+            let ruta = entrada.unwrap().path();
+            fs::copy(&ruta, directorio.join(ruta.file_name().unwrap())).unwrap();
+        }
+        fs::write(directorio.join("cat_localidades.csv"), localidades).unwrap();
+        directorio
+    }
+
+    /// Número de registros de los cuatro catálogos y nombre de la localidad 3.
+    async fn estado_catalogos(db: &DatabaseConnection) -> (u64, u64, u64, u64, String) {
+        (
+            CatEstados::find().count(db).await.unwrap(),
+            CatMunicipios::find().count(db).await.unwrap(),
+            CatCodigosPostales::find().count(db).await.unwrap(),
+            CatLocalidades::find().count(db).await.unwrap(),
+            CatLocalidades::find_by_id(3)
+                .one(db)
+                .await
+                .unwrap()
+                .unwrap()
+                .localidad,
+        )
+    }
+
+    /// Con 7 columnas, un lote de `BATCH_SIZE` localidades pasaría del límite de parámetros de
+    /// SQLite; la carga debe partirlas en lotes más chicos.
+    #[actix_web::test]
+    async fn carga_localidades_en_varios_lotes() {
+        let directorio =
+            std::env::temp_dir().join(format!("catalogos_lotes_{}", std::process::id()));
+        fs::create_dir_all(&directorio).unwrap();
+        for entrada in fs::read_dir(CATALOGOS_PRUEBA).unwrap() {
+            let ruta = entrada.unwrap().path();
+            fs::copy(&ruta, directorio.join(ruta.file_name().unwrap())).unwrap();
+        }
+        let total = 3 * BATCH_SIZE;
+        let mut localidades = String::from("id_localidad,localidad,cp,id_municipio,id_estado\n");
+        for id in 1..=total {
+            writeln!(localidades, "{id},LOCALIDAD {id},6600,6,9").unwrap();
+        }
+        fs::write(directorio.join("cat_localidades.csv"), localidades).unwrap();
+
+        let db = bd_sqlite_migrada().await;
+        let resultado = llenar_catalogos(&db, directorio.to_str().unwrap()).await;
+        fs::remove_dir_all(Path::new(&directorio)).unwrap();
+        resultado.unwrap();
+
+        assert!(MAXIMO_PARAMETROS / BATCH_SIZE < cat_localidades::Column::iter().count());
+        assert_eq!(CatLocalidades::find().count(&db).await.unwrap(), total as u64);
+    }
+
+    /// Un renglón inválido se detecta antes de vaciar las tablas.
+    #[actix_web::test]
+    async fn recarga_invalida_conserva_los_catalogos() {
+        let db = bd_sqlite().await;
+        let originales = estado_catalogos(&db).await;
+        let directorio = catalogos_con_localidades(
+            "recarga_invalida",
+            "id_localidad,localidad,cp,id_municipio,id_estado\n1,DEL VALLE CENTRO,3100,3,9\n\
+             2,COYOACAN CENTRO,4000,4,9\n3,JUAREZ,6600,6,9\n4,ROMA NORTE,6700,6,99\n\
+             5,GUADALAJARA CENTRO,44100,525,14\n",
+        );
+
+        let error = recargar_catalogos(&db, directorio.to_str().unwrap())
+            .await
+            .unwrap_err();
+        fs::remove_dir_all(&directorio).unwrap();
+        assert!(matches!(error, UbicacionError::CatalogosInvalidos(_)));
+        assert!(error
+            .detalle(Idioma::Es)
+            .ends_with("\ncat_localidades.csv, línea 5: id_estado 99 no existe"));
+        assert_eq!(estado_catalogos(&db).await, originales);
+    }
+
+    /// Si la inserción falla después de vaciar las tablas, la transacción se revierte.
+    #[actix_web::test]
+    async fn recarga_fallida_revierte_la_transaccion() {
+        let db = bd_sqlite().await;
+        let originales = estado_catalogos(&db).await;
+        // La BD rechaza una localidad que pasa la validación de los CSV
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            "CREATE TRIGGER rechaza_localidad BEFORE INSERT ON cat_localidades \
+             WHEN NEW.localidad = 'RECHAZADA' BEGIN SELECT RAISE(ABORT, 'localidad rechazada'); END",
+        ))
+        .await
+        .unwrap();
+
+        // La localidad rechazada queda sola en el segundo lote, para que el error indique su línea
+        let tamano = Lote::<cat_localidades::ActiveModel>::new("cat_localidades").tamano;
+        let mut localidades = String::from("id_localidad,localidad,cp,id_municipio,id_estado\n");
+        for id in 1..=tamano {
+            writeln!(localidades, "{id},LOCALIDAD {id},6600,6,9").unwrap();
+        }
+        writeln!(localidades, "{},RECHAZADA,6700,6,9", tamano + 1).unwrap();
+        let directorio = catalogos_con_localidades("recarga_fallida", &localidades);
+
+        let error = recargar_catalogos(&db, directorio.to_str().unwrap())
+            .await
+            .unwrap_err();
+        fs::remove_dir_all(&directorio).unwrap();
+        let linea = tamano as u64 + 2;
+        assert!(
+            matches!(
+                &error,
+                UbicacionError::RenglonCatalogo {
+                    catalogo: "cat_localidades",
+                    primera_linea,
+                    ultima_linea,
+                    causa,
+                } if *primera_linea == linea
+                    && *ultima_linea == linea
+                    && causa.contains("localidad rechazada")
+            ),
+            "{:?}",
+            error
+        );
+        assert!(error
+            .detalle(Idioma::Es)
+            .starts_with(&format!("cat_localidades.csv, línea {}: ", linea)));
+        assert_eq!(estado_catalogos(&db).await, originales);
     }
-    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,484 @@
+//! # Reporte de diferencias entre catálogos
+//! En este módulo se incluyen funciones para comparar un nuevo juego de CSV de SEPOMEX
+//! contra los catálogos cargados en la base de datos, sin modificar esta última:
+//! - Verificación de que la BD tiene aplicadas todas las migraciones (`verificar_migraciones`)
+//! - Comparación de municipios, códigos postales y localidades (`comparar_catalogos`)
+//! - Escritura del reporte en JSON y CSV (`escribir_reporte`)
+use crate::{
+    entities::prelude::*,
+    services::catalogos_ubicacion::{CodigoPostal, Localidad, Municipio},
+    types::{catalogos::*, errores::UbicacionError},
+    utils::conversores::{existe_catalogo, leer_catalogo, linea_renglon},
+};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, Statement};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+
+/// Verifica que la BD tenga aplicadas todas las migraciones, sin correrlas ni crear
+/// `seaql_migrations` (`Migrator::get_pending_migrations` la crea si no existe).
+///
+/// ## Argumentos
+/// * `db` - Conexión activa a la base de datos.
+///
+/// ## Errores
+/// Retorna [`UbicacionError::EsquemaDesactualizado`] con las migraciones pendientes, o
+/// [`UbicacionError::BaseDatos`] si no se pudo leer la versión de una migración.
+pub async fn verificar_migraciones(db: &DatabaseConnection) -> Result<(), UbicacionError> {
+    let consulta = Statement::from_string(
+        db.get_database_backend(),
+        "SELECT version FROM seaql_migrations",
+    );
+    let aplicadas: HashSet<String> = match db.query_all(consulta).await {
+        Ok(renglones) => renglones
+            .iter()
+            .map(|renglon| renglon.try_get("", "version"))
+            .collect::<Result<_, _>>()?,
+        // Sin la tabla de migraciones no se ha aplicado ninguna
+        Err(_) => HashSet::new(),
+    };
+    let pendientes: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|migracion| migracion.name().to_string())
+        .filter(|nombre| !aplicadas.contains(nombre))
+        .collect();
+    if !pendientes.is_empty() {
+        return Err(UbicacionError::EsquemaDesactualizado(pendientes));
+    }
+    Ok(())
+}
+
+/// Compara los catálogos de municipios, códigos postales y localidades de `catalogos_path`
+/// contra los registros actuales de la base de datos.
+/// - Si alguno de los archivos no existe en `catalogos_path`, ese catálogo se omite del reporte.
+/// - Los cambios se ordenan por catálogo e id para que el reporte sea estable entre ejecuciones.
+///
+/// ## Argumentos
+/// * `db` - Conexión activa a la base de datos.
+/// * `catalogos_path` - Ruta del directorio con los CSV nuevos.
+///
+/// ## Errores
+/// Retorna un [`UbicacionError`] si ocurre algún problema al leer los archivos CSV (con el archivo y la línea del renglón) o consultar la base de datos.
+///
+/// ## Ejemplo
+/// ```rust
+/// let reporte = comparar_catalogos(&db, "./catalogos_nuevos").await?;
+/// ```
+pub async fn comparar_catalogos(
+    db: &DatabaseConnection,
+    catalogos_path: &str,
+) -> Result<ReporteDiferencias, UbicacionError> {
+    let mut cambios = Vec::new();
+
+    // Municipios. Se conservan los nombres de ambos lados para describir los CPs reasignados.
+    let municipios_bd: HashMap<i32, String> = CatMunicipios::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, m.municipio))
+        .collect();
+    let mut municipios_csv: HashMap<i32, String> = HashMap::new();
+    if existe_catalogo(catalogos_path, "cat_municipios") {
+        let mut rdr = leer_catalogo(catalogos_path, "cat_municipios")?;
+        for (indice, result) in rdr.deserialize().enumerate() {
+            let record: Municipio = result
+                .map_err(|e| UbicacionError::renglon("cat_municipios", linea_renglon(indice), e))?;
+            municipios_csv.insert(record.id_municipio, record.municipio);
+        }
+        comparar_nombres(
+            "cat_municipios",
+            &municipios_bd,
+            &municipios_csv,
+            &mut cambios,
+        );
+    }
+
+    // Códigos postales. Un CP "reasignado" es aquel que cambió de municipio.
+    if existe_catalogo(catalogos_path, "cat_codigos_postales") {
+        let cps_bd: HashMap<i32, i32> = CatCodigosPostales::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|cp| (cp.codigo_postal, cp.id_municipio))
+            .collect();
+        let mut cps_csv: HashMap<i32, i32> = HashMap::new();
+        let mut rdr = leer_catalogo(catalogos_path, "cat_codigos_postales")?;
+        for (indice, result) in rdr.deserialize().enumerate() {
+            let record: CodigoPostal = result.map_err(|e| {
+                UbicacionError::renglon("cat_codigos_postales", linea_renglon(indice), e)
+            })?;
+            cps_csv.insert(record.cp, record.id_municipio);
+        }
+
+        let nombre_municipio = |municipios: &HashMap<i32, String>, id: i32| {
+            municipios
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| format!("municipio {}", id))
+        };
+        for (cp, id_municipio) in &cps_bd {
+            match cps_csv.get(cp) {
+                None => cambios.push(CambioCatalogo {
+                    catalogo: "cat_codigos_postales".to_string(),
+                    cambio: TipoCambio::Eliminado,
+                    id: *cp,
+                    anterior: Some(nombre_municipio(&municipios_bd, *id_municipio)),
+                    nuevo: None,
+                }),
+                Some(nuevo) if nuevo != id_municipio => cambios.push(CambioCatalogo {
+                    catalogo: "cat_codigos_postales".to_string(),
+                    cambio: TipoCambio::Reasignado,
+                    id: *cp,
+                    anterior: Some(nombre_municipio(&municipios_bd, *id_municipio)),
+                    nuevo: Some(nombre_municipio(&municipios_csv, *nuevo)),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (cp, id_municipio) in &cps_csv {
+            if !cps_bd.contains_key(cp) {
+                cambios.push(CambioCatalogo {
+                    catalogo: "cat_codigos_postales".to_string(),
+                    cambio: TipoCambio::Agregado,
+                    id: *cp,
+                    anterior: None,
+                    nuevo: Some(nombre_municipio(&municipios_csv, *id_municipio)),
+                });
+            }
+        }
+    }
+
+    // Localidades (colonias). Pueden cambiar de nombre, de código postal o ambos.
+    if existe_catalogo(catalogos_path, "cat_localidades") {
+        let localidades_bd: HashMap<i32, (String, i32)> = CatLocalidades::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|l| (l.id, (l.localidad, l.codigo_postal)))
+            .collect();
+        let mut localidades_csv: HashMap<i32, (String, i32)> = HashMap::new();
+        let mut rdr = leer_catalogo(catalogos_path, "cat_localidades")?;
+        for (indice, result) in rdr.deserialize().enumerate() {
+            let record: Localidad = result.map_err(|e| {
+                UbicacionError::renglon("cat_localidades", linea_renglon(indice), e)
+            })?;
+            localidades_csv.insert(record.id_localidad, (record.localidad, record.cp));
+        }
+
+        let nombres_bd: HashMap<i32, String> = localidades_bd
+            .iter()
+            .map(|(id, (nombre, _))| (*id, nombre.clone()))
+            .collect();
+        let nombres_csv: HashMap<i32, String> = localidades_csv
+            .iter()
+            .map(|(id, (nombre, _))| (*id, nombre.clone()))
+            .collect();
+        comparar_nombres("cat_localidades", &nombres_bd, &nombres_csv, &mut cambios);
+
+        for (id, (_, cp)) in &localidades_bd {
+            if let Some((_, nuevo_cp)) = localidades_csv.get(id) {
+                if nuevo_cp != cp {
+                    cambios.push(CambioCatalogo {
+                        catalogo: "cat_localidades".to_string(),
+                        cambio: TipoCambio::Reasignado,
+                        id: *id,
+                        anterior: Some(cp.to_string()),
+                        nuevo: Some(nuevo_cp.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    cambios.sort_by(|a, b| {
+        a.catalogo
+            .cmp(&b.catalogo)
+            .then(a.id.cmp(&b.id))
+            .then((a.cambio as u8).cmp(&(b.cambio as u8)))
+    });
+    let contar = |tipo: TipoCambio| cambios.iter().filter(|c| c.cambio == tipo).count();
+
+    Ok(ReporteDiferencias {
+        agregados: contar(TipoCambio::Agregado),
+        eliminados: contar(TipoCambio::Eliminado),
+        renombrados: contar(TipoCambio::Renombrado),
+        reasignados: contar(TipoCambio::Reasignado),
+        cambios,
+    })
+}
+
+/// Escribe el reporte en `{salida}.json` (reporte completo) y `{salida}.csv` (un renglón por cambio).
+///
+/// ## Argumentos
+/// * `reporte` - Reporte generado por [`comparar_catalogos`].
+/// * `salida` - Ruta de los archivos de salida, sin extensión.
+///
+/// ## Errores
+/// Retorna [`UbicacionError::Archivo`] si no se pueden crear o escribir los archivos.
+pub fn escribir_reporte(reporte: &ReporteDiferencias, salida: &str) -> Result<(), UbicacionError> {
+    let json = File::create(format!("{}.json", salida))?;
+    serde_json::to_writer_pretty(json, reporte).map_err(io::Error::from)?;
+
+    let mut wtr = csv::Writer::from_path(format!("{}.csv", salida)).map_err(io::Error::from)?;
+    for cambio in &reporte.cambios {
+        wtr.serialize(cambio).map_err(io::Error::from)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Agrega a `cambios` los registros agregados, eliminados y renombrados entre dos mapas id -> nombre.
+fn comparar_nombres(
+    catalogo: &str,
+    bd: &HashMap<i32, String>,
+    csv: &HashMap<i32, String>,
+    cambios: &mut Vec<CambioCatalogo>,
+) {
+    for (id, nombre) in bd {
+        match csv.get(id) {
+            None => cambios.push(CambioCatalogo {
+                catalogo: catalogo.to_string(),
+                cambio: TipoCambio::Eliminado,
+                id: *id,
+                anterior: Some(nombre.clone()),
+                nuevo: None,
+            }),
+            Some(nuevo) if nuevo != nombre => cambios.push(CambioCatalogo {
+                catalogo: catalogo.to_string(),
+                cambio: TipoCambio::Renombrado,
+                id: *id,
+                anterior: Some(nombre.clone()),
+                nuevo: Some(nuevo.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (id, nombre) in csv {
+        if !bd.contains_key(id) {
+            cambios.push(CambioCatalogo {
+                catalogo: catalogo.to_string(),
+                cambio: TipoCambio::Agregado,
+                id: *id,
+                anterior: None,
+                nuevo: Some(nombre.clone()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::{bd_sqlite, bd_sqlite_migrada};
+    use std::path::PathBuf;
+
+    /// Directorio temporal con los CSV dados.
+    fn catalogos_nuevos(nombre: &str, archivos: &[(&str, &str)]) -> PathBuf {
+        let directorio =
+            std::env::temp_dir().join(format!("diferencias_{}_{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(&directorio).unwrap();
+        for (archivo, contenido) in archivos {
+            std::fs::write(directorio.join(archivo), contenido).unwrap();
+        }
+        directorio
+    }
+
+    /// Catálogos nuevos respecto a los de prueba: Benito Juárez cambia de nombre, Guadalajara
+    /// se elimina y se agrega Zapopan; el 06700 pasa a Benito Juárez, el 44100 a Zapopan, se
+    /// elimina el 04000 y se agrega el 45000; Juárez cambia de nombre, Roma Norte pasa al 06600,
+    /// se elimina Coyoacán Centro y se agrega Zapopan Centro.
+    const MUNICIPIOS: &str = "municipio,id_estado,id_municipio\n\
+        BENITO JUAREZ CDMX,9,3\nCOYOACAN,9,4\nCUAUHTEMOC,9,6\nZAPOPAN,14,120\n";
+    const CODIGOS_POSTALES: &str = "cp,id_estado,id_municipio\n\
+        3100,9,3\n6600,9,6\n6700,9,3\n44100,14,120\n45000,14,120\n";
+    const LOCALIDADES: &str = "id_localidad,localidad,cp,id_municipio,id_estado\n\
+        1,DEL VALLE CENTRO,3100,3,9\n3,JUAREZ CENTRO,6600,6,9\n4,ROMA NORTE,6600,6,9\n\
+        5,GUADALAJARA CENTRO,44100,120,14\n6,ZAPOPAN CENTRO,45000,120,14\n";
+
+    /// Cambio del reporte: catálogo, tipo, id, anterior y nuevo.
+    type Tupla<'a> = (&'a str, TipoCambio, i32, Option<&'a str>, Option<&'a str>);
+
+    /// Cambios del reporte como tuplas, para compararlos completos y en orden.
+    fn tuplas(reporte: &ReporteDiferencias) -> Vec<Tupla<'_>> {
+        reporte
+            .cambios
+            .iter()
+            .map(|c| {
+                (
+                    c.catalogo.as_str(),
+                    c.cambio,
+                    c.id,
+                    c.anterior.as_deref(),
+                    c.nuevo.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn reporta_cambios_ordenados() {
+        let db = bd_sqlite().await;
+        let directorio = catalogos_nuevos(
+            "completo",
+            &[
+                ("cat_municipios.csv", MUNICIPIOS),
+                ("cat_codigos_postales.csv", CODIGOS_POSTALES),
+                ("cat_localidades.csv", LOCALIDADES),
+            ],
+        );
+        let ruta = directorio.to_str().unwrap();
+        let reporte = comparar_catalogos(&db, ruta).await.unwrap();
+
+        use TipoCambio::*;
+        assert_eq!(
+            tuplas(&reporte),
+            [
+                (
+                    "cat_codigos_postales",
+                    Eliminado,
+                    4000,
+                    Some("COYOACAN"),
+                    None
+                ),
+                (
+                    "cat_codigos_postales",
+                    Reasignado,
+                    6700,
+                    Some("CUAUHTEMOC"),
+                    Some("BENITO JUAREZ CDMX")
+                ),
+                (
+                    "cat_codigos_postales",
+                    Reasignado,
+                    44100,
+                    Some("GUADALAJARA"),
+                    Some("ZAPOPAN")
+                ),
+                (
+                    "cat_codigos_postales",
+                    Agregado,
+                    45000,
+                    None,
+                    Some("ZAPOPAN")
+                ),
+                (
+                    "cat_localidades",
+                    Eliminado,
+                    2,
+                    Some("COYOACAN CENTRO"),
+                    None
+                ),
+                (
+                    "cat_localidades",
+                    Renombrado,
+                    3,
+                    Some("JUAREZ"),
+                    Some("JUAREZ CENTRO")
+                ),
+                ("cat_localidades", Reasignado, 4, Some("6700"), Some("6600")),
+                ("cat_localidades", Agregado, 6, None, Some("ZAPOPAN CENTRO")),
+                (
+                    "cat_municipios",
+                    Renombrado,
+                    3,
+                    Some("BENITO JUAREZ"),
+                    Some("BENITO JUAREZ CDMX")
+                ),
+                ("cat_municipios", Agregado, 120, None, Some("ZAPOPAN")),
+                ("cat_municipios", Eliminado, 525, Some("GUADALAJARA"), None),
+            ]
+        );
+        assert_eq!(
+            (
+                reporte.agregados,
+                reporte.eliminados,
+                reporte.renombrados,
+                reporte.reasignados
+            ),
+            (3, 3, 2, 3)
+        );
+
+        // Otra comparación de los mismos archivos da el mismo reporte
+        let otra = comparar_catalogos(&db, ruta).await.unwrap();
+        assert_eq!(tuplas(&otra), tuplas(&reporte));
+    }
+
+    #[actix_web::test]
+    async fn omite_catalogos_sin_archivo() {
+        let db = bd_sqlite().await;
+        let directorio = catalogos_nuevos("municipios", &[("cat_municipios.csv", MUNICIPIOS)]);
+        let reporte = comparar_catalogos(&db, directorio.to_str().unwrap())
+            .await
+            .unwrap();
+        assert!(reporte
+            .cambios
+            .iter()
+            .all(|cambio| cambio.catalogo == "cat_municipios"));
+        assert_eq!(reporte.cambios.len(), 3);
+    }
+
+    #[actix_web::test]
+    async fn escribe_json_y_csv() {
+        let db = bd_sqlite().await;
+        let directorio = catalogos_nuevos(
+            "reporte",
+            &[
+                ("cat_municipios.csv", MUNICIPIOS),
+                ("cat_codigos_postales.csv", CODIGOS_POSTALES),
+                ("cat_localidades.csv", LOCALIDADES),
+            ],
+        );
+        let reporte = comparar_catalogos(&db, directorio.to_str().unwrap())
+            .await
+            .unwrap();
+        let salida = directorio.join("diferencias");
+        let salida = salida.to_str().unwrap();
+        escribir_reporte(&reporte, salida).unwrap();
+
+        let json: ReporteDiferencias =
+            serde_json::from_reader(File::open(format!("{}.json", salida)).unwrap()).unwrap();
+        assert_eq!(tuplas(&json), tuplas(&reporte));
+        assert_eq!(json.reasignados, reporte.reasignados);
+
+        let mut rdr = csv::Reader::from_path(format!("{}.csv", salida)).unwrap();
+        assert_eq!(
+            rdr.headers().unwrap(),
+            vec!["catalogo", "cambio", "id", "anterior", "nuevo"]
+        );
+        let renglones: Vec<CambioCatalogo> = rdr.deserialize().map(Result::unwrap).collect();
+        let csv = ReporteDiferencias {
+            agregados: 0,
+            eliminados: 0,
+            renombrados: 0,
+            reasignados: 0,
+            cambios: renglones,
+        };
+        assert_eq!(tuplas(&csv), tuplas(&reporte));
+    }
+
+    #[actix_web::test]
+    async fn verifica_migraciones_sin_modificar_la_bd() {
+        verificar_migraciones(&bd_sqlite_migrada().await)
+            .await
+            .unwrap();
+
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+        let Err(UbicacionError::EsquemaDesactualizado(pendientes)) =
+            verificar_migraciones(&db).await
+        else {
+            panic!("la BD vacía debía tener migraciones pendientes");
+        };
+        assert_eq!(pendientes.len(), Migrator::migrations().len());
+        // No se creó `seaql_migrations`
+        let tablas = db
+            .query_all(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT name FROM sqlite_master WHERE type = 'table'",
+            ))
+            .await
+            .unwrap();
+        assert!(tablas.is_empty());
+    }
+}
@@ -0,0 +1,452 @@
+//! # Índice geográfico de centroides
+//! En este módulo se incluye el índice en memoria para la geocodificación inversa: un árbol k-d
+//! con los centroides de códigos postales y localidades, que responde los N más cercanos a unas
+//! coordenadas, o los que están dentro de un radio, sin recorrer todos los puntos.
+//!
+//! Los puntos se guardan como vectores unitarios en 3D ([`posicion`]) y no como latitud y
+//! longitud, por lo que los vecinos son exactos también cerca del antimeridiano y de los polos,
+//! y la distancia en metros se obtiene directamente de la cuerda.
+use crate::{
+    services::repositorio::RepositorioUbicacion,
+    types::{
+        catalogos::{CodigoPostalCercano, ResultadoGeocodificacion},
+        errores::UbicacionError,
+    },
+    utils::{
+        conversores::CatalogoIdCadena,
+        geodesia::{cuerda2, cuerda2_metros, metros_cuerda, posicion},
+    },
+};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, RwLock};
+
+/// Centroide de un código postal o de una localidad (`localidad` es `None` para los CPs).
+/// El estado y el municipio son los del código postal.
+#[derive(Debug, Clone)]
+pub struct Centroide {
+    pub cp: i32,
+    pub estado: CatalogoIdCadena,
+    pub municipio: CatalogoIdCadena,
+    pub localidad: Option<CatalogoIdCadena>,
+    pub latitud: Decimal,
+    pub longitud: Decimal,
+}
+
+/// Centroide con su posición sobre la esfera unitaria.
+#[derive(Debug)]
+struct Punto {
+    posicion: [f64; 3],
+    centroide: Centroide,
+}
+
+/// Árbol k-d implícito: cada subarreglo tiene su mediana en medio, partida por el eje
+/// `profundidad % 3`, con los puntos menores a la izquierda y los mayores a la derecha.
+#[derive(Debug, Default)]
+struct ArbolKd {
+    puntos: Vec<Punto>,
+}
+
+/// Punto candidato durante la búsqueda; el montículo deja arriba el más lejano.
+struct Candidato {
+    distancia2: f64,
+    indice: usize,
+}
+
+impl PartialEq for Candidato {
+    fn eq(&self, otro: &Self) -> bool {
+        self.cmp(otro) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidato {}
+
+impl PartialOrd for Candidato {
+    fn partial_cmp(&self, otro: &Self) -> Option<Ordering> {
+        Some(self.cmp(otro))
+    }
+}
+
+impl Ord for Candidato {
+    fn cmp(&self, otro: &Self) -> Ordering {
+        self.distancia2.total_cmp(&otro.distancia2)
+    }
+}
+
+impl ArbolKd {
+    /// Construye el árbol con los centroides.
+    fn new(centroides: Vec<Centroide>) -> Self {
+        let mut puntos: Vec<Punto> = centroides
+            .into_iter()
+            .filter_map(|centroide| {
+                let posicion = posicion(centroide.latitud.to_f64()?, centroide.longitud.to_f64()?);
+                Some(Punto {
+                    posicion,
+                    centroide,
+                })
+            })
+            .collect();
+        ordenar(&mut puntos, 0);
+        ArbolKd { puntos }
+    }
+
+    /// Índices de los `n` puntos más cercanos a `objetivo` con su distancia al cuadrado,
+    /// del más cercano al más lejano.
+    fn cercanos(&self, objetivo: [f64; 3], n: usize) -> Vec<Candidato> {
+        let mut candidatos = BinaryHeap::with_capacity(n + 1);
+        if n > 0 {
+            self.buscar(0, self.puntos.len(), 0, objetivo, n, &mut candidatos);
+        }
+        candidatos.into_sorted_vec()
+    }
+
+    /// Busca en el subárbol `puntos[inicio..fin]`, primero del lado de `objetivo` y después
+    /// del otro lado sólo si el plano de corte está más cerca que el candidato más lejano.
+    fn buscar(
+        &self,
+        inicio: usize,
+        fin: usize,
+        profundidad: usize,
+        objetivo: [f64; 3],
+        n: usize,
+        candidatos: &mut BinaryHeap<Candidato>,
+    ) {
+        if inicio >= fin {
+            return;
+        }
+        let medio = inicio + (fin - inicio) / 2;
+        let punto = &self.puntos[medio];
+
+        let distancia2 = cuerda2(punto.posicion, objetivo);
+        if candidatos.len() < n {
+            candidatos.push(Candidato {
+                distancia2,
+                indice: medio,
+            });
+        } else if candidatos
+            .peek()
+            .is_some_and(|lejano| distancia2 < lejano.distancia2)
+        {
+            candidatos.pop();
+            candidatos.push(Candidato {
+                distancia2,
+                indice: medio,
+            });
+        }
+
+        let eje = profundidad % 3;
+        let diferencia = objetivo[eje] - punto.posicion[eje];
+        let (cercano, lejano) = if diferencia < 0.0 {
+            ((inicio, medio), (medio + 1, fin))
+        } else {
+            ((medio + 1, fin), (inicio, medio))
+        };
+        self.buscar(
+            cercano.0,
+            cercano.1,
+            profundidad + 1,
+            objetivo,
+            n,
+            candidatos,
+        );
+        let cruzar = candidatos.len() < n
+            || candidatos
+                .peek()
+                .is_some_and(|lejano| diferencia * diferencia < lejano.distancia2);
+        if cruzar {
+            self.buscar(lejano.0, lejano.1, profundidad + 1, objetivo, n, candidatos);
+        }
+    }
+
+    /// Agrega a `encontrados` los índices de los puntos de `puntos[inicio..fin]` cuya distancia
+    /// al cuadrado a `objetivo` es a lo más `maximo2`, junto con esa distancia.
+    fn en_radio(
+        &self,
+        inicio: usize,
+        fin: usize,
+        profundidad: usize,
+        objetivo: [f64; 3],
+        maximo2: f64,
+        encontrados: &mut Vec<Candidato>,
+    ) {
+        if inicio >= fin {
+            return;
+        }
+        let medio = inicio + (fin - inicio) / 2;
+        let punto = &self.puntos[medio];
+
+        let distancia2 = cuerda2(punto.posicion, objetivo);
+        if distancia2 <= maximo2 {
+            encontrados.push(Candidato {
+                distancia2,
+                indice: medio,
+            });
+        }
+
+        let eje = profundidad % 3;
+        let diferencia = objetivo[eje] - punto.posicion[eje];
+        // El plano de corte está dentro del radio: puede haber puntos de ambos lados
+        if diferencia <= 0.0 || diferencia * diferencia <= maximo2 {
+            self.en_radio(
+                inicio,
+                medio,
+                profundidad + 1,
+                objetivo,
+                maximo2,
+                encontrados,
+            );
+        }
+        if diferencia >= 0.0 || diferencia * diferencia <= maximo2 {
+            self.en_radio(
+                medio + 1,
+                fin,
+                profundidad + 1,
+                objetivo,
+                maximo2,
+                encontrados,
+            );
+        }
+    }
+}
+
+/// Ordena `puntos` como árbol k-d a partir del eje de `profundidad`.
+fn ordenar(puntos: &mut [Punto], profundidad: usize) {
+    if puntos.len() <= 1 {
+        return;
+    }
+    let eje = profundidad % 3;
+    let medio = puntos.len() / 2;
+    puntos.select_nth_unstable_by(medio, |a, b| a.posicion[eje].total_cmp(&b.posicion[eje]));
+    let (izquierda, derecha) = puntos.split_at_mut(medio);
+    ordenar(izquierda, profundidad + 1);
+    ordenar(&mut derecha[1..], profundidad + 1);
+}
+
+/// Índice geográfico compartido entre workers.
+/// Se reconstruye al arrancar y después de cada recarga de catálogos.
+#[derive(Debug, Default)]
+pub struct IndiceGeografico {
+    arbol: RwLock<Arc<ArbolKd>>,
+}
+
+impl IndiceGeografico {
+    /// Vuelve a construir el índice con los centroides del repositorio.
+    /// Si la consulta falla se conserva el índice anterior.
+    ///
+    /// ## Errores
+    /// Devuelve el [`UbicacionError`] del repositorio.
+    pub async fn reconstruir(
+        &self,
+        repositorio: &dyn RepositorioUbicacion,
+    ) -> Result<(), UbicacionError> {
+        let arbol = ArbolKd::new(repositorio.centroides().await?);
+        *self.arbol.write().unwrap() = Arc::new(arbol);
+        Ok(())
+    }
+
+    /// Devuelve los `n` centroides más cercanos a las coordenadas (en grados), del más
+    /// cercano al más lejano, con su distancia en metros redondeada a decímetros.
+    pub fn cercanos(&self, latitud: f64, longitud: f64, n: usize) -> Vec<ResultadoGeocodificacion> {
+        let arbol = self.arbol.read().unwrap().clone();
+        arbol
+            .cercanos(posicion(latitud, longitud), n)
+            .into_iter()
+            .map(|candidato| {
+                let centroide = &arbol.puntos[candidato.indice].centroide;
+                ResultadoGeocodificacion {
+                    cp: centroide.cp,
+                    localidad: centroide.localidad.clone(),
+                    latitud: centroide.latitud,
+                    longitud: centroide.longitud,
+                    distancia_metros: metros_cuerda(candidato.distancia2),
+                }
+            })
+            .collect()
+    }
+
+    /// Devuelve los centroides de códigos postales (sin los de localidades) que están a lo más
+    /// a `radio_metros` de las coordenadas (en grados), sin un orden en particular, con su
+    /// distancia en metros redondeada a decímetros.
+    pub fn codigos_postales_en_radio(
+        &self,
+        latitud: f64,
+        longitud: f64,
+        radio_metros: f64,
+    ) -> Vec<CodigoPostalCercano> {
+        let arbol = self.arbol.read().unwrap().clone();
+        let mut encontrados = Vec::new();
+        arbol.en_radio(
+            0,
+            arbol.puntos.len(),
+            0,
+            posicion(latitud, longitud),
+            cuerda2_metros(radio_metros),
+            &mut encontrados,
+        );
+        encontrados
+            .into_iter()
+            .map(|candidato| (&arbol.puntos[candidato.indice].centroide, candidato))
+            .filter(|(centroide, _)| centroide.localidad.is_none())
+            .map(|(centroide, candidato)| CodigoPostalCercano {
+                cp: centroide.cp,
+                estado: centroide.estado.clone(),
+                municipio: centroide.municipio.clone(),
+                latitud: centroide.latitud,
+                longitud: centroide.longitud,
+                distancia_metros: metros_cuerda(candidato.distancia2),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::CATALOGOS_PRUEBA;
+    use crate::services::repositorio::memoria::{CatalogosMemoria, RepositorioMemoria};
+
+    /// Centroides de los catálogos de prueba y el índice construido con ellos.
+    async fn indice_prueba() -> (Vec<Centroide>, IndiceGeografico) {
+        let repositorio =
+            RepositorioMemoria::new(CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap());
+        let indice = IndiceGeografico::default();
+        indice.reconstruir(&repositorio).await.unwrap();
+        (repositorio.centroides().await.unwrap(), indice)
+    }
+
+    /// Distancia en metros con la fórmula de haversine, sin pasar por [`posicion`].
+    fn haversine(latitud: f64, longitud: f64, centroide: &Centroide) -> f64 {
+        let (latitud2, longitud2) = (
+            centroide.latitud.to_f64().unwrap(),
+            centroide.longitud.to_f64().unwrap(),
+        );
+        let (fi1, fi2) = (latitud.to_radians(), latitud2.to_radians());
+        let h = ((fi2 - fi1) / 2.0).sin().powi(2)
+            + fi1.cos() * fi2.cos() * ((longitud2 - longitud).to_radians() / 2.0).sin().powi(2);
+        2.0 * 6_371_008.8 * h.sqrt().asin()
+    }
+
+    /// Distancias de fuerza bruta a todos los centroides, de la menor a la mayor.
+    fn distancias_ordenadas(latitud: f64, longitud: f64, centroides: &[Centroide]) -> Vec<f64> {
+        let mut distancias: Vec<f64> = centroides
+            .iter()
+            .map(|centroide| haversine(latitud, longitud, centroide))
+            .collect();
+        distancias.sort_by(f64::total_cmp);
+        distancias
+    }
+
+    /// Centroide de un resultado, identificado por su CP y su localidad.
+    fn centroide_de(centroides: &[Centroide], cp: i32, localidad: Option<i32>) -> &Centroide {
+        centroides
+            .iter()
+            .find(|c| c.cp == cp && c.localidad.as_ref().map(|l| l.id) == localidad)
+            .unwrap()
+    }
+
+    /// Puntos de consulta: sobre un centroide, entre los de CDMX, en el camino a Guadalajara
+    /// y del otro lado del mundo.
+    const PUNTOS: [(f64, f64); 5] = [
+        (19.427, -99.160),
+        (19.400, -99.163),
+        (20.000, -101.500),
+        (20.676, -103.347),
+        (-33.870, 151.210),
+    ];
+
+    #[actix_web::test]
+    async fn cercanos_como_fuerza_bruta() {
+        let (centroides, indice) = indice_prueba().await;
+        assert_eq!(centroides.len(), 10);
+
+        for (latitud, longitud) in PUNTOS {
+            let esperadas = distancias_ordenadas(latitud, longitud, &centroides);
+            for n in [1, 2, 3, 7, 10, 25] {
+                let resultados = indice.cercanos(latitud, longitud, n);
+                assert_eq!(resultados.len(), n.min(centroides.len()));
+                for (resultado, esperada) in resultados.iter().zip(&esperadas) {
+                    let propia = haversine(
+                        latitud,
+                        longitud,
+                        centroide_de(
+                            &centroides,
+                            resultado.cp,
+                            resultado.localidad.as_ref().map(|l| l.id),
+                        ),
+                    );
+                    // Las distancias se redondean a decímetros
+                    assert!(
+                        (resultado.distancia_metros - esperada).abs() <= 0.1,
+                        "({}, {}) n={}: {} contra {}",
+                        latitud,
+                        longitud,
+                        n,
+                        resultado.distancia_metros,
+                        esperada
+                    );
+                    assert!((resultado.distancia_metros - propia).abs() <= 0.1);
+                }
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn codigos_postales_en_radio_como_fuerza_bruta() {
+        let (centroides, indice) = indice_prueba().await;
+        let codigos_postales: Vec<&Centroide> = centroides
+            .iter()
+            .filter(|centroide| centroide.localidad.is_none())
+            .collect();
+
+        for origen in &codigos_postales {
+            let (latitud, longitud) = (
+                origen.latitud.to_f64().unwrap(),
+                origen.longitud.to_f64().unwrap(),
+            );
+            // Radios 5 cm antes y después de cada CP, para que quede justo fuera y justo dentro
+            let radios: Vec<f64> = codigos_postales
+                .iter()
+                .map(|destino| haversine(latitud, longitud, destino))
+                .flat_map(|distancia| [distancia - 0.05, distancia + 0.05])
+                .chain([1_000.0, 5_000.0, 100_000.0, 1_000_000.0])
+                .filter(|radio| *radio > 0.0)
+                .collect();
+            for radio in radios {
+                let mut encontrados: Vec<(i32, f64)> = indice
+                    .codigos_postales_en_radio(latitud, longitud, radio)
+                    .into_iter()
+                    .map(|cercano| (cercano.cp, cercano.distancia_metros))
+                    .collect();
+                encontrados.sort_by_key(|(cp, _)| *cp);
+                let mut esperados: Vec<(i32, f64)> = codigos_postales
+                    .iter()
+                    .map(|destino| (destino.cp, haversine(latitud, longitud, destino)))
+                    .filter(|(_, distancia)| *distancia <= radio)
+                    .collect();
+                esperados.sort_by_key(|(cp, _)| *cp);
+
+                assert_eq!(
+                    encontrados.iter().map(|(cp, _)| *cp).collect::<Vec<_>>(),
+                    esperados.iter().map(|(cp, _)| *cp).collect::<Vec<_>>(),
+                    "CP {} radio {}",
+                    origen.cp,
+                    radio
+                );
+                for ((_, encontrada), (_, esperada)) in encontrados.iter().zip(&esperados) {
+                    assert!((encontrada - esperada).abs() <= 0.1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn indice_vacio_no_devuelve_cercanos() {
+        let indice = IndiceGeografico::default();
+        assert!(indice.cercanos(19.427, -99.160, 5).is_empty());
+        assert!(indice
+            .codigos_postales_en_radio(19.427, -99.160, 100_000.0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,430 @@
+//! # Límites geográficos de municipios y códigos postales
+//! En este módulo se incluyen los polígonos de los municipios y, opcionalmente, de los códigos
+//! postales, para resolver unas coordenadas al municipio que las contiene. El centroide más
+//! cercano no basta cerca de los límites: puede pertenecer al municipio vecino.
+//!
+//! ## Archivos
+//!
+//! Se leen del directorio de catálogos y ambos son opcionales:
+//!
+//! - `limites_municipios.geojson`
+//!   `FeatureCollection` de `Polygon` o `MultiPolygon` con la propiedad `id_municipio`
+//!   (id de `cat_municipios`).
+//!
+//! - `limites_codigos_postales.geojson`
+//!   Igual, con la propiedad `cp` (o `d_cp`, como en los archivos de SEPOMEX).
+//!
+//! También se aceptan comprimidos (`.geojson.gz`, `.geojson.zst`). Las coordenadas deben estar
+//! en WGS84; los shapefiles de INEGI (proyección Lambert) se convierten antes con
+//! `ogr2ogr -f GeoJSON -t_srs EPSG:4326 limites_municipios.geojson municipios.shp`.
+use crate::{
+    types::{errores::UbicacionError, geojson::GeometriaGeoJson},
+    utils::conversores::{abrir_archivo, buscar_archivo},
+};
+use actix_web::web;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{hash_map::Entry, HashMap};
+use std::io::{self, BufReader};
+use std::sync::{Arc, RwLock};
+
+/// Archivo con los límites de los municipios
+const LIMITES_MUNICIPIOS: &str = "limites_municipios";
+/// Archivo con los límites de los códigos postales
+const LIMITES_CODIGOS_POSTALES: &str = "limites_codigos_postales";
+/// Extensiones con las que se buscan los límites, en orden de preferencia
+const EXTENSIONES_LIMITES: [&str; 3] = ["geojson", "geojson.gz", "geojson.zst"];
+
+/// Anillo de un polígono: vértices `[longitud, latitud]` en grados, como en GeoJSON.
+type Anillo = Vec<[f64; 2]>;
+
+/// Polígono: el primer anillo es el exterior y los demás son huecos.
+type Poligono = Vec<Anillo>;
+
+/// Caja envolvente en grados, para descartar polígonos sin recorrer sus vértices.
+#[derive(Debug, Clone, Copy)]
+struct Caja {
+    oeste: f64,
+    sur: f64,
+    este: f64,
+    norte: f64,
+}
+
+impl Caja {
+    fn contiene(&self, longitud: f64, latitud: f64) -> bool {
+        (self.oeste..=self.este).contains(&longitud) && (self.sur..=self.norte).contains(&latitud)
+    }
+}
+
+/// Polígonos de un municipio o de un código postal.
+#[derive(Debug)]
+struct Limite {
+    /// Id del municipio o código postal
+    id: i32,
+    poligonos: Vec<Poligono>,
+    caja: Caja,
+}
+
+impl Limite {
+    fn new(id: i32, poligonos: Vec<Poligono>) -> Self {
+        let mut caja = Caja {
+            oeste: f64::INFINITY,
+            sur: f64::INFINITY,
+            este: f64::NEG_INFINITY,
+            norte: f64::NEG_INFINITY,
+        };
+        for [longitud, latitud] in poligonos.iter().filter_map(|p| p.first()).flatten() {
+            caja.oeste = caja.oeste.min(*longitud);
+            caja.este = caja.este.max(*longitud);
+            caja.sur = caja.sur.min(*latitud);
+            caja.norte = caja.norte.max(*latitud);
+        }
+        Limite {
+            id,
+            poligonos,
+            caja,
+        }
+    }
+
+    /// Indica si las coordenadas están dentro de alguno de los polígonos (y fuera de sus huecos).
+    fn contiene(&self, longitud: f64, latitud: f64) -> bool {
+        self.caja.contiene(longitud, latitud)
+            && self.poligonos.iter().any(|poligono| {
+                // Regla par-impar: los huecos invierten el resultado del anillo exterior
+                poligono
+                    .iter()
+                    .filter(|anillo| cruces_impares(anillo, longitud, latitud))
+                    .count()
+                    % 2
+                    == 1
+            })
+    }
+}
+
+/// Indica si un rayo hacia el este desde las coordenadas cruza un número impar de aristas del
+/// anillo, es decir, si las coordenadas están dentro del anillo.
+fn cruces_impares(anillo: &[[f64; 2]], longitud: f64, latitud: f64) -> bool {
+    let mut dentro = false;
+    let mut anterior = match anillo.last() {
+        Some(vertice) => *vertice,
+        None => return false,
+    };
+    for &actual in anillo {
+        let [x1, y1] = anterior;
+        let [x2, y2] = actual;
+        if (y1 > latitud) != (y2 > latitud)
+            && longitud < x1 + (latitud - y1) * (x2 - x1) / (y2 - y1)
+        {
+            dentro = !dentro;
+        }
+        anterior = actual;
+    }
+    dentro
+}
+
+/// `FeatureCollection` de un archivo de límites; sólo se leen las propiedades y la geometría.
+#[derive(Debug, Deserialize)]
+struct ArchivoLimites {
+    features: Vec<EntidadArchivo>,
+}
+
+/// `Feature` de un archivo de límites.
+#[derive(Debug, Deserialize)]
+struct EntidadArchivo {
+    #[serde(default)]
+    properties: Option<Map<String, Value>>,
+    geometry: Option<GeometriaArchivo>,
+}
+
+/// Geometrías aceptadas en los límites. Las coordenadas pueden traer altitud, que se ignora.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum GeometriaArchivo {
+    Polygon {
+        coordinates: Vec<Vec<Vec<f64>>>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<Vec<f64>>>>,
+    },
+}
+
+/// Convierte los anillos de GeoJSON en [`Poligono`]. Falla si algún vértice no tiene longitud y latitud.
+fn poligono(anillos: Vec<Vec<Vec<f64>>>) -> Option<Poligono> {
+    anillos
+        .into_iter()
+        .map(|anillo| {
+            anillo
+                .into_iter()
+                .map(|vertice| match vertice[..] {
+                    [longitud, latitud, ..] => Some([longitud, latitud]),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Valor entero de la primera propiedad que exista entre `nombres`. Acepta números o textos
+/// (`"01000"`), como vienen en los archivos de INEGI y SEPOMEX.
+fn propiedad_entera(propiedades: &Map<String, Value>, nombres: &[&str]) -> Option<i32> {
+    let valor = nombres.iter().find_map(|nombre| propiedades.get(*nombre))?;
+    match valor {
+        Value::Number(numero) => numero.as_i64()?.try_into().ok(),
+        Value::String(texto) => texto.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Lee los límites de `{catalogos_path}/{archivo}.geojson`. Si el archivo no existe devuelve `None`.
+///
+/// ## Argumentos
+/// * `catalogos_path` - Ruta base donde se encuentran los catálogos.
+/// * `archivo` - `limites_municipios` o `limites_codigos_postales`.
+/// * `propiedades` - Nombres aceptados para la propiedad con el id, en orden de preferencia.
+///
+/// ## Errores
+/// Retorna [`UbicacionError::LimitesInvalidos`] si el archivo no es GeoJSON válido o una entidad
+/// no tiene id o geometría de polígono, o [`UbicacionError::LecturaCatalogo`] si no se pudo abrir.
+fn leer_limites(
+    catalogos_path: &str,
+    archivo: &'static str,
+    propiedades: &[&str],
+) -> Result<Option<Capa>, UbicacionError> {
+    let Some(ruta) = buscar_archivo(catalogos_path, archivo, &EXTENSIONES_LIMITES) else {
+        return Ok(None);
+    };
+    let invalido = |causa: String| UbicacionError::LimitesInvalidos { archivo, causa };
+
+    let lector = BufReader::new(abrir_archivo(&ruta)?);
+    let coleccion: ArchivoLimites =
+        serde_json::from_reader(lector).map_err(|e| invalido(e.to_string()))?;
+
+    let mut capa = Capa::default();
+    for (indice, entidad) in coleccion.features.into_iter().enumerate() {
+        let id = entidad
+            .properties
+            .as_ref()
+            .and_then(|p| propiedad_entera(p, propiedades))
+            .ok_or_else(|| {
+                invalido(format!(
+                    "la entidad {} no tiene la propiedad {}",
+                    indice, propiedades[0]
+                ))
+            })?;
+        let poligonos = match entidad.geometry {
+            Some(GeometriaArchivo::Polygon { coordinates }) => {
+                poligono(coordinates).map(|p| vec![p])
+            }
+            Some(GeometriaArchivo::MultiPolygon { coordinates }) => {
+                coordinates.into_iter().map(poligono).collect()
+            }
+            None => None,
+        }
+        .ok_or_else(|| {
+            invalido(format!(
+                "la entidad {} (id {}) no tiene polígono",
+                indice, id
+            ))
+        })?;
+        capa.agregar(id, poligonos);
+    }
+    Ok(Some(capa))
+}
+
+/// Límites de un archivo, con un índice por id.
+#[derive(Debug, Default)]
+struct Capa {
+    limites: Vec<Limite>,
+    /// Posición en `limites` de cada id
+    por_id: HashMap<i32, usize>,
+}
+
+impl Capa {
+    /// Agrega los polígonos de un id. Si el id ya existe (p. ej. un municipio con islas
+    /// partido en varias entidades), se unen con los anteriores.
+    fn agregar(&mut self, id: i32, poligonos: Vec<Poligono>) {
+        match self.por_id.entry(id) {
+            Entry::Occupied(posicion) => {
+                let limite = &mut self.limites[*posicion.get()];
+                let mut todos = std::mem::take(&mut limite.poligonos);
+                todos.extend(poligonos);
+                *limite = Limite::new(id, todos);
+            }
+            Entry::Vacant(posicion) => {
+                posicion.insert(self.limites.len());
+                self.limites.push(Limite::new(id, poligonos));
+            }
+        }
+    }
+
+    /// Id del primer límite que contiene las coordenadas. En la frontera entre dos polígonos
+    /// el resultado depende del orden del archivo.
+    fn contenedor(&self, latitud: f64, longitud: f64) -> Option<i32> {
+        self.limites
+            .iter()
+            .find(|limite| limite.contiene(longitud, latitud))
+            .map(|limite| limite.id)
+    }
+
+    /// Polígonos de un id como geometría de GeoJSON.
+    fn geometria(&self, id: i32) -> Option<GeometriaGeoJson> {
+        let limite = &self.limites[*self.por_id.get(&id)?];
+        Some(GeometriaGeoJson::MultiPolygon {
+            coordinates: limite.poligonos.clone(),
+        })
+    }
+}
+
+/// Límites de municipios y códigos postales. Cada capa se comparte con la versión anterior
+/// mientras su archivo no cambie.
+#[derive(Debug, Default)]
+struct Limites {
+    municipios: Arc<Capa>,
+    codigos_postales: Arc<Capa>,
+}
+
+impl Limites {
+    /// Lee los límites de `catalogos_path`. La capa cuyo archivo no está en el directorio se toma
+    /// de `anteriores`.
+    fn leer(catalogos_path: &str, anteriores: &Limites) -> Result<Self, UbicacionError> {
+        let municipios = leer_limites(catalogos_path, LIMITES_MUNICIPIOS, &["id_municipio"])?;
+        let codigos_postales =
+            leer_limites(catalogos_path, LIMITES_CODIGOS_POSTALES, &["cp", "d_cp"])?;
+        Ok(Limites {
+            municipios: municipios
+                .map(Arc::new)
+                .unwrap_or_else(|| anteriores.municipios.clone()),
+            codigos_postales: codigos_postales
+                .map(Arc::new)
+                .unwrap_or_else(|| anteriores.codigos_postales.clone()),
+        })
+    }
+}
+
+/// Límites geográficos compartidos entre workers.
+/// Se leen al arrancar y después de cada recarga de catálogos.
+#[derive(Debug, Default)]
+pub struct LimitesGeograficos {
+    limites: RwLock<Arc<Limites>>,
+}
+
+impl LimitesGeograficos {
+    /// Vuelve a leer los límites de `catalogos_path`. Cada capa se reemplaza sólo si su archivo
+    /// está en el directorio; si falta, o la lectura falla, se conservan los límites anteriores.
+    ///
+    /// ## Errores
+    /// Retorna [`UbicacionError::LimitesInvalidos`] o [`UbicacionError::LecturaCatalogo`].
+    pub async fn recargar(&self, catalogos_path: &str) -> Result<(), UbicacionError> {
+        // Leer archivos de cientos de MB bloquea, por lo que se hace fuera del worker
+        let catalogos_path = catalogos_path.to_string();
+        let anteriores = self.limites.read().unwrap().clone();
+        let nuevos = web::block(move || Limites::leer(&catalogos_path, &anteriores))
+            .await
+            .map_err(|e| io::Error::other(e.to_string()))??;
+        *self.limites.write().unwrap() = Arc::new(nuevos);
+        Ok(())
+    }
+
+    /// Indica si hay límites de municipios cargados.
+    pub fn tiene_municipios(&self) -> bool {
+        !self.limites.read().unwrap().municipios.limites.is_empty()
+    }
+
+    /// Id del municipio cuyo polígono contiene las coordenadas (en grados).
+    pub fn municipio(&self, latitud: f64, longitud: f64) -> Option<i32> {
+        let limites = self.limites.read().unwrap().clone();
+        limites.municipios.contenedor(latitud, longitud)
+    }
+
+    /// Código postal cuyo polígono contiene las coordenadas (en grados). Es `None` también si
+    /// no se cargaron límites de códigos postales.
+    pub fn codigo_postal(&self, latitud: f64, longitud: f64) -> Option<i32> {
+        let limites = self.limites.read().unwrap().clone();
+        limites.codigos_postales.contenedor(latitud, longitud)
+    }
+
+    /// Límites de un municipio como multipolígono, o `None` si no se cargaron.
+    pub fn geometria_municipio(&self, id_municipio: i32) -> Option<GeometriaGeoJson> {
+        self.limites
+            .read()
+            .unwrap()
+            .municipios
+            .geometria(id_municipio)
+    }
+
+    /// Límites de un código postal como multipolígono, o `None` si no se cargaron.
+    pub fn geometria_codigo_postal(&self, cp: i32) -> Option<GeometriaGeoJson> {
+        self.limites.read().unwrap().codigos_postales.geometria(cp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Cuadrado alrededor de Cuauhtémoc (CDMX) como `FeatureCollection` con la propiedad dada.
+    fn cuadrado(propiedad: &str, id: i32) -> String {
+        format!(
+            r#"{{"type":"FeatureCollection","features":[{{"type":"Feature",
+            "properties":{{"{propiedad}":{id}}},
+            "geometry":{{"type":"Polygon","coordinates":[[[-99.2,19.4],[-99.1,19.4],
+            [-99.1,19.5],[-99.2,19.5],[-99.2,19.4]]]}}}}]}}"#
+        )
+    }
+
+    /// Directorio temporal con los archivos dados.
+    fn directorio(nombre: &str, archivos: &[(&str, String)]) -> String {
+        let directorio: PathBuf =
+            std::env::temp_dir().join(format!("limites_{}_{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(&directorio).unwrap();
+        for (archivo, contenido) in archivos {
+            std::fs::write(directorio.join(archivo), contenido).unwrap();
+        }
+        directorio.to_string_lossy().to_string()
+    }
+
+    #[actix_web::test]
+    async fn recarga_conserva_la_capa_sin_archivo() {
+        let limites = LimitesGeograficos::default();
+        let municipios = directorio(
+            "municipios",
+            &[("limites_municipios.geojson", cuadrado("id_municipio", 6))],
+        );
+        limites.recargar(&municipios).await.unwrap();
+        assert_eq!(limites.municipio(19.427, -99.16), Some(6));
+        assert_eq!(limites.codigo_postal(19.427, -99.16), None);
+
+        let codigos_postales = directorio(
+            "codigos_postales",
+            &[("limites_codigos_postales.geojson", cuadrado("d_cp", 6600))],
+        );
+        limites.recargar(&codigos_postales).await.unwrap();
+        assert_eq!(limites.municipio(19.427, -99.16), Some(6));
+        assert_eq!(limites.codigo_postal(19.427, -99.16), Some(6600));
+    }
+
+    #[actix_web::test]
+    async fn recarga_invalida_conserva_los_limites() {
+        let limites = LimitesGeograficos::default();
+        let validos = directorio(
+            "validos",
+            &[("limites_municipios.geojson", cuadrado("id_municipio", 6))],
+        );
+        limites.recargar(&validos).await.unwrap();
+
+        let invalidos = directorio(
+            "invalidos",
+            &[
+                ("limites_municipios.geojson", cuadrado("id_municipio", 3)),
+                ("limites_codigos_postales.geojson", "{".to_string()),
+            ],
+        );
+        assert!(matches!(
+            limites.recargar(&invalidos).await,
+            Err(UbicacionError::LimitesInvalidos { .. })
+        ));
+        assert_eq!(limites.municipio(19.427, -99.16), Some(6));
+    }
+}
@@ -1 +1,8 @@
+pub mod cache_cp;
 pub mod catalogos_ubicacion;
+pub mod diferencias_catalogos;
+pub mod indice_geografico;
+pub mod limites_geograficos;
+pub mod recarga_catalogos;
+pub mod repositorio;
+pub mod validacion_catalogos;
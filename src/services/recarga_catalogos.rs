@@ -0,0 +1,77 @@
+//! # Registro de trabajos de recarga de catálogos
+//! En este módulo se incluye el registro compartido entre workers de Actix con el
+//! estado de las recargas de catálogos que se ejecutan en segundo plano.
+use crate::types::{
+    catalogos::{EstadoRecarga, TrabajoRecarga},
+    errores::UbicacionError,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Trabajos de recarga iniciados desde que arrancó el servicio.
+/// Se comparte entre workers con `web::Data`, por lo que debe crearse fuera de `HttpServer::new`.
+#[derive(Debug, Default)]
+pub struct TrabajosRecarga {
+    siguiente_id: AtomicU64,
+    trabajos: Mutex<HashMap<u64, TrabajoRecarga>>,
+}
+
+impl TrabajosRecarga {
+    /// Registra un trabajo nuevo en estado `en_proceso`.
+    /// Devuelve `None` si ya hay otra recarga en proceso, pues ambas vaciarían las mismas tablas.
+    pub fn registrar(&self, origen: String) -> Option<TrabajoRecarga> {
+        let mut trabajos = self.trabajos.lock().unwrap();
+        if trabajos
+            .values()
+            .any(|t| t.estado == EstadoRecarga::EnProceso)
+        {
+            return None;
+        }
+
+        let trabajo = TrabajoRecarga {
+            job_id: self.siguiente_id.fetch_add(1, Ordering::Relaxed) + 1,
+            estado: EstadoRecarga::EnProceso,
+            origen,
+            error: None,
+            advertencias: Vec::new(),
+        };
+        trabajos.insert(trabajo.job_id, trabajo.clone());
+        Some(trabajo)
+    }
+
+    /// Marca el trabajo como completado o fallido según el resultado de la recarga, con las
+    /// advertencias de los pasos posteriores que fallaron.
+    pub fn finalizar(
+        &self,
+        job_id: u64,
+        resultado: Result<(), UbicacionError>,
+        advertencias: Vec<String>,
+    ) {
+        if let Some(trabajo) = self.trabajos.lock().unwrap().get_mut(&job_id) {
+            trabajo.advertencias = advertencias;
+            match resultado {
+                Ok(()) => trabajo.estado = EstadoRecarga::Completada,
+                Err(e) => {
+                    trabajo.estado = EstadoRecarga::Fallida;
+                    trabajo.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Indica si hay una recarga en proceso. Sirve para rechazar una recarga antes de recibir sus
+    /// archivos; `registrar` vuelve a revisarlo.
+    pub fn en_proceso(&self) -> bool {
+        self.trabajos
+            .lock()
+            .unwrap()
+            .values()
+            .any(|t| t.estado == EstadoRecarga::EnProceso)
+    }
+
+    /// Devuelve una copia del estado actual del trabajo, si existe.
+    pub fn consultar(&self, job_id: u64) -> Option<TrabajoRecarga> {
+        self.trabajos.lock().unwrap().get(&job_id).cloned()
+    }
+}
@@ -0,0 +1,872 @@
+//! # Repositorio de catálogos en la base de datos
+//! Consulta las tablas `cat_*` creadas por las migraciones, en el motor que indique `DATABASE_URL`.
+//! Las zonas de entrega se guardan en `zonas` y sus tablas de miembros.
+use super::RepositorioUbicacion;
+use crate::{
+    entities::{prelude::*, *},
+    services::{catalogos_ubicacion::recargar_catalogos, indice_geografico::Centroide},
+    types::{
+        catalogos::{
+            CPResponse, CentroideCodigoPostal, CodigosPostalesMunicipio, LocalidadCP,
+            MunicipioEstado, MunicipiosEstado,
+        },
+        errores::UbicacionError,
+        zonas::{clave_zona, MiembrosFaltantes, MotivoZona, RangoCP, Zona, ZonaCP, ZonaPayload},
+    },
+    utils::conversores::CatalogoIdCadena,
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, Condition,
+    ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set, SqlErr, TransactionTrait,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Renglones por `INSERT` al guardar los miembros de una zona
+const LOTE_MIEMBROS: usize = 5000;
+
+/// Repositorio que consulta los catálogos en la base de datos.
+pub struct RepositorioBaseDatos {
+    db: DatabaseConnection,
+}
+
+impl RepositorioBaseDatos {
+    /// Crea el repositorio sobre un pool de conexiones ya migrado.
+    pub fn new(db: DatabaseConnection) -> Self {
+        RepositorioBaseDatos { db }
+    }
+}
+
+#[async_trait]
+impl RepositorioUbicacion for RepositorioBaseDatos {
+    async fn buscar_cp(&self, cp: i32) -> Result<CPResponse, UbicacionError> {
+        // Una sola consulta: el CP con su estado y municipio, y un renglón por localidad
+        let renglones: Vec<RenglonCP> = consulta_cp(cp)
+            .into_model::<RenglonCP>()
+            .all(&self.db)
+            .await?;
+
+        let Some(primero) = renglones.first() else {
+            return Err(UbicacionError::CodigoPostalNoEncontrado);
+        };
+
+        // Estado y municipio son los del CP, no los de sus localidades
+        let estado = match (primero.id_estado, &primero.estado) {
+            (Some(id), Some(estado)) => CatalogoIdCadena {
+                id,
+                value: estado.clone(),
+            },
+            _ => return Err(UbicacionError::CatalogoInconsistente("estados")),
+        };
+        let municipio = match (primero.id_municipio, &primero.municipio) {
+            (Some(id), Some(municipio)) => CatalogoIdCadena {
+                id,
+                value: municipio.clone(),
+            },
+            _ => return Err(UbicacionError::CatalogoInconsistente("municipios")),
+        };
+        let (latitud, longitud) = (primero.latitud_cp, primero.longitud_cp);
+
+        // Un CP sin localidades trae un solo renglón con la localidad en NULL
+        let localidades: Vec<LocalidadCP> = renglones
+            .into_iter()
+            .filter_map(|r| match (r.id_localidad, r.localidad) {
+                (Some(id), Some(value)) => Some(LocalidadCP {
+                    id,
+                    value,
+                    latitud: r.latitud_localidad,
+                    longitud: r.longitud_localidad,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(CPResponse {
+            estado,
+            municipio,
+            localidades,
+            latitud,
+            longitud,
+        })
+    }
+
+    async fn buscar_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<MunicipioEstado>, UbicacionError> {
+        let Some((municipio, estado)) = CatMunicipios::find_by_id(id_municipio)
+            .find_also_related(CatEstados)
+            .one(&self.db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let estado = estado.ok_or(UbicacionError::CatalogoInconsistente("estados"))?;
+
+        Ok(Some(MunicipioEstado {
+            estado: CatalogoIdCadena {
+                id: estado.id,
+                value: estado.estado,
+            },
+            municipio: CatalogoIdCadena {
+                id: municipio.id,
+                value: municipio.municipio,
+            },
+        }))
+    }
+
+    async fn municipios_estado(
+        &self,
+        id_estado: i32,
+    ) -> Result<Option<MunicipiosEstado>, UbicacionError> {
+        let Some(estado) = CatEstados::find_by_id(id_estado).one(&self.db).await? else {
+            return Ok(None);
+        };
+        let municipios = consulta_municipios_estado(id_estado)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|m| CatalogoIdCadena {
+                id: m.id,
+                value: m.municipio,
+            })
+            .collect();
+
+        Ok(Some(MunicipiosEstado {
+            estado: CatalogoIdCadena {
+                id: estado.id,
+                value: estado.estado,
+            },
+            municipios,
+        }))
+    }
+
+    async fn codigos_postales_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<CodigosPostalesMunicipio>, UbicacionError> {
+        let Some(MunicipioEstado { estado, municipio }) =
+            self.buscar_municipio(id_municipio).await?
+        else {
+            return Ok(None);
+        };
+        let codigos_postales = consulta_codigos_postales_municipio(id_municipio)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|c| CentroideCodigoPostal {
+                cp: c.codigo_postal,
+                latitud: c.latitud,
+                longitud: c.longitud,
+            })
+            .collect();
+
+        Ok(Some(CodigosPostalesMunicipio {
+            estado,
+            municipio,
+            codigos_postales,
+        }))
+    }
+
+    async fn centroides_codigos_postales(
+        &self,
+        cps: &[i32],
+    ) -> Result<Vec<CentroideCodigoPostal>, UbicacionError> {
+        Ok(CatCodigosPostales::find()
+            .filter(cat_codigos_postales::Column::CodigoPostal.is_in(cps.iter().copied()))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|c| CentroideCodigoPostal {
+                cp: c.codigo_postal,
+                latitud: c.latitud,
+                longitud: c.longitud,
+            })
+            .collect())
+    }
+
+    async fn recargar(&self, catalogos_path: &str) -> Result<(), UbicacionError> {
+        recargar_catalogos(&self.db, catalogos_path).await
+    }
+
+    async fn centroides(&self) -> Result<Vec<Centroide>, UbicacionError> {
+        // Estado y municipio son los del CP, también para las localidades
+        let cps: Vec<RenglonCentroide> = CatCodigosPostales::find()
+            .select_only()
+            .column_as(cat_codigos_postales::Column::CodigoPostal, "cp")
+            .column_as(cat_estados::Column::Id, "id_estado")
+            .column(cat_estados::Column::Estado)
+            .column_as(cat_municipios::Column::Id, "id_municipio")
+            .column(cat_municipios::Column::Municipio)
+            .expr_as(Expr::val(Option::<i32>::None), "id_localidad")
+            .expr_as(Expr::val(Option::<String>::None), "localidad")
+            .column(cat_codigos_postales::Column::Latitud)
+            .column(cat_codigos_postales::Column::Longitud)
+            .inner_join(CatEstados)
+            .inner_join(CatMunicipios)
+            .filter(cat_codigos_postales::Column::Latitud.is_not_null())
+            .filter(cat_codigos_postales::Column::Longitud.is_not_null())
+            .into_model()
+            .all(&self.db)
+            .await?;
+        let localidades: Vec<RenglonCentroide> = CatLocalidades::find()
+            .select_only()
+            .column_as(cat_codigos_postales::Column::CodigoPostal, "cp")
+            .column_as(cat_estados::Column::Id, "id_estado")
+            .column(cat_estados::Column::Estado)
+            .column_as(cat_municipios::Column::Id, "id_municipio")
+            .column(cat_municipios::Column::Municipio)
+            .column_as(cat_localidades::Column::Id, "id_localidad")
+            .column(cat_localidades::Column::Localidad)
+            .column(cat_localidades::Column::Latitud)
+            .column(cat_localidades::Column::Longitud)
+            .inner_join(CatCodigosPostales)
+            .join(
+                JoinType::InnerJoin,
+                cat_codigos_postales::Relation::CatEstados.def(),
+            )
+            .join(
+                JoinType::InnerJoin,
+                cat_codigos_postales::Relation::CatMunicipios.def(),
+            )
+            .filter(cat_localidades::Column::Latitud.is_not_null())
+            .filter(cat_localidades::Column::Longitud.is_not_null())
+            .into_model()
+            .all(&self.db)
+            .await?;
+
+        Ok(cps
+            .into_iter()
+            .chain(localidades)
+            .map(|r| Centroide {
+                cp: r.cp,
+                estado: CatalogoIdCadena {
+                    id: r.id_estado,
+                    value: r.estado,
+                },
+                municipio: CatalogoIdCadena {
+                    id: r.id_municipio,
+                    value: r.municipio,
+                },
+                localidad: r
+                    .id_localidad
+                    .zip(r.localidad)
+                    .map(|(id, value)| CatalogoIdCadena { id, value }),
+                latitud: r.latitud,
+                longitud: r.longitud,
+            })
+            .collect())
+    }
+
+    async fn miembros_faltantes(
+        &self,
+        payload: &ZonaPayload,
+    ) -> Result<MiembrosFaltantes, UbicacionError> {
+        // Una consulta por tipo de miembro, en lotes para no exceder los parámetros por sentencia
+        let mut codigos_postales = HashSet::new();
+        for lote in payload.codigos_postales.chunks(LOTE_MIEMBROS) {
+            codigos_postales.extend(
+                CatCodigosPostales::find()
+                    .select_only()
+                    .column(cat_codigos_postales::Column::CodigoPostal)
+                    .filter(cat_codigos_postales::Column::CodigoPostal.is_in(lote.iter().copied()))
+                    .into_tuple::<i32>()
+                    .all(&self.db)
+                    .await?,
+            );
+        }
+
+        // CPs del catálogo dentro de algún rango, ordenados para buscar el primero de cada rango
+        let mut en_rangos = Vec::new();
+        for lote in payload.rangos.chunks(LOTE_MIEMBROS) {
+            let condicion = lote.iter().fold(Condition::any(), |condicion, rango| {
+                condicion.add(
+                    cat_codigos_postales::Column::CodigoPostal.between(rango.desde, rango.hasta),
+                )
+            });
+            en_rangos.extend(
+                CatCodigosPostales::find()
+                    .select_only()
+                    .column(cat_codigos_postales::Column::CodigoPostal)
+                    .filter(condicion)
+                    .into_tuple::<i32>()
+                    .all(&self.db)
+                    .await?,
+            );
+        }
+        en_rangos.sort_unstable();
+
+        let mut municipios = HashSet::new();
+        for lote in payload.municipios.chunks(LOTE_MIEMBROS) {
+            municipios.extend(
+                CatMunicipios::find()
+                    .select_only()
+                    .column(cat_municipios::Column::Id)
+                    .filter(cat_municipios::Column::Id.is_in(lote.iter().copied()))
+                    .into_tuple::<i32>()
+                    .all(&self.db)
+                    .await?,
+            );
+        }
+
+        Ok(MiembrosFaltantes {
+            codigos_postales: payload
+                .codigos_postales
+                .iter()
+                .copied()
+                .filter(|cp| !codigos_postales.contains(cp))
+                .collect(),
+            rangos: payload
+                .rangos
+                .iter()
+                .copied()
+                .filter(|rango| {
+                    let primero = en_rangos.partition_point(|&cp| cp < rango.desde);
+                    en_rangos.get(primero).is_none_or(|&cp| cp > rango.hasta)
+                })
+                .collect(),
+            municipios: payload
+                .municipios
+                .iter()
+                .copied()
+                .filter(|id_municipio| !municipios.contains(id_municipio))
+                .collect(),
+        })
+    }
+
+    fn zonas_persistentes(&self) -> bool {
+        true
+    }
+
+    async fn zonas(&self) -> Result<Vec<Zona>, UbicacionError> {
+        let zonas = Zonas::find()
+            .order_by_asc(zonas::Column::Id)
+            .all(&self.db)
+            .await?;
+        con_miembros(&self.db, zonas).await
+    }
+
+    async fn zona(&self, id_zona: i32) -> Result<Option<Zona>, UbicacionError> {
+        let Some(zona) = Zonas::find_by_id(id_zona).one(&self.db).await? else {
+            return Ok(None);
+        };
+        Ok(con_miembros(&self.db, vec![zona]).await?.pop())
+    }
+
+    async fn crear_zona(&self, payload: ZonaPayload) -> Result<Zona, UbicacionError> {
+        let txn = self.db.begin().await?;
+        let zona = zonas::ActiveModel {
+            id: NotSet,
+            nombre: Set(payload.nombre.clone()),
+            clave: Set(clave_zona(&payload.nombre)),
+            descripcion: Set(payload.descripcion.clone()),
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| error_nombre_zona(e, &payload.nombre))?;
+        insertar_miembros(&txn, zona.id, &payload).await?;
+        txn.commit().await?;
+        Ok(Zona::new(zona.id, payload))
+    }
+
+    async fn actualizar_zona(
+        &self,
+        id_zona: i32,
+        payload: ZonaPayload,
+    ) -> Result<Option<Zona>, UbicacionError> {
+        let txn = self.db.begin().await?;
+        let Some(zona) = Zonas::find_by_id(id_zona).one(&txn).await? else {
+            return Ok(None);
+        };
+        let mut zona: zonas::ActiveModel = zona.into();
+        zona.nombre = Set(payload.nombre.clone());
+        zona.clave = Set(clave_zona(&payload.nombre));
+        zona.descripcion = Set(payload.descripcion.clone());
+        zona.update(&txn)
+            .await
+            .map_err(|e| error_nombre_zona(e, &payload.nombre))?;
+        eliminar_miembros(&txn, id_zona).await?;
+        insertar_miembros(&txn, id_zona, &payload).await?;
+        txn.commit().await?;
+        Ok(Some(Zona::new(id_zona, payload)))
+    }
+
+    async fn eliminar_zona(&self, id_zona: i32) -> Result<bool, UbicacionError> {
+        // Los miembros se borran con el `ON DELETE CASCADE` de sus claves foráneas; sqlx activa
+        // las claves foráneas en cada conexión de SQLite
+        let eliminadas = Zonas::delete_by_id(id_zona)
+            .exec(&self.db)
+            .await?
+            .rows_affected;
+        Ok(eliminadas > 0)
+    }
+
+    async fn zonas_cp(&self, cp: i32) -> Result<Vec<ZonaCP>, UbicacionError> {
+        let Some(codigo_postal) = CatCodigosPostales::find_by_id(cp).one(&self.db).await? else {
+            return Err(UbicacionError::CodigoPostalNoEncontrado);
+        };
+
+        // Zonas de cada tipo de miembro que incluye al CP
+        let por_codigo_postal: Vec<i32> = ZonasCodigosPostales::find()
+            .select_only()
+            .column(zonas_codigos_postales::Column::IdZona)
+            .filter(zonas_codigos_postales::Column::CodigoPostal.eq(cp))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        let por_rango: Vec<i32> = ZonasRangosCp::find()
+            .select_only()
+            .column(zonas_rangos_cp::Column::IdZona)
+            .filter(zonas_rangos_cp::Column::CpDesde.lte(cp))
+            .filter(zonas_rangos_cp::Column::CpHasta.gte(cp))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        let por_municipio: Vec<i32> = ZonasMunicipios::find()
+            .select_only()
+            .column(zonas_municipios::Column::IdZona)
+            .filter(zonas_municipios::Column::IdMunicipio.eq(codigo_postal.id_municipio))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let mut motivos: BTreeMap<i32, Vec<MotivoZona>> = BTreeMap::new();
+        for (ids, motivo) in [
+            (por_codigo_postal, MotivoZona::CodigoPostal),
+            (por_rango, MotivoZona::Rango),
+            (por_municipio, MotivoZona::Municipio),
+        ] {
+            for id_zona in ids {
+                let motivos_zona = motivos.entry(id_zona).or_default();
+                if !motivos_zona.contains(&motivo) {
+                    motivos_zona.push(motivo);
+                }
+            }
+        }
+        if motivos.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let zonas = Zonas::find()
+            .filter(zonas::Column::Id.is_in(motivos.keys().copied()))
+            .order_by_asc(zonas::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(zonas
+            .into_iter()
+            .map(|zona| ZonaCP {
+                motivos: motivos.remove(&zona.id).unwrap_or_default(),
+                id: zona.id,
+                nombre: zona.nombre,
+                descripcion: zona.descripcion,
+            })
+            .collect())
+    }
+}
+
+/// Agrega a las zonas leídas de `zonas` sus códigos postales, rangos y municipios, ordenados.
+async fn con_miembros<C: ConnectionTrait>(
+    conn: &C,
+    zonas: Vec<zonas::Model>,
+) -> Result<Vec<Zona>, UbicacionError> {
+    let ids: Vec<i32> = zonas.iter().map(|zona| zona.id).collect();
+
+    let mut codigos_postales: HashMap<i32, Vec<i32>> = HashMap::new();
+    for miembro in ZonasCodigosPostales::find()
+        .filter(zonas_codigos_postales::Column::IdZona.is_in(ids.clone()))
+        .order_by_asc(zonas_codigos_postales::Column::CodigoPostal)
+        .all(conn)
+        .await?
+    {
+        codigos_postales
+            .entry(miembro.id_zona)
+            .or_default()
+            .push(miembro.codigo_postal);
+    }
+    let mut rangos: HashMap<i32, Vec<RangoCP>> = HashMap::new();
+    for miembro in ZonasRangosCp::find()
+        .filter(zonas_rangos_cp::Column::IdZona.is_in(ids.clone()))
+        .order_by_asc(zonas_rangos_cp::Column::CpDesde)
+        .order_by_asc(zonas_rangos_cp::Column::CpHasta)
+        .all(conn)
+        .await?
+    {
+        rangos.entry(miembro.id_zona).or_default().push(RangoCP {
+            desde: miembro.cp_desde,
+            hasta: miembro.cp_hasta,
+        });
+    }
+    let mut municipios: HashMap<i32, Vec<i32>> = HashMap::new();
+    for miembro in ZonasMunicipios::find()
+        .filter(zonas_municipios::Column::IdZona.is_in(ids))
+        .order_by_asc(zonas_municipios::Column::IdMunicipio)
+        .all(conn)
+        .await?
+    {
+        municipios
+            .entry(miembro.id_zona)
+            .or_default()
+            .push(miembro.id_municipio);
+    }
+
+    Ok(zonas
+        .into_iter()
+        .map(|zona| Zona {
+            codigos_postales: codigos_postales.remove(&zona.id).unwrap_or_default(),
+            rangos: rangos.remove(&zona.id).unwrap_or_default(),
+            municipios: municipios.remove(&zona.id).unwrap_or_default(),
+            id: zona.id,
+            nombre: zona.nombre,
+            descripcion: zona.descripcion,
+        })
+        .collect())
+}
+
+/// Inserta en lotes los miembros de `payload` para la zona `id_zona`.
+async fn insertar_miembros<C: ConnectionTrait>(
+    conn: &C,
+    id_zona: i32,
+    payload: &ZonaPayload,
+) -> Result<(), DbErr> {
+    // Las tablas de miembros no tienen id autoincremental, por lo que no hay nada que devolver
+    for lote in payload.codigos_postales.chunks(LOTE_MIEMBROS) {
+        ZonasCodigosPostales::insert_many(lote.iter().map(|&codigo_postal| {
+            zonas_codigos_postales::ActiveModel {
+                id_zona: Set(id_zona),
+                codigo_postal: Set(codigo_postal),
+            }
+        }))
+        .exec_without_returning(conn)
+        .await?;
+    }
+    for lote in payload.rangos.chunks(LOTE_MIEMBROS) {
+        ZonasRangosCp::insert_many(lote.iter().map(|rango| zonas_rangos_cp::ActiveModel {
+            id_zona: Set(id_zona),
+            cp_desde: Set(rango.desde),
+            cp_hasta: Set(rango.hasta),
+        }))
+        .exec_without_returning(conn)
+        .await?;
+    }
+    for lote in payload.municipios.chunks(LOTE_MIEMBROS) {
+        ZonasMunicipios::insert_many(lote.iter().map(|&id_municipio| {
+            zonas_municipios::ActiveModel {
+                id_zona: Set(id_zona),
+                id_municipio: Set(id_municipio),
+            }
+        }))
+        .exec_without_returning(conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Elimina los códigos postales, rangos y municipios de la zona `id_zona`.
+async fn eliminar_miembros<C: ConnectionTrait>(conn: &C, id_zona: i32) -> Result<(), DbErr> {
+    ZonasCodigosPostales::delete_many()
+        .filter(zonas_codigos_postales::Column::IdZona.eq(id_zona))
+        .exec(conn)
+        .await?;
+    ZonasRangosCp::delete_many()
+        .filter(zonas_rangos_cp::Column::IdZona.eq(id_zona))
+        .exec(conn)
+        .await?;
+    ZonasMunicipios::delete_many()
+        .filter(zonas_municipios::Column::IdZona.eq(id_zona))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Convierte la violación de `uq_zonas_clave` en [`UbicacionError::ZonaDuplicada`].
+fn error_nombre_zona(e: DbErr, nombre: &str) -> UbicacionError {
+    match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            UbicacionError::ZonaDuplicada(nombre.to_string())
+        }
+        _ => UbicacionError::BaseDatos(e),
+    }
+}
+
+/// Consulta de [`RepositorioBaseDatos::buscar_cp`]: el CP con su estado y municipio, y un
+/// renglón por localidad (con el índice `idx_localidades_codigo_postal`).
+fn consulta_cp(cp: i32) -> Select<CatCodigosPostales> {
+    CatCodigosPostales::find_by_id(cp)
+        .select_only()
+        .column_as(cat_estados::Column::Id, "id_estado")
+        .column(cat_estados::Column::Estado)
+        .column_as(cat_municipios::Column::Id, "id_municipio")
+        .column(cat_municipios::Column::Municipio)
+        .column_as(cat_codigos_postales::Column::Latitud, "latitud_cp")
+        .column_as(cat_codigos_postales::Column::Longitud, "longitud_cp")
+        .column_as(cat_localidades::Column::Id, "id_localidad")
+        .column(cat_localidades::Column::Localidad)
+        .column_as(cat_localidades::Column::Latitud, "latitud_localidad")
+        .column_as(cat_localidades::Column::Longitud, "longitud_localidad")
+        .left_join(CatEstados)
+        .left_join(CatMunicipios)
+        .left_join(CatLocalidades)
+        .order_by_asc(cat_localidades::Column::Id)
+}
+
+/// Consulta de [`RepositorioBaseDatos::municipios_estado`]: los municipios de un estado
+/// ordenados por id (con el índice `uq_municipios_estado_municipio`).
+fn consulta_municipios_estado(id_estado: i32) -> Select<CatMunicipios> {
+    CatMunicipios::find()
+        .filter(cat_municipios::Column::IdEstado.eq(id_estado))
+        .order_by_asc(cat_municipios::Column::Id)
+}
+
+/// Consulta de [`RepositorioBaseDatos::codigos_postales_municipio`]: los códigos postales de un
+/// municipio ordenados (con el índice `idx_cp_id_municipio`).
+fn consulta_codigos_postales_municipio(id_municipio: i32) -> Select<CatCodigosPostales> {
+    CatCodigosPostales::find()
+        .filter(cat_codigos_postales::Column::IdMunicipio.eq(id_municipio))
+        .order_by_asc(cat_codigos_postales::Column::CodigoPostal)
+}
+
+/// Renglón de la consulta de [`RepositorioBaseDatos::buscar_cp`].
+/// Las columnas son opcionales por los `LEFT JOIN`: un CP sin localidades o que apunta a
+/// un estado o municipio inexistente también devuelve renglón.
+#[derive(Debug, FromQueryResult)]
+struct RenglonCP {
+    id_estado: Option<i32>,
+    estado: Option<String>,
+    id_municipio: Option<i32>,
+    municipio: Option<String>,
+    latitud_cp: Option<Decimal>,
+    longitud_cp: Option<Decimal>,
+    id_localidad: Option<i32>,
+    localidad: Option<String>,
+    latitud_localidad: Option<Decimal>,
+    longitud_localidad: Option<Decimal>,
+}
+
+/// Renglón de la consulta de [`RepositorioBaseDatos::centroides`].
+/// La localidad es `NULL` en los centroides de códigos postales.
+#[derive(Debug, FromQueryResult)]
+struct RenglonCentroide {
+    cp: i32,
+    id_estado: i32,
+    estado: String,
+    id_municipio: i32,
+    municipio: String,
+    id_localidad: Option<i32>,
+    localidad: Option<String>,
+    latitud: Decimal,
+    longitud: Decimal,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pruebas::{bd_sqlite, CATALOGOS_PRUEBA},
+        services::repositorio::memoria::{CatalogosMemoria, RepositorioMemoria},
+    };
+    use sea_orm::{DbBackend, QueryTrait, Statement};
+
+    /// Migra una BD SQLite en memoria, carga los catálogos de prueba y consulta como lo hace
+    /// el repositorio en memoria. SQLite no guarda la escala de los decimales, por lo que las
+    /// coordenadas se comparan normalizadas.
+    #[actix_web::test]
+    async fn sqlite_responde_como_memoria() {
+        let sqlite = RepositorioBaseDatos::new(bd_sqlite().await);
+        let memoria =
+            RepositorioMemoria::new(CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap());
+        let normalizado = |valor: Option<Decimal>| valor.map(|v| v.normalize());
+
+        for cp in [3100, 4000, 6600, 6700, 44100] {
+            let (esperado, obtenido) = (
+                memoria.buscar_cp(cp).await.unwrap(),
+                sqlite.buscar_cp(cp).await.unwrap(),
+            );
+            assert_eq!(obtenido.estado, esperado.estado);
+            assert_eq!(obtenido.municipio, esperado.municipio);
+            assert_eq!(normalizado(obtenido.latitud), normalizado(esperado.latitud));
+            assert_eq!(
+                normalizado(obtenido.longitud),
+                normalizado(esperado.longitud)
+            );
+            let localidades = |r: &CPResponse| {
+                r.localidades
+                    .iter()
+                    .map(|l| (l.id, l.value.clone(), normalizado(l.latitud)))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(localidades(&obtenido), localidades(&esperado));
+        }
+        assert!(matches!(
+            sqlite.buscar_cp(99999).await,
+            Err(UbicacionError::CodigoPostalNoEncontrado)
+        ));
+
+        let municipios = sqlite.municipios_estado(9).await.unwrap().unwrap();
+        let ids: Vec<i32> = municipios.municipios.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![3, 4, 6]);
+        assert!(sqlite.municipios_estado(40).await.unwrap().is_none());
+
+        let cps = sqlite.codigos_postales_municipio(6).await.unwrap().unwrap();
+        let cps: Vec<i32> = cps.codigos_postales.iter().map(|c| c.cp).collect();
+        assert_eq!(cps, vec![6600, 6700]);
+
+        let mut centroides = sqlite
+            .centroides_codigos_postales(&[44100, 99999, 6600])
+            .await
+            .unwrap();
+        centroides.sort_by_key(|c| c.cp);
+        let cps: Vec<i32> = centroides.iter().map(|c| c.cp).collect();
+        assert_eq!(cps, vec![6600, 44100]);
+        assert_eq!(
+            normalizado(centroides[0].latitud),
+            normalizado(memoria.buscar_cp(6600).await.unwrap().latitud)
+        );
+
+        assert_eq!(
+            sqlite.centroides().await.unwrap().len(),
+            memoria.centroides().await.unwrap().len()
+        );
+    }
+
+    /// Detalle de `EXPLAIN QUERY PLAN` de la consulta en SQLite, un renglón por paso.
+    async fn plan<E: EntityTrait>(db: &DatabaseConnection, consulta: Select<E>) -> Vec<String> {
+        let sentencia = consulta.build(DbBackend::Sqlite);
+        let explain = Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            format!("EXPLAIN QUERY PLAN {}", sentencia.sql),
+            sentencia.values.map(|v| v.0).unwrap_or_default(),
+        );
+        db.query_all(explain)
+            .await
+            .unwrap()
+            .iter()
+            .map(|renglon| renglon.try_get("", "detail").unwrap())
+            .collect()
+    }
+
+    /// Las búsquedas por CP, por estado y por municipio usan los índices de
+    /// `m20261018_120000_indices_catalogos` en lugar de recorrer las tablas.
+    #[actix_web::test]
+    async fn consultas_usan_indices() {
+        let db = bd_sqlite().await;
+        let usa = |plan: &[String], indice: &str| {
+            assert!(
+                plan.iter()
+                    .any(|paso| paso.contains(&format!("INDEX {indice} "))),
+                "{indice} no está en {plan:?}"
+            );
+            assert!(
+                !plan.iter().any(|paso| paso.starts_with("SCAN ")),
+                "recorrido completo en {plan:?}"
+            );
+        };
+
+        usa(
+            &plan(&db, consulta_cp(6600)).await,
+            "idx_localidades_codigo_postal",
+        );
+        usa(
+            &plan(&db, consulta_municipios_estado(9)).await,
+            "uq_municipios_estado_municipio",
+        );
+        usa(
+            &plan(&db, consulta_codigos_postales_municipio(6)).await,
+            "idx_cp_id_municipio",
+        );
+    }
+
+    /// Zona de prueba en CDMX: dos CPs de Cuauhtémoc, el rango de Benito Juárez y Coyoacán.
+    fn zona_cdmx(nombre: &str) -> ZonaPayload {
+        ZonaPayload {
+            nombre: nombre.to_string(),
+            descripcion: None,
+            codigos_postales: vec![6600, 6700],
+            rangos: vec![RangoCP {
+                desde: 3000,
+                hasta: 3999,
+            }],
+            municipios: vec![4],
+        }
+    }
+
+    #[actix_web::test]
+    async fn zonas_en_sqlite() {
+        use sea_orm::PaginatorTrait;
+
+        let repositorio = RepositorioBaseDatos::new(bd_sqlite().await);
+        let zona = repositorio
+            .crear_zona(zona_cdmx("CDMX centro"))
+            .await
+            .unwrap();
+        assert_eq!(
+            repositorio
+                .zona(zona.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .rangos
+                .len(),
+            1
+        );
+        let zonas = repositorio.zonas_cp(3100).await.unwrap();
+        assert_eq!(zonas[0].motivos, [MotivoZona::Rango]);
+
+        // El nombre es único sin distinguir mayúsculas ni espacios repetidos
+        assert!(matches!(
+            repositorio.crear_zona(zona_cdmx("cdmx  CENTRO")).await,
+            Err(UbicacionError::ZonaDuplicada(_))
+        ));
+
+        // Los miembros se borran en cascada con la zona
+        assert!(repositorio.eliminar_zona(zona.id).await.unwrap());
+        assert_eq!(
+            ZonasCodigosPostales::find()
+                .count(&repositorio.db)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            ZonasRangosCp::find().count(&repositorio.db).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            ZonasMunicipios::find()
+                .count(&repositorio.db)
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(!repositorio.eliminar_zona(zona.id).await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn miembros_faltantes_como_memoria() {
+        let sqlite = RepositorioBaseDatos::new(bd_sqlite().await);
+        let memoria =
+            RepositorioMemoria::new(CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA).unwrap());
+        let rango = |desde, hasta| RangoCP { desde, hasta };
+        let payload = ZonaPayload {
+            nombre: "Prueba".to_string(),
+            descripcion: None,
+            codigos_postales: vec![3100, 9999, 44100, 99999],
+            rangos: vec![rango(3000, 3999), rango(7000, 7999), rango(44000, 44100)],
+            municipios: vec![3, 999],
+        };
+        let esperados = MiembrosFaltantes {
+            codigos_postales: vec![9999, 99999],
+            rangos: vec![rango(7000, 7999)],
+            municipios: vec![999],
+        };
+        assert_eq!(
+            sqlite.miembros_faltantes(&payload).await.unwrap(),
+            esperados
+        );
+        assert_eq!(
+            memoria.miembros_faltantes(&payload).await.unwrap(),
+            esperados
+        );
+    }
+}
@@ -0,0 +1,558 @@
+//! # Repositorio de catálogos en memoria
+//! Todos los catálogos (unos cuantos MB) se cargan al iniciar el servicio en índices por id y
+//! por código postal, por lo que no se necesita una base de datos. Los índices se construyen
+//! desde los CSV o desde un snapshot JSON generado previamente con el subcomando `snapshot`.
+//!
+//! Las zonas de entrega no se pueden crear sin BD, pues se perderían al reiniciar el servicio:
+//! no hay ninguna y las modificaciones fallan con [`UbicacionError::ZonasNoPersistentes`].
+use super::RepositorioUbicacion;
+use crate::{
+    services::{
+        catalogos_ubicacion::{
+            coordenadas, registros, validar, CodigoPostal, Estado, Localidad, Municipio,
+        },
+        indice_geografico::Centroide,
+    },
+    types::{
+        catalogos::{
+            CPResponse, CentroideCodigoPostal, CodigosPostalesMunicipio, LocalidadCP,
+            MunicipioEstado, MunicipiosEstado,
+        },
+        errores::UbicacionError,
+        zonas::{MiembrosFaltantes, Zona, ZonaCP, ZonaPayload},
+    },
+    utils::conversores::CatalogoIdCadena,
+};
+use actix_web::web;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::sync::{Arc, RwLock};
+
+/// Índices de los catálogos en memoria. Es también el formato del snapshot.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CatalogosMemoria {
+    /// Nombre del estado por id
+    estados: HashMap<i32, String>,
+    /// Nombre del municipio por id
+    municipios: HashMap<i32, String>,
+    /// Estado de cada municipio, por id de municipio
+    #[serde(default)]
+    estados_municipios: HashMap<i32, i32>,
+    /// Municipio, estado y centroide por código postal
+    codigos_postales: HashMap<i32, CodigoPostalMemoria>,
+    /// Localidades por código postal, ordenadas por id
+    localidades: HashMap<i32, Vec<LocalidadCP>>,
+}
+
+/// Renglón de `cat_codigos_postales` en memoria.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(from = "CodigoPostalSnapshot")]
+struct CodigoPostalMemoria {
+    id_municipio: i32,
+    id_estado: i32,
+    latitud: Option<Decimal>,
+    longitud: Option<Decimal>,
+}
+
+/// Formatos de un código postal en el snapshot.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CodigoPostalSnapshot {
+    Actual {
+        id_municipio: i32,
+        id_estado: i32,
+        latitud: Option<Decimal>,
+        longitud: Option<Decimal>,
+    },
+    /// Snapshots anteriores a los centroides: `[id_municipio, id_estado]`
+    Anterior(i32, i32),
+}
+
+impl From<CodigoPostalSnapshot> for CodigoPostalMemoria {
+    fn from(snapshot: CodigoPostalSnapshot) -> Self {
+        match snapshot {
+            CodigoPostalSnapshot::Actual {
+                id_municipio,
+                id_estado,
+                latitud,
+                longitud,
+            } => CodigoPostalMemoria {
+                id_municipio,
+                id_estado,
+                latitud,
+                longitud,
+            },
+            CodigoPostalSnapshot::Anterior(id_municipio, id_estado) => CodigoPostalMemoria {
+                id_municipio,
+                id_estado,
+                latitud: None,
+                longitud: None,
+            },
+        }
+    }
+}
+
+impl CatalogosMemoria {
+    /// Construye los índices desde los CSV de `catalogos_path`, después de validarlos.
+    ///
+    /// ## Errores
+    /// Retorna [`UbicacionError::CatalogosInvalidos`] con el reporte de validación si hay renglones inválidos,
+    /// o un error con el archivo y la línea del renglón que no se pudo leer.
+    ///
+    /// ## Ejemplo
+    /// ```rust
+    /// let catalogos = CatalogosMemoria::desde_csv("./catalogos")?;
+    /// ```
+    pub fn desde_csv(catalogos_path: &str) -> Result<Self, UbicacionError> {
+        validar(catalogos_path)?;
+
+        let mut catalogos = CatalogosMemoria::default();
+        for registro in registros::<Estado>(catalogos_path, "cat_estados")? {
+            let (_, estado) = registro?;
+            catalogos.estados.insert(estado.id_estado, estado.estado);
+        }
+        for registro in registros::<Municipio>(catalogos_path, "cat_municipios")? {
+            let (_, municipio) = registro?;
+            catalogos
+                .municipios
+                .insert(municipio.id_municipio, municipio.municipio);
+            catalogos
+                .estados_municipios
+                .insert(municipio.id_municipio, municipio.id_estado);
+        }
+        let coordenadas_cps = coordenadas(catalogos_path, "coordenadas_codigos_postales")?;
+        for registro in registros::<CodigoPostal>(catalogos_path, "cat_codigos_postales")? {
+            let (_, codigo_postal) = registro?;
+            let (latitud, longitud) = coordenadas_cps.get(&codigo_postal.cp).copied().unzip();
+            catalogos.codigos_postales.insert(
+                codigo_postal.cp,
+                CodigoPostalMemoria {
+                    id_municipio: codigo_postal.id_municipio,
+                    id_estado: codigo_postal.id_estado,
+                    latitud,
+                    longitud,
+                },
+            );
+        }
+        let coordenadas_localidades = coordenadas(catalogos_path, "coordenadas_localidades")?;
+        for registro in registros::<Localidad>(catalogos_path, "cat_localidades")? {
+            let (_, localidad) = registro?;
+            let (latitud, longitud) = coordenadas_localidades
+                .get(&localidad.id_localidad)
+                .copied()
+                .unzip();
+            catalogos
+                .localidades
+                .entry(localidad.cp)
+                .or_default()
+                .push(LocalidadCP {
+                    id: localidad.id_localidad,
+                    value: localidad.localidad,
+                    latitud,
+                    longitud,
+                });
+        }
+        // Mismo orden que la consulta a la BD
+        for localidades in catalogos.localidades.values_mut() {
+            localidades.sort_by_key(|l| l.id);
+        }
+        Ok(catalogos)
+    }
+
+    /// Lee un snapshot JSON generado con [`CatalogosMemoria::guardar_snapshot`].
+    ///
+    /// ## Errores
+    /// Retorna [`UbicacionError::Archivo`] si el archivo no existe o no es un snapshot válido.
+    pub fn desde_snapshot(ruta: &str) -> Result<Self, UbicacionError> {
+        let archivo = BufReader::new(File::open(ruta)?);
+        let mut catalogos: Self = serde_json::from_reader(archivo).map_err(io::Error::from)?;
+        // Los snapshots anteriores no incluyen el estado de cada municipio; se toma de sus CPs
+        if catalogos.estados_municipios.is_empty() {
+            for codigo_postal in catalogos.codigos_postales.values() {
+                catalogos
+                    .estados_municipios
+                    .entry(codigo_postal.id_municipio)
+                    .or_insert(codigo_postal.id_estado);
+            }
+        }
+        Ok(catalogos)
+    }
+
+    /// Escribe los índices como snapshot JSON, para arrancar sin leer ni validar los CSV.
+    ///
+    /// ## Errores
+    /// Retorna [`UbicacionError::Archivo`] si no se pudo escribir el archivo.
+    pub fn guardar_snapshot(&self, ruta: &str) -> Result<(), UbicacionError> {
+        let archivo = BufWriter::new(File::create(ruta)?);
+        Ok(serde_json::to_writer(archivo, self).map_err(io::Error::from)?)
+    }
+
+    /// Número de códigos postales en el catálogo.
+    pub fn codigos_postales(&self) -> usize {
+        self.codigos_postales.len()
+    }
+
+    /// Centroides de los códigos postales y localidades con coordenadas, con el estado y
+    /// municipio del código postal. Se omiten los CPs cuyo estado o municipio no existe.
+    fn centroides(&self) -> Vec<Centroide> {
+        let estado_municipio = |cp: i32| {
+            let codigo_postal = self.codigos_postales.get(&cp)?;
+            Some((
+                CatalogoIdCadena {
+                    id: codigo_postal.id_estado,
+                    value: self.estados.get(&codigo_postal.id_estado)?.clone(),
+                },
+                CatalogoIdCadena {
+                    id: codigo_postal.id_municipio,
+                    value: self.municipios.get(&codigo_postal.id_municipio)?.clone(),
+                },
+            ))
+        };
+        let cps = self
+            .codigos_postales
+            .iter()
+            .filter_map(|(&cp, codigo_postal)| {
+                let (estado, municipio) = estado_municipio(cp)?;
+                Some(Centroide {
+                    cp,
+                    estado,
+                    municipio,
+                    localidad: None,
+                    latitud: codigo_postal.latitud?,
+                    longitud: codigo_postal.longitud?,
+                })
+            });
+        let localidades = self.localidades.iter().flat_map(|(&cp, localidades)| {
+            localidades.iter().filter_map(move |localidad| {
+                let (estado, municipio) = estado_municipio(cp)?;
+                Some(Centroide {
+                    cp,
+                    estado,
+                    municipio,
+                    localidad: Some(CatalogoIdCadena {
+                        id: localidad.id,
+                        value: localidad.value.clone(),
+                    }),
+                    latitud: localidad.latitud?,
+                    longitud: localidad.longitud?,
+                })
+            })
+        });
+        cps.chain(localidades).collect()
+    }
+
+    fn buscar_cp(&self, cp: i32) -> Result<CPResponse, UbicacionError> {
+        let Some(codigo_postal) = self.codigos_postales.get(&cp) else {
+            return Err(UbicacionError::CodigoPostalNoEncontrado);
+        };
+
+        // Estado y municipio son los del CP, no los de sus localidades
+        let estado = self
+            .estados
+            .get(&codigo_postal.id_estado)
+            .ok_or(UbicacionError::CatalogoInconsistente("estados"))?;
+        let municipio = self
+            .municipios
+            .get(&codigo_postal.id_municipio)
+            .ok_or(UbicacionError::CatalogoInconsistente("municipios"))?;
+
+        Ok(CPResponse {
+            estado: CatalogoIdCadena {
+                id: codigo_postal.id_estado,
+                value: estado.clone(),
+            },
+            municipio: CatalogoIdCadena {
+                id: codigo_postal.id_municipio,
+                value: municipio.clone(),
+            },
+            localidades: self.localidades.get(&cp).cloned().unwrap_or_default(),
+            latitud: codigo_postal.latitud,
+            longitud: codigo_postal.longitud,
+        })
+    }
+
+    fn buscar_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<MunicipioEstado>, UbicacionError> {
+        let (Some(municipio), Some(&id_estado)) = (
+            self.municipios.get(&id_municipio),
+            self.estados_municipios.get(&id_municipio),
+        ) else {
+            return Ok(None);
+        };
+        let estado = self
+            .estados
+            .get(&id_estado)
+            .ok_or(UbicacionError::CatalogoInconsistente("estados"))?;
+
+        Ok(Some(MunicipioEstado {
+            estado: CatalogoIdCadena {
+                id: id_estado,
+                value: estado.clone(),
+            },
+            municipio: CatalogoIdCadena {
+                id: id_municipio,
+                value: municipio.clone(),
+            },
+        }))
+    }
+
+    fn municipios_estado(&self, id_estado: i32) -> Option<MunicipiosEstado> {
+        let estado = self.estados.get(&id_estado)?;
+        let mut municipios: Vec<CatalogoIdCadena> = self
+            .estados_municipios
+            .iter()
+            .filter(|(_, &estado)| estado == id_estado)
+            .filter_map(|(&id, _)| {
+                Some(CatalogoIdCadena {
+                    id,
+                    value: self.municipios.get(&id)?.clone(),
+                })
+            })
+            .collect();
+        municipios.sort_by_key(|m| m.id);
+
+        Some(MunicipiosEstado {
+            estado: CatalogoIdCadena {
+                id: id_estado,
+                value: estado.clone(),
+            },
+            municipios,
+        })
+    }
+
+    fn codigos_postales_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<CodigosPostalesMunicipio>, UbicacionError> {
+        let Some(MunicipioEstado { estado, municipio }) = self.buscar_municipio(id_municipio)?
+        else {
+            return Ok(None);
+        };
+        let mut codigos_postales: Vec<CentroideCodigoPostal> = self
+            .codigos_postales
+            .iter()
+            .filter(|(_, codigo_postal)| codigo_postal.id_municipio == id_municipio)
+            .map(|(&cp, codigo_postal)| CentroideCodigoPostal {
+                cp,
+                latitud: codigo_postal.latitud,
+                longitud: codigo_postal.longitud,
+            })
+            .collect();
+        codigos_postales.sort_by_key(|c| c.cp);
+
+        Ok(Some(CodigosPostalesMunicipio {
+            estado,
+            municipio,
+            codigos_postales,
+        }))
+    }
+}
+
+/// Repositorio que responde desde [`CatalogosMemoria`].
+/// Una recarga construye índices nuevos y los reemplaza sólo si la carga terminó bien.
+pub struct RepositorioMemoria {
+    catalogos: RwLock<Arc<CatalogosMemoria>>,
+}
+
+impl RepositorioMemoria {
+    /// Crea el repositorio con los índices ya cargados y sin zonas.
+    pub fn new(catalogos: CatalogosMemoria) -> Self {
+        RepositorioMemoria {
+            catalogos: RwLock::new(Arc::new(catalogos)),
+        }
+    }
+
+    /// Índices vigentes; se clona el `Arc` para no retener el candado durante la consulta.
+    fn catalogos(&self) -> Arc<CatalogosMemoria> {
+        self.catalogos.read().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl RepositorioUbicacion for RepositorioMemoria {
+    async fn buscar_cp(&self, cp: i32) -> Result<CPResponse, UbicacionError> {
+        self.catalogos().buscar_cp(cp)
+    }
+
+    async fn buscar_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<MunicipioEstado>, UbicacionError> {
+        self.catalogos().buscar_municipio(id_municipio)
+    }
+
+    async fn municipios_estado(
+        &self,
+        id_estado: i32,
+    ) -> Result<Option<MunicipiosEstado>, UbicacionError> {
+        Ok(self.catalogos().municipios_estado(id_estado))
+    }
+
+    async fn codigos_postales_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<CodigosPostalesMunicipio>, UbicacionError> {
+        self.catalogos().codigos_postales_municipio(id_municipio)
+    }
+
+    async fn centroides_codigos_postales(
+        &self,
+        cps: &[i32],
+    ) -> Result<Vec<CentroideCodigoPostal>, UbicacionError> {
+        let catalogos = self.catalogos();
+        Ok(cps
+            .iter()
+            .filter_map(|cp| {
+                catalogos
+                    .codigos_postales
+                    .get(cp)
+                    .map(|codigo_postal| CentroideCodigoPostal {
+                        cp: *cp,
+                        latitud: codigo_postal.latitud,
+                        longitud: codigo_postal.longitud,
+                    })
+            })
+            .collect())
+    }
+
+    async fn recargar(&self, catalogos_path: &str) -> Result<(), UbicacionError> {
+        // Leer y validar los CSV bloquea, por lo que se hace fuera del worker
+        let catalogos_path = catalogos_path.to_string();
+        let nuevos = web::block(move || CatalogosMemoria::desde_csv(&catalogos_path))
+            .await
+            .map_err(|e| io::Error::other(e.to_string()))??;
+        *self.catalogos.write().unwrap() = Arc::new(nuevos);
+        Ok(())
+    }
+
+    async fn centroides(&self) -> Result<Vec<Centroide>, UbicacionError> {
+        Ok(self.catalogos().centroides())
+    }
+
+    async fn miembros_faltantes(
+        &self,
+        payload: &ZonaPayload,
+    ) -> Result<MiembrosFaltantes, UbicacionError> {
+        let catalogos = self.catalogos();
+        let existe_cp = |cp: &i32| catalogos.codigos_postales.contains_key(cp);
+        Ok(MiembrosFaltantes {
+            codigos_postales: payload
+                .codigos_postales
+                .iter()
+                .copied()
+                .filter(|cp| !existe_cp(cp))
+                .collect(),
+            rangos: payload
+                .rangos
+                .iter()
+                .copied()
+                .filter(|rango| !(rango.desde..=rango.hasta).any(|cp| existe_cp(&cp)))
+                .collect(),
+            municipios: payload
+                .municipios
+                .iter()
+                .copied()
+                .filter(|id_municipio| !catalogos.municipios.contains_key(id_municipio))
+                .collect(),
+        })
+    }
+
+    fn zonas_persistentes(&self) -> bool {
+        false
+    }
+
+    async fn zonas(&self) -> Result<Vec<Zona>, UbicacionError> {
+        Ok(Vec::new())
+    }
+
+    async fn zona(&self, _id_zona: i32) -> Result<Option<Zona>, UbicacionError> {
+        Ok(None)
+    }
+
+    async fn crear_zona(&self, _payload: ZonaPayload) -> Result<Zona, UbicacionError> {
+        Err(UbicacionError::ZonasNoPersistentes)
+    }
+
+    async fn actualizar_zona(
+        &self,
+        _id_zona: i32,
+        _payload: ZonaPayload,
+    ) -> Result<Option<Zona>, UbicacionError> {
+        Err(UbicacionError::ZonasNoPersistentes)
+    }
+
+    async fn eliminar_zona(&self, _id_zona: i32) -> Result<bool, UbicacionError> {
+        Err(UbicacionError::ZonasNoPersistentes)
+    }
+
+    async fn zonas_cp(&self, cp: i32) -> Result<Vec<ZonaCP>, UbicacionError> {
+        if !self.catalogos().codigos_postales.contains_key(&cp) {
+            return Err(UbicacionError::CodigoPostalNoEncontrado);
+        }
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::CATALOGOS_PRUEBA;
+
+    /// Ruta temporal para un snapshot de prueba.
+    fn ruta_snapshot(nombre: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("snapshot_{}_{}.json", nombre, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[actix_web::test]
+    async fn lee_snapshot_guardado() {
+        let ruta = ruta_snapshot("actual");
+        CatalogosMemoria::desde_csv(CATALOGOS_PRUEBA)
+            .unwrap()
+            .guardar_snapshot(&ruta)
+            .unwrap();
+        let catalogos = CatalogosMemoria::desde_snapshot(&ruta);
+        std::fs::remove_file(&ruta).unwrap();
+
+        let repositorio = RepositorioMemoria::new(catalogos.unwrap());
+        let respuesta = repositorio.buscar_cp(6600).await.unwrap();
+        assert_eq!(respuesta.latitud, Some(Decimal::new(19_427_000, 6)));
+        assert_eq!(respuesta.localidades[0].value, "JUAREZ");
+    }
+
+    /// Los snapshots anteriores a los centroides guardan cada CP como `[id_municipio, id_estado]`
+    /// y sus localidades sin coordenadas.
+    #[actix_web::test]
+    async fn lee_snapshot_sin_centroides() {
+        let ruta = ruta_snapshot("anterior");
+        std::fs::write(
+            &ruta,
+            r#"{
+                "estados": {"9": "CIUDAD DE MEXICO"},
+                "municipios": {"6": "CUAUHTEMOC"},
+                "codigos_postales": {"6600": [6, 9]},
+                "localidades": {"6600": [{"id": 3, "value": "JUAREZ"}]}
+            }"#,
+        )
+        .unwrap();
+        let catalogos = CatalogosMemoria::desde_snapshot(&ruta);
+        std::fs::remove_file(&ruta).unwrap();
+
+        let repositorio = RepositorioMemoria::new(catalogos.unwrap());
+        let respuesta = repositorio.buscar_cp(6600).await.unwrap();
+        assert_eq!(respuesta.estado.value, "CIUDAD DE MEXICO");
+        assert_eq!(respuesta.municipio.value, "CUAUHTEMOC");
+        assert_eq!(respuesta.latitud, None);
+        assert_eq!(respuesta.localidades[0].latitud, None);
+        // El estado de cada municipio se toma de sus CPs
+        assert!(repositorio.buscar_municipio(6).await.unwrap().is_some());
+    }
+}
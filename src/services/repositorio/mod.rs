@@ -0,0 +1,153 @@
+//! # Repositorios de catálogos de ubicación
+//! Los controladores consultan los catálogos a través de [`RepositorioUbicacion`], por lo que
+//! no dependen de dónde estén guardados. Hay dos implementaciones, elegidas con `FUENTE_DATOS`:
+//! - [`base_datos::RepositorioBaseDatos`]: consulta las tablas de la BD (por defecto)
+//! - [`memoria::RepositorioMemoria`]: índices en memoria construidos desde los CSV o un snapshot
+//!
+//! También guarda las zonas de entrega, que se definen con la API y no vienen en los catálogos.
+use crate::{
+    services::indice_geografico::Centroide,
+    types::{
+        catalogos::{
+            CPResponse, CentroideCodigoPostal, CodigosPostalesMunicipio, MunicipioEstado,
+            MunicipiosEstado,
+        },
+        errores::UbicacionError,
+        zonas::{MiembrosFaltantes, Zona, ZonaCP, ZonaPayload},
+    },
+};
+use async_trait::async_trait;
+
+pub mod memoria;
+pub mod base_datos;
+
+/// Operaciones sobre los catálogos que necesitan los endpoints.
+/// Se comparte entre workers como `web::Data<dyn RepositorioUbicacion>`.
+#[async_trait]
+pub trait RepositorioUbicacion: Send + Sync {
+    /// Devuelve el estado y municipio de un código postal (según `cat_codigos_postales`)
+    /// y sus localidades ordenadas por id, que pueden ser ninguna.
+    ///
+    /// ## Errores
+    /// - `CodigoPostalNoEncontrado`: El CP no está en el catálogo de códigos postales
+    /// - `CatalogoInconsistente`: No existe el estado o municipio del CP
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn buscar_cp(&self, cp: i32) -> Result<CPResponse, UbicacionError>;
+
+    /// Devuelve el municipio con su estado, o `None` si el municipio no está en el catálogo.
+    ///
+    /// ## Errores
+    /// - `CatalogoInconsistente`: No existe el estado del municipio
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn buscar_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<MunicipioEstado>, UbicacionError>;
+
+    /// Devuelve el estado con sus municipios ordenados por id, o `None` si el estado no está
+    /// en el catálogo.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn municipios_estado(
+        &self,
+        id_estado: i32,
+    ) -> Result<Option<MunicipiosEstado>, UbicacionError>;
+
+    /// Devuelve el municipio con su estado y sus códigos postales (según `cat_codigos_postales`)
+    /// ordenados, con su centroide, o `None` si el municipio no está en el catálogo.
+    ///
+    /// ## Errores
+    /// - `CatalogoInconsistente`: No existe el estado del municipio
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn codigos_postales_municipio(
+        &self,
+        id_municipio: i32,
+    ) -> Result<Option<CodigosPostalesMunicipio>, UbicacionError>;
+
+    /// Devuelve el centroide de cada código postal de `cps` que está en el catálogo, en una sola
+    /// consulta. Los que no están en el catálogo no aparecen; el orden no está garantizado.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn centroides_codigos_postales(
+        &self,
+        cps: &[i32],
+    ) -> Result<Vec<CentroideCodigoPostal>, UbicacionError>;
+
+    /// Vacía los catálogos y los vuelve a cargar desde los CSV de `catalogos_path`.
+    /// Si la carga falla se conservan los catálogos anteriores.
+    ///
+    /// ## Errores
+    /// - `CatalogosInvalidos`: Reporte de validación de los CSV
+    /// - `RenglonCatalogo`: Archivo y línea del renglón que no se pudo cargar
+    async fn recargar(&self, catalogos_path: &str) -> Result<(), UbicacionError>;
+
+    /// Devuelve los centroides de todos los códigos postales y localidades que tienen
+    /// coordenadas, para construir el índice de geocodificación inversa.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn centroides(&self) -> Result<Vec<Centroide>, UbicacionError>;
+
+    /// Devuelve los códigos postales, rangos y municipios de `payload` que no están en los
+    /// catálogos, con una consulta por tipo de miembro.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn miembros_faltantes(
+        &self,
+        payload: &ZonaPayload,
+    ) -> Result<MiembrosFaltantes, UbicacionError>;
+
+    /// Indica si las zonas se guardan de forma persistente. Sin BD no se pueden crear,
+    /// reemplazar ni eliminar zonas, pues se perderían al reiniciar el servicio.
+    fn zonas_persistentes(&self) -> bool;
+
+    /// Devuelve las zonas de entrega con sus miembros, ordenadas por id.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn zonas(&self) -> Result<Vec<Zona>, UbicacionError>;
+
+    /// Devuelve la zona con sus miembros, o `None` si no existe.
+    ///
+    /// ## Errores
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn zona(&self, id_zona: i32) -> Result<Option<Zona>, UbicacionError>;
+
+    /// Crea una zona con los datos de `payload`, ya validados, y la devuelve con su id.
+    ///
+    /// ## Errores
+    /// - `ZonaDuplicada`: Ya existe una zona con el mismo nombre (según `clave_zona`)
+    /// - `ZonasNoPersistentes`: Sin BD
+    /// - `BaseDatos`: Error inesperado al modificar la BD (sólo con BD)
+    async fn crear_zona(&self, payload: ZonaPayload) -> Result<Zona, UbicacionError>;
+
+    /// Reemplaza el nombre, la descripción y los miembros de una zona, o devuelve `None` si no existe.
+    ///
+    /// ## Errores
+    /// - `ZonaDuplicada`: Otra zona tiene el mismo nombre (según `clave_zona`)
+    /// - `ZonasNoPersistentes`: Sin BD
+    /// - `BaseDatos`: Error inesperado al modificar la BD (sólo con BD)
+    async fn actualizar_zona(
+        &self,
+        id_zona: i32,
+        payload: ZonaPayload,
+    ) -> Result<Option<Zona>, UbicacionError>;
+
+    /// Elimina una zona con sus miembros. Devuelve `false` si no existía.
+    ///
+    /// ## Errores
+    /// - `ZonasNoPersistentes`: Sin BD
+    /// - `BaseDatos`: Error inesperado al modificar la BD (sólo con BD)
+    async fn eliminar_zona(&self, id_zona: i32) -> Result<bool, UbicacionError>;
+
+    /// Devuelve las zonas que contienen un código postal, ordenadas por id. El municipio del CP
+    /// es el de `cat_codigos_postales`.
+    ///
+    /// ## Errores
+    /// - `CodigoPostalNoEncontrado`: El CP no está en el catálogo de códigos postales
+    /// - `BaseDatos`: Error inesperado al consultar la BD (sólo con BD)
+    async fn zonas_cp(&self, cp: i32) -> Result<Vec<ZonaCP>, UbicacionError>;
+}
@@ -0,0 +1,558 @@
+//! # Validación de los CSV de catálogos
+//! En este módulo se incluye la validación renglón por renglón de los CSV de catálogos,
+//! que se ejecuta antes de insertar cualquier registro:
+//! - Número de columnas y encabezados esperados
+//! - Ids numéricos y sin duplicados (incluidos los códigos postales)
+//! - Referencias a estados, municipios y códigos postales existentes en los mismos CSV
+//! - Longitud de los nombres según las columnas de la migración (`string_len(50/100)`)
+//! - Nombres de estado sin repetir y de municipio sin repetir dentro de su estado (índices únicos)
+//! - Claves de estado y códigos postales dentro de su rango oficial (`CHECK` de la migración)
+//! - Coordenadas (opcionales) decimales, dentro de rango y de códigos postales o localidades existentes
+use crate::{
+    types::catalogos::{ErrorRenglon, ReporteValidacion},
+    utils::conversores::{existe_catalogo, leer_catalogo, linea_renglon},
+};
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Longitud máxima de `cat_estados.estado` y `cat_municipios.municipio`
+const LONGITUD_ESTADO_MUNICIPIO: usize = 50;
+/// Longitud máxima de `cat_localidades.localidad`
+const LONGITUD_LOCALIDAD: usize = 100;
+/// Claves INEGI de los estados
+const RANGO_ID_ESTADO: RangeInclusive<i32> = 1..=32;
+/// Códigos postales válidos (01000 a 99999)
+const RANGO_CP: RangeInclusive<i32> = 1000..=99999;
+/// Valor absoluto máximo de la latitud y de la longitud, en grados
+const LATITUD_MAXIMA: i64 = 90;
+const LONGITUD_MAXIMA: i64 = 180;
+
+/// Valida los cuatro CSV de catálogos de `catalogos_path` y devuelve todos los renglones inválidos.
+/// Si existen, valida también los CSV de coordenadas de códigos postales y de localidades.
+/// La validación no se detiene en el primer error, para poder corregir el archivo de una sola vez.
+///
+/// ## Argumentos
+/// * `catalogos_path` - Ruta base donde se encuentran los archivos CSV de los catálogos.
+///
+/// ## Retorno
+/// - [`ReporteValidacion`]: Lista de errores con archivo y línea; vacía si los catálogos son válidos
+///
+/// ## Ejemplo
+/// ```rust
+/// let reporte = validar_catalogos("./catalogos");
+/// if !reporte.es_valido() { println!("{}", reporte); }
+/// ```
+pub fn validar_catalogos(catalogos_path: &str) -> ReporteValidacion {
+    let mut v = Validador::default();
+
+    // Estados
+    let mut estados = HashSet::new();
+    let mut nombres_estados = HashSet::new();
+    v.recorrer(
+        catalogos_path,
+        "cat_estados",
+        &["id_estado", "estado"],
+        |v, linea, campos| {
+            if let Some(id) = v.entero("cat_estados", linea, "id_estado", campos[0]) {
+                if !estados.insert(id) {
+                    v.error("cat_estados", linea, format!("id_estado {} duplicado", id));
+                }
+                v.rango("cat_estados", linea, "id_estado", id, RANGO_ID_ESTADO);
+            }
+            v.nombre(
+                "cat_estados",
+                linea,
+                "estado",
+                campos[1],
+                LONGITUD_ESTADO_MUNICIPIO,
+            );
+            if !nombres_estados.insert(campos[1].to_string()) {
+                v.error(
+                    "cat_estados",
+                    linea,
+                    format!("estado {:?} duplicado", campos[1]),
+                );
+            }
+        },
+    );
+
+    // Municipios
+    let mut municipios = HashSet::new();
+    let mut nombres_municipios = HashSet::new();
+    v.recorrer(
+        catalogos_path,
+        "cat_municipios",
+        &["id_municipio", "municipio", "id_estado"],
+        |v, linea, campos| {
+            if let Some(id) = v.entero("cat_municipios", linea, "id_municipio", campos[0]) {
+                if !municipios.insert(id) {
+                    v.error(
+                        "cat_municipios",
+                        linea,
+                        format!("id_municipio {} duplicado", id),
+                    );
+                }
+            }
+            v.nombre(
+                "cat_municipios",
+                linea,
+                "municipio",
+                campos[1],
+                LONGITUD_ESTADO_MUNICIPIO,
+            );
+            v.referencia("cat_municipios", linea, "id_estado", campos[2], &estados);
+            if !nombres_municipios.insert((campos[2].to_string(), campos[1].to_string())) {
+                v.error(
+                    "cat_municipios",
+                    linea,
+                    format!(
+                        "municipio {:?} duplicado en el estado {}",
+                        campos[1], campos[2]
+                    ),
+                );
+            }
+        },
+    );
+
+    // Códigos postales
+    let mut cps = HashSet::new();
+    v.recorrer(
+        catalogos_path,
+        "cat_codigos_postales",
+        &["cp", "id_municipio", "id_estado"],
+        |v, linea, campos| {
+            if let Some(cp) = v.entero("cat_codigos_postales", linea, "cp", campos[0]) {
+                if !cps.insert(cp) {
+                    v.error(
+                        "cat_codigos_postales",
+                        linea,
+                        format!("código postal {} duplicado", cp),
+                    );
+                }
+                v.rango("cat_codigos_postales", linea, "cp", cp, RANGO_CP);
+            }
+            v.referencia(
+                "cat_codigos_postales",
+                linea,
+                "id_municipio",
+                campos[1],
+                &municipios,
+            );
+            v.referencia(
+                "cat_codigos_postales",
+                linea,
+                "id_estado",
+                campos[2],
+                &estados,
+            );
+        },
+    );
+
+    // Localidades
+    let mut localidades = HashSet::new();
+    v.recorrer(
+        catalogos_path,
+        "cat_localidades",
+        &[
+            "id_localidad",
+            "localidad",
+            "cp",
+            "id_municipio",
+            "id_estado",
+        ],
+        |v, linea, campos| {
+            if let Some(id) = v.entero("cat_localidades", linea, "id_localidad", campos[0]) {
+                if !localidades.insert(id) {
+                    v.error(
+                        "cat_localidades",
+                        linea,
+                        format!("id_localidad {} duplicado", id),
+                    );
+                }
+            }
+            v.nombre(
+                "cat_localidades",
+                linea,
+                "localidad",
+                campos[1],
+                LONGITUD_LOCALIDAD,
+            );
+            v.referencia("cat_localidades", linea, "cp", campos[2], &cps);
+            v.referencia(
+                "cat_localidades",
+                linea,
+                "id_municipio",
+                campos[3],
+                &municipios,
+            );
+            v.referencia("cat_localidades", linea, "id_estado", campos[4], &estados);
+        },
+    );
+
+    // Coordenadas (opcionales)
+    for (catalogo, columna, existentes) in [
+        ("coordenadas_codigos_postales", "cp", &cps),
+        ("coordenadas_localidades", "id_localidad", &localidades),
+    ] {
+        if !existe_catalogo(catalogos_path, catalogo) {
+            continue;
+        }
+        let mut ids = HashSet::new();
+        v.recorrer(
+            catalogos_path,
+            catalogo,
+            &[columna, "latitud", "longitud"],
+            |v, linea, campos| {
+                if let Some(id) = v.entero(catalogo, linea, columna, campos[0]) {
+                    if !ids.insert(id) {
+                        v.error(catalogo, linea, format!("{} {} duplicado", columna, id));
+                    } else if !existentes.contains(&id) {
+                        v.error(catalogo, linea, format!("{} {} no existe", columna, id));
+                    }
+                }
+                v.coordenada(catalogo, linea, "latitud", campos[1], LATITUD_MAXIMA);
+                v.coordenada(catalogo, linea, "longitud", campos[2], LONGITUD_MAXIMA);
+            },
+        );
+    }
+
+    ReporteValidacion { errores: v.errores }
+}
+
+/// Acumula los errores encontrados durante la validación.
+#[derive(Default)]
+struct Validador {
+    errores: Vec<ErrorRenglon>,
+}
+
+impl Validador {
+    fn error(&mut self, catalogo: &str, linea: u64, mensaje: String) {
+        self.errores.push(ErrorRenglon {
+            archivo: format!("{}.csv", catalogo),
+            linea,
+            mensaje,
+        });
+    }
+
+    /// Recorre el CSV de `catalogo` y llama a `validar` con el número de línea y los valores
+    /// de `columnas` (en ese orden) de cada renglón con el número correcto de columnas.
+    fn recorrer<F>(&mut self, ruta: &str, catalogo: &str, columnas: &[&str], mut validar: F)
+    where
+        F: FnMut(&mut Self, u64, &[&str]),
+    {
+        let mut rdr = match leer_catalogo(ruta, catalogo) {
+            Ok(rdr) => rdr,
+            Err(e) => {
+                self.error(catalogo, 0, e.to_string());
+                return;
+            }
+        };
+        let encabezados = match rdr.headers() {
+            Ok(encabezados) => encabezados.clone(),
+            Err(e) => {
+                self.error(catalogo, 1, e.to_string());
+                return;
+            }
+        };
+        let mut indices = Vec::with_capacity(columnas.len());
+        for columna in columnas {
+            match encabezados.iter().position(|h| h == *columna) {
+                Some(i) => indices.push(i),
+                None => self.error(catalogo, 1, format!("falta la columna {}", columna)),
+            }
+        }
+        if indices.len() != columnas.len() {
+            return;
+        }
+
+        for (indice, resultado) in rdr.records().enumerate() {
+            let linea = linea_renglon(indice);
+            match resultado {
+                Ok(renglon) => {
+                    let valores: Vec<&str> = indices
+                        .iter()
+                        .map(|i| renglon.get(*i).unwrap_or(""))
+                        .collect();
+                    validar(self, linea, &valores);
+                }
+                Err(e) => {
+                    let continuar = !e.is_io_error();
+                    let mensaje = match e.into_kind() {
+                        csv::ErrorKind::UnequalLengths {
+                            expected_len, len, ..
+                        } => format!(
+                            "se esperaban {} columnas y se encontraron {}",
+                            expected_len, len
+                        ),
+                        otro => format!("{:?}", otro),
+                    };
+                    self.error(catalogo, linea, mensaje);
+                    // Un error de E/S impide seguir leyendo el archivo
+                    if !continuar {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Valida que el valor sea un entero de 32 bits.
+    fn entero(&mut self, catalogo: &str, linea: u64, columna: &str, valor: &str) -> Option<i32> {
+        match valor.parse::<i32>() {
+            Ok(n) => Some(n),
+            Err(_) => {
+                self.error(
+                    catalogo,
+                    linea,
+                    format!("{} no es numérico: \"{}\"", columna, valor),
+                );
+                None
+            }
+        }
+    }
+
+    /// Valida que el valor sea un entero y que exista en `existentes`.
+    fn referencia(
+        &mut self,
+        catalogo: &str,
+        linea: u64,
+        columna: &str,
+        valor: &str,
+        existentes: &HashSet<i32>,
+    ) {
+        if let Some(id) = self.entero(catalogo, linea, columna, valor) {
+            if !existentes.contains(&id) {
+                self.error(catalogo, linea, format!("{} {} no existe", columna, id));
+            }
+        }
+    }
+
+    /// Valida que el nombre no esté vacío ni exceda la longitud de la columna.
+    fn nombre(&mut self, catalogo: &str, linea: u64, columna: &str, valor: &str, maximo: usize) {
+        let longitud = valor.chars().count();
+        if longitud == 0 {
+            self.error(catalogo, linea, format!("{} vacío", columna));
+        } else if longitud > maximo {
+            self.error(
+                catalogo,
+                linea,
+                format!("{} excede {} caracteres ({})", columna, maximo, longitud),
+            );
+        }
+    }
+
+    /// Valida que la coordenada sea un decimal entre `-maximo` y `maximo` grados.
+    fn coordenada(&mut self, catalogo: &str, linea: u64, columna: &str, valor: &str, maximo: i64) {
+        match Decimal::from_str(valor) {
+            Ok(grados) if grados.abs() <= Decimal::from(maximo) => {}
+            Ok(grados) => self.error(
+                catalogo,
+                linea,
+                format!(
+                    "{} {} fuera de rango (-{} a {})",
+                    columna, grados, maximo, maximo
+                ),
+            ),
+            Err(_) => self.error(
+                catalogo,
+                linea,
+                format!("{} no es decimal: \"{}\"", columna, valor),
+            ),
+        }
+    }
+
+    /// Valida que la clave esté dentro de su rango oficial.
+    fn rango(
+        &mut self,
+        catalogo: &str,
+        linea: u64,
+        columna: &str,
+        valor: i32,
+        rango: RangeInclusive<i32>,
+    ) {
+        if !rango.contains(&valor) {
+            self.error(
+                catalogo,
+                linea,
+                format!(
+                    "{} {} fuera de rango ({} a {})",
+                    columna,
+                    valor,
+                    rango.start(),
+                    rango.end()
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruebas::CATALOGOS_PRUEBA;
+    use std::path::{Path, PathBuf};
+
+    /// Copia los catálogos de prueba a un directorio temporal y reemplaza `archivo` con
+    /// `contenido`.
+    fn catalogos_con(nombre: &str, archivo: &str, contenido: &str) -> PathBuf {
+        let directorio =
+            std::env::temp_dir().join(format!("validacion_{}_{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(&directorio).unwrap();
+        for entrada in std::fs::read_dir(CATALOGOS_PRUEBA).unwrap() {
+            let entrada = entrada.unwrap();
+            std::fs::copy(entrada.path(), directorio.join(entrada.file_name())).unwrap();
+        }
+        std::fs::write(directorio.join(archivo), contenido).unwrap();
+        directorio
+    }
+
+    /// Archivo, línea y mensaje de cada error al validar `directorio`.
+    fn errores(directorio: &Path) -> Vec<(String, u64, String)> {
+        validar_catalogos(directorio.to_str().unwrap())
+            .errores
+            .into_iter()
+            .map(|e| (e.archivo, e.linea, e.mensaje))
+            .collect()
+    }
+
+    fn error(archivo: &str, linea: u64, mensaje: &str) -> (String, u64, String) {
+        (archivo.to_string(), linea, mensaje.to_string())
+    }
+
+    #[test]
+    fn catalogos_validos_sin_errores() {
+        let reporte = validar_catalogos(CATALOGOS_PRUEBA);
+        assert!(reporte.es_valido(), "{}", reporte);
+    }
+
+    #[test]
+    fn reporta_numero_de_columnas() {
+        let directorio = catalogos_con(
+            "columnas",
+            "cat_estados.csv",
+            "id_estado,estado\n9,CIUDAD DE MEXICO,SOBRA\n14\n",
+        );
+        // La línea 1 es el encabezado; JALISCO ya no existe para sus municipios y CPs
+        let errores = errores(&directorio);
+        assert_eq!(
+            &errores[..2],
+            [
+                error(
+                    "cat_estados.csv",
+                    2,
+                    "se esperaban 2 columnas y se encontraron 3"
+                ),
+                error(
+                    "cat_estados.csv",
+                    3,
+                    "se esperaban 2 columnas y se encontraron 1"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reporta_ids_no_numericos() {
+        let directorio = catalogos_con(
+            "no_numericos",
+            "cat_municipios.csv",
+            "municipio,id_estado,id_municipio\nBENITO JUAREZ,9,3\nCOYOACAN,9,4\n\
+             CUAUHTEMOC,CDMX,6\nGUADALAJARA,14,5x5\n",
+        );
+        assert_eq!(
+            errores(&directorio),
+            [
+                error(
+                    "cat_municipios.csv",
+                    4,
+                    "id_estado no es numérico: \"CDMX\""
+                ),
+                error(
+                    "cat_municipios.csv",
+                    5,
+                    "id_municipio no es numérico: \"5x5\""
+                ),
+                error("cat_codigos_postales.csv", 6, "id_municipio 525 no existe"),
+                error("cat_localidades.csv", 6, "id_municipio 525 no existe"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reporta_municipios_sin_estado() {
+        let directorio = catalogos_con(
+            "municipio_sin_estado",
+            "cat_municipios.csv",
+            "municipio,id_estado,id_municipio\nBENITO JUAREZ,9,3\nCOYOACAN,9,4\n\
+             CUAUHTEMOC,9,6\nGUADALAJARA,15,525\n",
+        );
+        assert_eq!(
+            errores(&directorio),
+            [error("cat_municipios.csv", 5, "id_estado 15 no existe")]
+        );
+    }
+
+    #[test]
+    fn reporta_codigos_postales_sin_municipio() {
+        let directorio = catalogos_con(
+            "cp_sin_municipio",
+            "cat_codigos_postales.csv",
+            "cp,id_estado,id_municipio\n3100,9,3\n4000,9,4\n6600,9,6\n6700,9,7\n44100,14,525\n",
+        );
+        assert_eq!(
+            errores(&directorio),
+            [error(
+                "cat_codigos_postales.csv",
+                5,
+                "id_municipio 7 no existe"
+            )]
+        );
+    }
+
+    #[test]
+    fn reporta_codigos_postales_duplicados() {
+        let directorio = catalogos_con(
+            "cp_duplicado",
+            "cat_codigos_postales.csv",
+            "cp,id_estado,id_municipio\n3100,9,3\n4000,9,4\n6600,9,6\n6700,9,6\n44100,14,525\n\
+             6600,9,6\n",
+        );
+        assert_eq!(
+            errores(&directorio),
+            [error(
+                "cat_codigos_postales.csv",
+                7,
+                "código postal 6600 duplicado"
+            )]
+        );
+    }
+
+    #[test]
+    fn reporta_nombres_vacios_o_largos() {
+        let directorio = catalogos_con(
+            "nombres",
+            "cat_localidades.csv",
+            &format!(
+                "id_localidad,localidad,cp,id_municipio,id_estado\n1,,3100,3,9\n2,{},4000,4,9\n\
+                 3,{},6600,6,9\n4,ROMA NORTE,6700,6,9\n5,GUADALAJARA CENTRO,44100,525,14\n",
+                "Ñ".repeat(LONGITUD_LOCALIDAD + 1),
+                "Ñ".repeat(LONGITUD_LOCALIDAD),
+            ),
+        );
+        // La longitud se cuenta en caracteres, no en bytes
+        assert_eq!(
+            errores(&directorio),
+            [
+                error("cat_localidades.csv", 2, "localidad vacío"),
+                error(
+                    "cat_localidades.csv",
+                    3,
+                    "localidad excede 100 caracteres (101)"
+                ),
+            ]
+        );
+    }
+}
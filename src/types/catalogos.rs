@@ -8,8 +8,9 @@
 //!
 //! Los DTOs de la BD se encuentran en src/entities y son generados automaticamente por Sea ORM.
 use crate::utils::conversores::CatalogoIdCadena;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 /// Payload usado para solicitar información de un código postal específico.
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
@@ -20,10 +21,323 @@ pub struct CPPayload {
 
 /// Respuesta que contiene los datos asociados a un código postal,
 /// incluyendo estado, municipio y localidades correspondientes.
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct CPResponse {
     pub estado: CatalogoIdCadena,
     pub municipio: CatalogoIdCadena,
-    pub localidades: Vec<CatalogoIdCadena>,
+    pub localidades: Vec<LocalidadCP>,
+    /// Latitud del centroide del código postal (grados decimales, `null` si no se conoce)
+    pub latitud: Option<Decimal>,
+    /// Longitud del centroide del código postal (grados decimales, `null` si no se conoce)
+    pub longitud: Option<Decimal>,
+}
+
+/// Localidad de un código postal, con el centroide de la localidad.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, PartialEq)]
+pub struct LocalidadCP {
+    pub id: i32,
+    pub value: String,
+    /// Latitud del centroide (grados decimales, `null` si no se conoce)
+    pub latitud: Option<Decimal>,
+    /// Longitud del centroide (grados decimales, `null` si no se conoce)
+    pub longitud: Option<Decimal>,
+}
+
+/// Parámetros de la geocodificación inversa.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct GeocodificacionQuery {
+    /// Latitud en grados decimales (WGS84)
+    #[param(example = 19.3467)]
+    pub lat: f64,
+    /// Longitud en grados decimales (WGS84)
+    #[param(example = -99.1903)]
+    pub lon: f64,
+    /// Número de resultados (por defecto 5, máximo 50)
+    pub n: Option<usize>,
+}
+
+/// Código postal o localidad cercana a unas coordenadas.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ResultadoGeocodificacion {
+    pub cp: i32,
+    /// Localidad (colonia) cuyo centroide es el cercano; `null` si es el centroide del código postal
+    pub localidad: Option<CatalogoIdCadena>,
+    /// Latitud del centroide
+    pub latitud: Decimal,
+    /// Longitud del centroide
+    pub longitud: Decimal,
+    /// Distancia sobre la superficie desde las coordenadas consultadas
+    pub distancia_metros: f64,
+}
+
+/// Coordenadas que se resuelven al municipio que las contiene.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct CoordenadasQuery {
+    /// Latitud en grados decimales (WGS84)
+    #[param(example = 19.3467)]
+    pub lat: f64,
+    /// Longitud en grados decimales (WGS84)
+    #[param(example = -99.1903)]
+    pub lon: f64,
+}
+
+/// Estado y municipio de un municipio del catálogo.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct MunicipioEstado {
+    pub estado: CatalogoIdCadena,
+    pub municipio: CatalogoIdCadena,
+}
+
+/// Estado con sus municipios, ordenados por id.
+#[derive(Debug, Clone)]
+pub struct MunicipiosEstado {
+    pub estado: CatalogoIdCadena,
+    pub municipios: Vec<CatalogoIdCadena>,
+}
+
+/// Municipio con su estado y sus códigos postales, ordenados.
+#[derive(Debug, Clone)]
+pub struct CodigosPostalesMunicipio {
+    pub estado: CatalogoIdCadena,
+    pub municipio: CatalogoIdCadena,
+    pub codigos_postales: Vec<CentroideCodigoPostal>,
 }
 
+/// Código postal con su centroide (`None` si no se conoce).
+#[derive(Debug, Clone)]
+pub struct CentroideCodigoPostal {
+    pub cp: i32,
+    pub latitud: Option<Decimal>,
+    pub longitud: Option<Decimal>,
+}
+
+/// Municipio cuyo polígono contiene unas coordenadas.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MunicipioCoordenadasResponse {
+    pub estado: CatalogoIdCadena,
+    pub municipio: CatalogoIdCadena,
+    /// Código postal cuyo polígono contiene las coordenadas; `null` si no se cargaron
+    /// límites de códigos postales o ninguno las contiene
+    pub cp: Option<i32>,
+}
+
+/// Criterio para ordenar los códigos postales cercanos.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrdenCercanos {
+    /// Del más cercano al más lejano
+    #[default]
+    Distancia,
+    /// Por código postal
+    Cp,
+}
+
+/// Parámetros de la búsqueda de códigos postales dentro de un radio.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct CercanosQuery {
+    /// Código postal central
+    #[param(example = 6600)]
+    pub cp: i32,
+    /// Radio de búsqueda en kilómetros (mayor que 0 y a lo más 100)
+    #[param(example = 5)]
+    pub radio_km: f64,
+    /// Criterio de orden (por defecto `distancia`)
+    pub orden: Option<OrdenCercanos>,
+    /// Si es `true` se invierte el orden
+    pub descendente: Option<bool>,
+    /// Página a devolver, desde 1 (por defecto 1)
+    pub pagina: Option<usize>,
+    /// Resultados por página (por defecto 20, máximo 100)
+    pub por_pagina: Option<usize>,
+}
+
+/// Código postal dentro del radio de búsqueda.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct CodigoPostalCercano {
+    pub cp: i32,
+    pub estado: CatalogoIdCadena,
+    pub municipio: CatalogoIdCadena,
+    /// Latitud del centroide
+    pub latitud: Decimal,
+    /// Longitud del centroide
+    pub longitud: Decimal,
+    /// Distancia sobre la superficie desde el centroide del código postal central
+    pub distancia_metros: f64,
+}
+
+/// Página de códigos postales dentro de un radio.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct CercanosResponse {
+    /// Código postal central
+    pub cp: i32,
+    pub radio_km: f64,
+    /// Códigos postales dentro del radio, en todas las páginas
+    pub total: usize,
+    pub pagina: usize,
+    pub por_pagina: usize,
+    pub resultados: Vec<CodigoPostalCercano>,
+}
+
+/// Códigos postales de origen y destino para calcular su distancia.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct DistanciaQuery {
+    /// Código postal de origen
+    #[param(example = 6600)]
+    pub origen: i32,
+    /// Código postal de destino
+    #[param(example = 44100)]
+    pub destino: i32,
+}
+
+/// Distancia de círculo máximo entre los centroides de dos códigos postales.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct DistanciaResponse {
+    pub origen: i32,
+    pub destino: i32,
+    pub distancia_metros: f64,
+}
+
+/// Payload para calcular las distancias entre varios orígenes y destinos.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MatrizDistanciasPayload {
+    /// Códigos postales de origen (renglones de la matriz)
+    #[schema(example = json!([6600, 1000]))]
+    pub origenes: Vec<i32>,
+    /// Códigos postales de destino (columnas de la matriz)
+    #[schema(example = json!([44100, 64000, 1000]))]
+    pub destinos: Vec<i32>,
+}
+
+/// Matriz de distancias entre códigos postales.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MatrizDistanciasResponse {
+    pub origenes: Vec<i32>,
+    pub destinos: Vec<i32>,
+    /// Distancia en metros de cada origen (renglón) a cada destino (columna)
+    pub distancias_metros: Vec<Vec<f64>>,
+}
+
+/// Tipo de cambio detectado al comparar un catálogo nuevo contra el cargado en la BD.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TipoCambio {
+    /// El registro existe en el archivo nuevo pero no en la BD
+    Agregado,
+    /// El registro existe en la BD pero no en el archivo nuevo
+    Eliminado,
+    /// El registro conserva su id pero cambió de nombre
+    Renombrado,
+    /// El registro conserva su id pero cambió de municipio (CPs) o de código postal (localidades)
+    Reasignado,
+}
+
+/// Renglón del reporte de diferencias entre catálogos.
+/// Es plano para poder escribirse tanto en JSON como en CSV.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct CambioCatalogo {
+    /// Nombre del catálogo (`cat_municipios`, `cat_codigos_postales` o `cat_localidades`)
+    pub catalogo: String,
+    pub cambio: TipoCambio,
+    /// Id del registro (código postal en el caso de `cat_codigos_postales`)
+    pub id: i32,
+    /// Valor cargado actualmente en la BD
+    pub anterior: Option<String>,
+    /// Valor contenido en el archivo nuevo
+    pub nuevo: Option<String>,
+}
+
+/// Reporte de diferencias entre los catálogos cargados y los de un nuevo directorio de CSV.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ReporteDiferencias {
+    pub agregados: usize,
+    pub eliminados: usize,
+    pub renombrados: usize,
+    pub reasignados: usize,
+    pub cambios: Vec<CambioCatalogo>,
+}
+
+/// Estado de un trabajo de recarga de catálogos.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EstadoRecarga {
+    EnProceso,
+    Completada,
+    Fallida,
+}
+
+/// Trabajo de recarga de catálogos ejecutado en segundo plano.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct TrabajoRecarga {
+    /// Id para consultar el estado del trabajo
+    pub job_id: u64,
+    pub estado: EstadoRecarga,
+    /// Directorio desde donde se leen los CSV, o `archivos subidos`
+    pub origen: String,
+    /// Descripción del error si la recarga falló
+    pub error: Option<String>,
+    /// Pasos posteriores al reemplazo de los catálogos que fallaron; la recarga se completa
+    /// con los catálogos nuevos y esos pasos conservan sus datos anteriores
+    pub advertencias: Vec<String>,
+}
+
+/// Parámetros opcionales para recargar catálogos desde un directorio del servidor.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct RecargaQuery {
+    /// Subdirectorio de `CATALOGOS_DIR` con los CSV, relativo y sin `..`; si se omite se usa el mismo `CATALOGOS_DIR`
+    pub directorio: Option<String>,
+}
+
+/// Estado de la caché de búsquedas por código postal.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct EstadisticasCache {
+    /// Número máximo de códigos postales en caché (0 si está desactivada)
+    pub capacidad: usize,
+    /// Códigos postales actualmente en caché
+    pub entradas: usize,
+    /// Segundos que una búsqueda permanece en caché
+    pub ttl_segundos: u64,
+    /// Búsquedas respondidas desde la caché
+    pub aciertos: u64,
+    /// Búsquedas que consultaron la base de datos
+    pub fallos: u64,
+}
+
+/// Payload usado para consultar el estado de un trabajo de recarga.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TrabajoPayload {
+    /// Id devuelto al iniciar la recarga
+    pub job_id: u64,
+}
+
+/// Renglón inválido encontrado al validar los CSV de catálogos.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ErrorRenglon {
+    /// Archivo CSV, p. ej. `cat_localidades.csv`
+    pub archivo: String,
+    /// Línea del archivo (la 1 es el encabezado; 0 si el error es del archivo completo)
+    pub linea: u64,
+    pub mensaje: String,
+}
+
+/// Resultado de validar los CSV de catálogos antes de insertarlos.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct ReporteValidacion {
+    pub errores: Vec<ErrorRenglon>,
+}
+
+impl ReporteValidacion {
+    /// Indica si no se encontró ningún renglón inválido
+    pub fn es_valido(&self) -> bool {
+        self.errores.is_empty()
+    }
+}
+
+impl std::fmt::Display for ReporteValidacion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Catálogos inválidos ({} errores)", self.errores.len())?;
+        for e in &self.errores {
+            write!(f, "\n{}, línea {}: {}", e.archivo, e.linea, e.mensaje)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,536 @@
+//! # Errores del servicio de ubicación
+//!
+//! Enum [`UbicacionError`] con todos los errores que pueden devolver los controladores,
+//! servicios y utilidades del servicio. Implementa [`ResponseError`], por lo que las rutas
+//! sólo devuelven `Result<_, UbicacionError>` y Actix construye la respuesta con el estado HTTP
+//! correspondiente.
+//!
+//! Los errores se responden como `application/problem+json` ([RFC 7807](https://www.rfc-editor.org/rfc/rfc7807))
+//! con la estructura [`ProblemDetails`], en español o inglés según `Accept-Language`. Cada variante tiene un código estable
+//! ([`UbicacionError::codigo`]), para que los clientes no dependan del texto del mensaje.
+use crate::{
+    middleware::peticion::{id_peticion_actual, idioma_actual},
+    types::{catalogos::ReporteValidacion, zonas::CausaZonaInvalida},
+    utils::{conversores::ErrorLecturaCatalogo, idioma::Idioma},
+};
+use actix_web::{
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
+use std::{fmt, io};
+use utoipa::ToSchema;
+
+/// Tipo de contenido de las respuestas de error
+pub const TIPO_CONTENIDO_PROBLEMA: &str = "application/problem+json";
+
+/// Cuerpo de las respuestas de error (RFC 7807, problem details).
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI que identifica el tipo de error (`urn:ubicacion:error:{codigo}`)
+    #[serde(rename = "type")]
+    #[schema(example = "urn:ubicacion:error:codigo_postal_no_encontrado")]
+    pub tipo: String,
+    /// Resumen del tipo de error; no cambia entre ocurrencias
+    #[schema(example = "Código postal no encontrado")]
+    pub title: String,
+    /// Estado HTTP de la respuesta
+    #[schema(example = 404)]
+    pub status: u16,
+    /// Descripción de esta ocurrencia del error
+    #[schema(example = "Código postal no encontrado")]
+    pub detail: String,
+    /// Código estable del error
+    #[schema(example = "codigo_postal_no_encontrado")]
+    pub codigo: String,
+    /// Id de la petición, igual al encabezado `X-Request-Id`
+    #[schema(example = "0b6f1c1e-8c1a-4d5e-9a67-2f0e0c3c6a51")]
+    pub request_id: Option<String>,
+}
+
+impl ProblemDetails {
+    /// Construye el cuerpo del error con el id de la petición en curso.
+    pub fn new(codigo: &str, title: &str, status: u16, detail: String) -> Self {
+        ProblemDetails {
+            tipo: format!("urn:ubicacion:error:{}", codigo),
+            title: title.to_string(),
+            status,
+            detail,
+            codigo: codigo.to_string(),
+            request_id: id_peticion_actual(),
+        }
+    }
+
+    /// Respuesta HTTP con el cuerpo en `application/problem+json`.
+    pub fn respuesta(&self) -> HttpResponse {
+        HttpResponse::build(
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        )
+        .content_type(TIPO_CONTENIDO_PROBLEMA)
+        .insert_header((header::CONTENT_LANGUAGE, idioma_actual().etiqueta()))
+        .json(self)
+    }
+}
+
+/// Errores del servicio de ubicación.
+#[derive(Debug)]
+pub enum UbicacionError {
+    /// El código postal no está entre 1000 y 99999
+    CodigoPostalInvalido,
+    /// El código postal no tiene localidades registradas
+    CodigoPostalNoEncontrado,
+    /// El estado no está en el catálogo
+    EstadoNoEncontrado,
+    /// El municipio no está en el catálogo
+    MunicipioNoEncontrado,
+    /// La latitud no está entre -90 y 90 o la longitud entre -180 y 180
+    CoordenadasInvalidas,
+    /// El código postal existe pero no tiene centroide cargado
+    CodigoPostalSinCoordenadas(i32),
+    /// La matriz de distancias no tiene orígenes o destinos, o tiene más del máximo indicado
+    MatrizDistanciasInvalida(usize),
+    /// El radio de búsqueda no es mayor que 0 o excede el máximo indicado (en km)
+    RadioInvalido(u32),
+    /// No se cargaron límites de municipios (`limites_municipios.geojson`)
+    LimitesNoCargados,
+    /// Ningún polígono de municipio contiene las coordenadas
+    CoordenadasSinMunicipio,
+    /// Falta un registro que debería existir según otro catálogo (p. ej. el estado de una localidad).
+    /// Contiene el nombre del catálogo (`estados`, `municipios`)
+    CatalogoInconsistente(&'static str),
+    /// La BD no tiene aplicadas todas las migraciones. Contiene las pendientes
+    EsquemaDesactualizado(Vec<String>),
+    /// Error al consultar o modificar la base de datos
+    BaseDatos(DbErr),
+    /// No se pudo abrir el archivo de un catálogo
+    LecturaCatalogo(ErrorLecturaCatalogo),
+    /// Los CSV no pasaron la validación previa a la carga
+    CatalogosInvalidos(ReporteValidacion),
+    /// Un archivo GeoJSON de límites no es válido. Contiene el archivo (sin extensión) y la causa
+    LimitesInvalidos {
+        archivo: &'static str,
+        causa: String,
+    },
+    /// Falló la lectura o inserción de uno o varios renglones de un CSV
+    RenglonCatalogo {
+        catalogo: &'static str,
+        primera_linea: u64,
+        ultima_linea: u64,
+        causa: String,
+    },
+    /// Se subió un archivo que no es un catálogo
+    ArchivoCatalogoInvalido(String),
+    /// El cuerpo multipart no se pudo leer
+    MultipartInvalido(String),
+    /// El directorio pedido para la recarga no es un subdirectorio de `CATALOGOS_DIR`
+    DirectorioRecargaInvalido(String),
+    /// Ya hay otra recarga de catálogos en proceso
+    RecargaEnProceso,
+    /// No existe el trabajo de recarga consultado
+    TrabajoNoEncontrado,
+    /// La tarea de una recarga terminó con un pánico. Contiene la causa
+    RecargaInterrumpida(String),
+    /// No existe la zona consultada
+    ZonaNoEncontrada,
+    /// Ya existe otra zona con el nombre indicado
+    ZonaDuplicada(String),
+    /// Los datos de la zona no son válidos. Contiene la causa
+    ZonaInvalida(CausaZonaInvalida),
+    /// Con `FUENTE_DATOS=memoria` las zonas no se pueden modificar: se perderían al reiniciar
+    ZonasNoPersistentes,
+    /// Error de E/S al escribir archivos (catálogos subidos, reportes)
+    Archivo(io::Error),
+    /// La petición no incluye token
+    TokenRequerido,
+    /// La firma o vigencia del token no es válida
+    TokenInvalido,
+    /// El token no tiene scope `admin`
+    ScopeAdminRequerido,
+}
+
+impl UbicacionError {
+    /// Construye el error de un renglón inválido, indicando catálogo y línea.
+    pub fn renglon(catalogo: &'static str, linea: u64, causa: impl fmt::Display) -> Self {
+        UbicacionError::RenglonCatalogo {
+            catalogo,
+            primera_linea: linea,
+            ultima_linea: linea,
+            causa: causa.to_string(),
+        }
+    }
+
+    /// Código estable del error, pensado para que lo interpreten los clientes.
+    pub fn codigo(&self) -> &'static str {
+        match self {
+            UbicacionError::CodigoPostalInvalido => "codigo_postal_invalido",
+            UbicacionError::CodigoPostalNoEncontrado => "codigo_postal_no_encontrado",
+            UbicacionError::EstadoNoEncontrado => "estado_no_encontrado",
+            UbicacionError::MunicipioNoEncontrado => "municipio_no_encontrado",
+            UbicacionError::CoordenadasInvalidas => "coordenadas_invalidas",
+            UbicacionError::CodigoPostalSinCoordenadas(_) => "codigo_postal_sin_coordenadas",
+            UbicacionError::MatrizDistanciasInvalida(_) => "matriz_distancias_invalida",
+            UbicacionError::RadioInvalido(_) => "radio_invalido",
+            UbicacionError::LimitesNoCargados => "limites_no_cargados",
+            UbicacionError::CoordenadasSinMunicipio => "coordenadas_sin_municipio",
+            UbicacionError::CatalogoInconsistente(_) => "catalogo_inconsistente",
+            UbicacionError::EsquemaDesactualizado(_) => "esquema_desactualizado",
+            UbicacionError::BaseDatos(_) => "error_base_datos",
+            UbicacionError::LecturaCatalogo(_) => "lectura_catalogo",
+            UbicacionError::CatalogosInvalidos(_) => "catalogos_invalidos",
+            UbicacionError::LimitesInvalidos { .. } => "limites_invalidos",
+            UbicacionError::RenglonCatalogo { .. } => "renglon_catalogo",
+            UbicacionError::ArchivoCatalogoInvalido(_) => "archivo_catalogo_invalido",
+            UbicacionError::MultipartInvalido(_) => "multipart_invalido",
+            UbicacionError::DirectorioRecargaInvalido(_) => "directorio_recarga_invalido",
+            UbicacionError::RecargaEnProceso => "recarga_en_proceso",
+            UbicacionError::TrabajoNoEncontrado => "trabajo_no_encontrado",
+            UbicacionError::RecargaInterrumpida(_) => "recarga_interrumpida",
+            UbicacionError::ZonaNoEncontrada => "zona_no_encontrada",
+            UbicacionError::ZonaDuplicada(_) => "zona_duplicada",
+            UbicacionError::ZonaInvalida(_) => "zona_invalida",
+            UbicacionError::ZonasNoPersistentes => "zonas_no_persistentes",
+            UbicacionError::Archivo(_) => "error_archivo",
+            UbicacionError::TokenRequerido => "token_requerido",
+            UbicacionError::TokenInvalido => "token_invalido",
+            UbicacionError::ScopeAdminRequerido => "scope_admin_requerido",
+        }
+    }
+
+    /// Resumen del tipo de error (`title` del problem details); no incluye datos de la ocurrencia.
+    pub fn titulo(&self, idioma: Idioma) -> &'static str {
+        match self {
+            UbicacionError::CodigoPostalInvalido => idioma.elegir(
+                "Formato de código postal inválido",
+                "Invalid postal code format",
+            ),
+            UbicacionError::CodigoPostalNoEncontrado => {
+                idioma.elegir("Código postal no encontrado", "Postal code not found")
+            }
+            UbicacionError::EstadoNoEncontrado => {
+                idioma.elegir("Estado no encontrado", "State not found")
+            }
+            UbicacionError::MunicipioNoEncontrado => {
+                idioma.elegir("Municipio no encontrado", "Municipality not found")
+            }
+            UbicacionError::CoordenadasInvalidas => {
+                idioma.elegir("Coordenadas inválidas", "Invalid coordinates")
+            }
+            UbicacionError::CodigoPostalSinCoordenadas(_) => idioma.elegir(
+                "Código postal sin coordenadas",
+                "Postal code without coordinates",
+            ),
+            UbicacionError::MatrizDistanciasInvalida(_) => {
+                idioma.elegir("Matriz de distancias inválida", "Invalid distance matrix")
+            }
+            UbicacionError::RadioInvalido(_) => {
+                idioma.elegir("Radio de búsqueda inválido", "Invalid search radius")
+            }
+            UbicacionError::LimitesNoCargados => idioma.elegir(
+                "Límites de municipios no disponibles",
+                "Municipality boundaries not available",
+            ),
+            UbicacionError::CoordenadasSinMunicipio => idioma.elegir(
+                "Coordenadas fuera de los municipios",
+                "Coordinates outside every municipality",
+            ),
+            UbicacionError::CatalogoInconsistente(_) => {
+                idioma.elegir("Catálogos inconsistentes", "Inconsistent catalogs")
+            }
+            UbicacionError::EsquemaDesactualizado(_) => {
+                idioma.elegir("Esquema desactualizado", "Outdated schema")
+            }
+            UbicacionError::BaseDatos(_) => {
+                idioma.elegir("Error en la base de datos", "Database error")
+            }
+            UbicacionError::LecturaCatalogo(_) => {
+                idioma.elegir("No se pudo leer el catálogo", "Could not read catalog")
+            }
+            UbicacionError::CatalogosInvalidos(_) => {
+                idioma.elegir("Catálogos inválidos", "Invalid catalogs")
+            }
+            UbicacionError::LimitesInvalidos { .. } => {
+                idioma.elegir("Límites geográficos inválidos", "Invalid boundaries")
+            }
+            UbicacionError::RenglonCatalogo { .. } => {
+                idioma.elegir("Renglón de catálogo inválido", "Invalid catalog row")
+            }
+            UbicacionError::ArchivoCatalogoInvalido(_) => {
+                idioma.elegir("Archivo de catálogo inválido", "Invalid catalog file")
+            }
+            UbicacionError::MultipartInvalido(_) => {
+                idioma.elegir("Cuerpo multipart inválido", "Invalid multipart body")
+            }
+            UbicacionError::DirectorioRecargaInvalido(_) => {
+                idioma.elegir("Directorio de recarga inválido", "Invalid reload directory")
+            }
+            UbicacionError::RecargaEnProceso => {
+                idioma.elegir("Recarga en proceso", "Reload in progress")
+            }
+            UbicacionError::TrabajoNoEncontrado => {
+                idioma.elegir("Trabajo de recarga no encontrado", "Reload job not found")
+            }
+            UbicacionError::RecargaInterrumpida(_) => {
+                idioma.elegir("Recarga interrumpida", "Reload interrupted")
+            }
+            UbicacionError::ZonaNoEncontrada => {
+                idioma.elegir("Zona no encontrada", "Zone not found")
+            }
+            UbicacionError::ZonaDuplicada(_) => idioma.elegir("Zona duplicada", "Duplicate zone"),
+            UbicacionError::ZonaInvalida(_) => idioma.elegir("Zona inválida", "Invalid zone"),
+            UbicacionError::ZonasNoPersistentes => {
+                idioma.elegir("Zonas no persistentes", "Zones not persistent")
+            }
+            UbicacionError::Archivo(_) => idioma.elegir("Error de archivo", "File error"),
+            UbicacionError::TokenRequerido => idioma.elegir("Token requerido", "Token required"),
+            UbicacionError::TokenInvalido => idioma.elegir("Token inválido", "Invalid token"),
+            UbicacionError::ScopeAdminRequerido => {
+                idioma.elegir("Scope admin requerido", "Admin scope required")
+            }
+        }
+    }
+
+    /// Descripción de esta ocurrencia del error (`detail` del problem details).
+    /// Los datos de la ocurrencia (rutas, causas, mensajes de validación) no se traducen.
+    pub fn detalle(&self, idioma: Idioma) -> String {
+        match self {
+            UbicacionError::CodigoPostalInvalido
+            | UbicacionError::CodigoPostalNoEncontrado
+            | UbicacionError::EstadoNoEncontrado
+            | UbicacionError::MunicipioNoEncontrado
+            | UbicacionError::TrabajoNoEncontrado
+            | UbicacionError::ZonaNoEncontrada
+            | UbicacionError::TokenInvalido => self.titulo(idioma).to_string(),
+            UbicacionError::CoordenadasInvalidas => idioma
+                .elegir(
+                    "La latitud debe estar entre -90 y 90 y la longitud entre -180 y 180",
+                    "Latitude must be between -90 and 90 and longitude between -180 and 180",
+                )
+                .to_string(),
+            UbicacionError::CodigoPostalSinCoordenadas(cp) => match idioma {
+                Idioma::Es => format!("El código postal {:05} no tiene coordenadas", cp),
+                Idioma::En => format!("Postal code {:05} has no coordinates", cp),
+            },
+            UbicacionError::MatrizDistanciasInvalida(maximo) => match idioma {
+                Idioma::Es => format!(
+                    "origenes y destinos deben tener entre 1 y {} códigos postales",
+                    maximo
+                ),
+                Idioma::En => format!(
+                    "origenes and destinos must have between 1 and {} postal codes",
+                    maximo
+                ),
+            },
+            UbicacionError::RadioInvalido(maximo) => match idioma {
+                Idioma::Es => format!("radio_km debe ser mayor que 0 y a lo más {}", maximo),
+                Idioma::En => format!("radio_km must be greater than 0 and at most {}", maximo),
+            },
+            UbicacionError::LimitesNoCargados => idioma
+                .elegir(
+                    "No se cargaron límites de municipios en el servicio",
+                    "No municipality boundaries were loaded in the service",
+                )
+                .to_string(),
+            UbicacionError::CoordenadasSinMunicipio => idioma
+                .elegir(
+                    "Las coordenadas no están dentro de ningún municipio",
+                    "The coordinates are not inside any municipality",
+                )
+                .to_string(),
+            UbicacionError::CatalogoInconsistente(catalogo) => match idioma {
+                Idioma::Es => format!("Error en el catalogo de {} en la base de datos", catalogo),
+                Idioma::En => format!("Inconsistent {} catalog in the database", catalogo),
+            },
+            UbicacionError::EsquemaDesactualizado(pendientes) => match idioma {
+                Idioma::Es => format!(
+                    "La base de datos tiene migraciones pendientes: {}",
+                    pendientes.join(", ")
+                ),
+                Idioma::En => format!(
+                    "The database has pending migrations: {}",
+                    pendientes.join(", ")
+                ),
+            },
+            UbicacionError::BaseDatos(e) => format!("{}: {}", self.titulo(idioma), e),
+            UbicacionError::LecturaCatalogo(e) => {
+                format!("{} {}: {}", self.titulo(idioma), e.ruta.display(), e.causa)
+            }
+            UbicacionError::CatalogosInvalidos(reporte) => {
+                let mut detalle = format!(
+                    "{} ({} {})",
+                    self.titulo(idioma),
+                    reporte.errores.len(),
+                    idioma.elegir("errores", "errors")
+                );
+                for e in &reporte.errores {
+                    detalle.push_str(&format!(
+                        "\n{}, {} {}: {}",
+                        e.archivo,
+                        idioma.elegir("línea", "line"),
+                        e.linea,
+                        e.mensaje
+                    ));
+                }
+                detalle
+            }
+            UbicacionError::LimitesInvalidos { archivo, causa } => {
+                format!("{}.geojson: {}", archivo, causa)
+            }
+            UbicacionError::RenglonCatalogo {
+                catalogo,
+                primera_linea,
+                ultima_linea,
+                causa,
+            } if primera_linea == ultima_linea => format!(
+                "{}.csv, {} {}: {}",
+                catalogo,
+                idioma.elegir("línea", "line"),
+                primera_linea,
+                causa
+            ),
+            UbicacionError::RenglonCatalogo {
+                catalogo,
+                primera_linea,
+                ultima_linea,
+                causa,
+            } => format!(
+                "{}.csv, {} {} {} {}: {}",
+                catalogo,
+                idioma.elegir("líneas", "lines"),
+                primera_linea,
+                idioma.elegir("a", "to"),
+                ultima_linea,
+                causa
+            ),
+            UbicacionError::ArchivoCatalogoInvalido(nombre) => {
+                format!("{}: {}", self.titulo(idioma), nombre)
+            }
+            UbicacionError::MultipartInvalido(e) => format!("{}: {}", self.titulo(idioma), e),
+            UbicacionError::DirectorioRecargaInvalido(directorio) => match idioma {
+                Idioma::Es => format!(
+                    "{} no es un subdirectorio existente de CATALOGOS_DIR",
+                    directorio
+                ),
+                Idioma::En => format!(
+                    "{} is not an existing subdirectory of CATALOGOS_DIR",
+                    directorio
+                ),
+            },
+            UbicacionError::RecargaEnProceso => idioma
+                .elegir(
+                    "Ya hay una recarga de catálogos en proceso",
+                    "A catalog reload is already in progress",
+                )
+                .to_string(),
+            UbicacionError::ZonaDuplicada(nombre) => match idioma {
+                Idioma::Es => format!("Ya existe una zona con el nombre {}", nombre),
+                Idioma::En => format!("A zone named {} already exists", nombre),
+            },
+            UbicacionError::ZonaInvalida(causa) => {
+                format!("{}: {}", self.titulo(idioma), causa.describir(idioma))
+            }
+            UbicacionError::ZonasNoPersistentes => idioma
+                .elegir(
+                    "Con FUENTE_DATOS=memoria las zonas no se pueden modificar porque se perderían al reiniciar el servicio",
+                    "Zones cannot be modified with FUENTE_DATOS=memoria because they would be lost when the service restarts",
+                )
+                .to_string(),
+            UbicacionError::Archivo(e) => format!("{}: {}", self.titulo(idioma), e),
+            UbicacionError::RecargaInterrumpida(causa) => {
+                format!("{}: {}", self.titulo(idioma), causa)
+            }
+            UbicacionError::TokenRequerido => idioma
+                .elegir("Se requiere un token", "Token is required")
+                .to_string(),
+            UbicacionError::ScopeAdminRequerido => idioma
+                .elegir("El token no tiene scope admin", "Admin scope required")
+                .to_string(),
+        }
+    }
+}
+
+/// Se muestra en español; se usa en bitácoras, en el estado de las recargas y en la línea de comandos.
+impl fmt::Display for UbicacionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.detalle(Idioma::Es))
+    }
+}
+
+impl std::error::Error for UbicacionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UbicacionError::BaseDatos(e) => Some(e),
+            UbicacionError::LecturaCatalogo(e) => Some(e),
+            UbicacionError::Archivo(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl ResponseError for UbicacionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            UbicacionError::CodigoPostalInvalido
+            | UbicacionError::CoordenadasInvalidas
+            | UbicacionError::MatrizDistanciasInvalida(_)
+            | UbicacionError::RadioInvalido(_)
+            | UbicacionError::ArchivoCatalogoInvalido(_)
+            | UbicacionError::MultipartInvalido(_)
+            | UbicacionError::DirectorioRecargaInvalido(_)
+            | UbicacionError::TokenRequerido => StatusCode::BAD_REQUEST,
+            UbicacionError::TokenInvalido => StatusCode::UNAUTHORIZED,
+            UbicacionError::ScopeAdminRequerido => StatusCode::FORBIDDEN,
+            UbicacionError::CodigoPostalNoEncontrado
+            | UbicacionError::EstadoNoEncontrado
+            | UbicacionError::MunicipioNoEncontrado
+            | UbicacionError::CoordenadasSinMunicipio
+            | UbicacionError::TrabajoNoEncontrado
+            | UbicacionError::ZonaNoEncontrada => StatusCode::NOT_FOUND,
+            UbicacionError::RecargaEnProceso | UbicacionError::ZonaDuplicada(_) => {
+                StatusCode::CONFLICT
+            }
+            UbicacionError::CatalogosInvalidos(_)
+            | UbicacionError::ZonaInvalida(_)
+            | UbicacionError::LimitesInvalidos { .. }
+            | UbicacionError::CodigoPostalSinCoordenadas(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            UbicacionError::LimitesNoCargados => StatusCode::SERVICE_UNAVAILABLE,
+            UbicacionError::ZonasNoPersistentes => StatusCode::NOT_IMPLEMENTED,
+            UbicacionError::CatalogoInconsistente(_)
+            | UbicacionError::EsquemaDesactualizado(_)
+            | UbicacionError::BaseDatos(_)
+            | UbicacionError::LecturaCatalogo(_)
+            | UbicacionError::RenglonCatalogo { .. }
+            | UbicacionError::RecargaInterrumpida(_)
+            | UbicacionError::Archivo(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let idioma = idioma_actual();
+        ProblemDetails::new(
+            self.codigo(),
+            self.titulo(idioma),
+            self.status_code().as_u16(),
+            self.detalle(idioma),
+        )
+        .respuesta()
+    }
+}
+
+impl From<DbErr> for UbicacionError {
+    fn from(e: DbErr) -> Self {
+        UbicacionError::BaseDatos(e)
+    }
+}
+
+impl From<ErrorLecturaCatalogo> for UbicacionError {
+    fn from(e: ErrorLecturaCatalogo) -> Self {
+        UbicacionError::LecturaCatalogo(e)
+    }
+}
+
+impl From<io::Error> for UbicacionError {
+    fn from(e: io::Error) -> Self {
+        UbicacionError::Archivo(e)
+    }
+}
@@ -0,0 +1,182 @@
+//! # Respuestas en GeoJSON
+//!
+//! Estructuras para responder los catálogos como `FeatureCollection` de GeoJSON
+//! ([RFC 7946](https://www.rfc-editor.org/rfc/rfc7946)), que se abren directamente en QGIS o
+//! Leaflet. Las coordenadas son `[longitud, latitud]` en grados decimales (WGS84).
+//!
+//! El formato se elige con `?formato=geojson` o con el encabezado `Accept: application/geo+json`
+//! ([`Formato::elegir`]).
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+/// Tipo de contenido de las respuestas en GeoJSON
+pub const TIPO_CONTENIDO_GEOJSON: &str = "application/geo+json";
+
+/// Formato de la respuesta.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Formato {
+    /// JSON con la estructura de cada endpoint
+    #[default]
+    Json,
+    /// `FeatureCollection` de GeoJSON
+    Geojson,
+}
+
+impl Formato {
+    /// Elige el formato de la respuesta. `formato` (de la query) tiene prioridad; si no se
+    /// indica, se responde GeoJSON cuando el encabezado `Accept` incluye `application/geo+json`
+    /// con calidad (`q`) mayor que 0.
+    pub fn elegir(formato: Option<Formato>, accept: Option<&str>) -> Formato {
+        formato.unwrap_or_else(|| {
+            let pide_geojson = accept.is_some_and(|accept| {
+                accept.split(',').any(|rango| {
+                    let mut partes = rango.split(';');
+                    let tipo = partes.next().unwrap_or_default().trim();
+                    tipo.eq_ignore_ascii_case(TIPO_CONTENIDO_GEOJSON) && calidad(partes) > 0.0
+                })
+            });
+            if pide_geojson {
+                Formato::Geojson
+            } else {
+                Formato::Json
+            }
+        })
+    }
+}
+
+/// Calidad (`q`) de un rango de `Accept` a partir de sus parámetros. Es 1 si no se indica o no es
+/// un número; `q=0` excluye el tipo.
+fn calidad<'a>(parametros: impl Iterator<Item = &'a str>) -> f32 {
+    parametros
+        .filter_map(|parametro| parametro.split_once('='))
+        .find(|(nombre, _)| nombre.trim().eq_ignore_ascii_case("q"))
+        .and_then(|(_, valor)| valor.trim().parse().ok())
+        .unwrap_or(1.0)
+}
+
+/// Parámetro para elegir el formato de la respuesta.
+#[derive(Debug, Deserialize, Serialize, IntoParams)]
+pub struct FormatoQuery {
+    /// Formato de la respuesta (`json` o `geojson`). Si se omite se usa el encabezado `Accept`
+    pub formato: Option<Formato>,
+}
+
+/// Geometría de una entidad: el centroide como punto o los límites como multipolígono.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type")]
+pub enum GeometriaGeoJson {
+    /// `[longitud, latitud]`
+    Point { coordinates: [f64; 2] },
+    /// Polígonos con sus anillos de vértices `[longitud, latitud]`; el primer anillo de cada
+    /// polígono es el exterior y los demás son huecos
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<[f64; 2]>>>,
+    },
+}
+
+impl GeometriaGeoJson {
+    /// Punto del centroide, o `None` si no se conoce.
+    pub fn punto(latitud: Option<Decimal>, longitud: Option<Decimal>) -> Option<Self> {
+        Some(GeometriaGeoJson::Point {
+            coordinates: [longitud?.to_f64()?, latitud?.to_f64()?],
+        })
+    }
+}
+
+/// Entidad (`Feature`) de GeoJSON.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EntidadGeoJson {
+    /// Siempre `Feature`
+    #[serde(rename = "type")]
+    #[schema(example = "Feature")]
+    pub tipo: String,
+    /// `null` si no se conocen el centroide ni los límites
+    pub geometry: Option<GeometriaGeoJson>,
+    /// Campos del catálogo
+    #[schema(value_type = Object)]
+    pub properties: Value,
+}
+
+impl EntidadGeoJson {
+    /// Construye la entidad; `properties` debe ser un objeto.
+    pub fn new(geometry: Option<GeometriaGeoJson>, properties: Value) -> Self {
+        EntidadGeoJson {
+            tipo: "Feature".to_string(),
+            geometry,
+            properties,
+        }
+    }
+}
+
+/// Colección de entidades (`FeatureCollection`) de GeoJSON.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ColeccionGeoJson {
+    /// Siempre `FeatureCollection`
+    #[serde(rename = "type")]
+    #[schema(example = "FeatureCollection")]
+    pub tipo: String,
+    pub features: Vec<EntidadGeoJson>,
+}
+
+impl ColeccionGeoJson {
+    /// Construye la colección con sus entidades.
+    pub fn new(features: Vec<EntidadGeoJson>) -> Self {
+        ColeccionGeoJson {
+            tipo: "FeatureCollection".to_string(),
+            features,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formato_de_la_query_tiene_prioridad() {
+        assert_eq!(
+            Formato::elegir(Some(Formato::Json), Some("application/geo+json")),
+            Formato::Json
+        );
+        assert_eq!(
+            Formato::elegir(Some(Formato::Geojson), None),
+            Formato::Geojson
+        );
+    }
+
+    #[test]
+    fn elige_geojson_por_accept() {
+        assert_eq!(
+            Formato::elegir(None, Some("application/geo+json")),
+            Formato::Geojson
+        );
+        assert_eq!(
+            Formato::elegir(None, Some("application/json, Application/Geo+JSON; q=0.5")),
+            Formato::Geojson
+        );
+        assert_eq!(
+            Formato::elegir(None, Some("application/json")),
+            Formato::Json
+        );
+        assert_eq!(Formato::elegir(None, None), Formato::Json);
+    }
+
+    #[test]
+    fn accept_con_q_cero_excluye_geojson() {
+        for accept in [
+            "application/geo+json;q=0",
+            "application/json, application/geo+json; q=0.0",
+            "application/geo+json; charset=utf-8; Q=0",
+        ] {
+            assert_eq!(
+                Formato::elegir(None, Some(accept)),
+                Formato::Json,
+                "{}",
+                accept
+            );
+        }
+    }
+}
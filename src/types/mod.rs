@@ -1 +1,4 @@
 pub mod catalogos;
+pub mod errores;
+pub mod geojson;
+pub mod zonas;
@@ -0,0 +1,228 @@
+//! # Zonas de entrega
+//!
+//! Estructuras de los endpoints de zonas. Una zona (p. ej. "CDMX centro") se define con
+//! códigos postales sueltos, rangos de códigos postales y municipios; un código postal pertenece
+//! a la zona si cumple cualquiera de ellos. Los municipios abarcan los códigos postales que
+//! `cat_codigos_postales` les asigna.
+use crate::utils::idioma::Idioma;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Rango de códigos postales, inclusivo en ambos extremos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema)]
+pub struct RangoCP {
+    /// Primer código postal del rango
+    #[schema(example = 6000)]
+    pub desde: i32,
+    /// Último código postal del rango
+    #[schema(example = 6999)]
+    pub hasta: i32,
+}
+
+/// Payload para crear o reemplazar una zona. Se requiere al menos un código postal, rango o municipio.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ZonaPayload {
+    /// Nombre de la zona, único sin distinguir mayúsculas ni espacios repetidos
+    #[schema(example = "CDMX centro")]
+    pub nombre: String,
+    /// Descripción libre de la zona
+    #[schema(example = "Entrega al día siguiente")]
+    pub descripcion: Option<String>,
+    /// Códigos postales sueltos
+    #[serde(default)]
+    #[schema(example = json!([3100, 3800]))]
+    pub codigos_postales: Vec<i32>,
+    /// Rangos de códigos postales
+    #[serde(default)]
+    pub rangos: Vec<RangoCP>,
+    /// Ids de municipios; abarcan todos sus códigos postales
+    #[serde(default)]
+    #[schema(example = json!([15, 7]))]
+    pub municipios: Vec<i32>,
+}
+
+/// Clave con la que se compara el nombre de una zona para que sea único: sin espacios en los
+/// extremos, con un solo espacio entre palabras y en minúsculas ("CDMX  Centro" y "cdmx centro"
+/// tienen la misma clave).
+pub fn clave_zona(nombre: &str) -> String {
+    nombre
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Miembros de una zona que no están en los catálogos, ordenados.
+#[derive(Debug, Default, PartialEq)]
+pub struct MiembrosFaltantes {
+    /// Códigos postales que no están en `cat_codigos_postales`
+    pub codigos_postales: Vec<i32>,
+    /// Rangos que no incluyen ningún código postal de `cat_codigos_postales`
+    pub rangos: Vec<RangoCP>,
+    /// Municipios que no están en `cat_municipios`
+    pub municipios: Vec<i32>,
+}
+
+/// Motivo por el que se rechaza una zona; se describe en el idioma de la respuesta.
+#[derive(Debug, PartialEq)]
+pub enum CausaZonaInvalida {
+    /// El nombre queda vacío sin los espacios de los extremos
+    NombreVacio,
+    /// El nombre excede la longitud máxima, en caracteres
+    NombreLargo(usize),
+    /// La descripción excede la longitud máxima, en caracteres
+    DescripcionLarga(usize),
+    /// No hay códigos postales, rangos ni municipios
+    SinMiembros,
+    /// El código postal está fuera de 1000 a 99999
+    CodigoPostalInvalido(i32),
+    /// Un extremo está fuera de 1000 a 99999 o el rango empieza después de terminar
+    RangoInvalido(RangoCP),
+    /// Miembros que no están en los catálogos (al menos uno)
+    FueraDelCatalogo(MiembrosFaltantes),
+}
+
+impl CausaZonaInvalida {
+    /// Describe la causa en `idioma`. Si faltan miembros de varios tipos, une las descripciones
+    /// con "; ".
+    pub fn describir(&self, idioma: Idioma) -> String {
+        match self {
+            CausaZonaInvalida::NombreVacio => idioma
+                .elegir("el nombre no puede estar vacío", "the name cannot be empty")
+                .to_string(),
+            CausaZonaInvalida::NombreLargo(maximo) => match idioma {
+                Idioma::Es => format!("el nombre tiene más de {} caracteres", maximo),
+                Idioma::En => format!("the name is longer than {} characters", maximo),
+            },
+            CausaZonaInvalida::DescripcionLarga(maximo) => match idioma {
+                Idioma::Es => format!("la descripción tiene más de {} caracteres", maximo),
+                Idioma::En => format!("the description is longer than {} characters", maximo),
+            },
+            CausaZonaInvalida::SinMiembros => idioma
+                .elegir(
+                    "la zona no tiene códigos postales, rangos ni municipios",
+                    "the zone has no postal codes, ranges or municipalities",
+                )
+                .to_string(),
+            CausaZonaInvalida::CodigoPostalInvalido(cp) => match idioma {
+                Idioma::Es => format!("el código postal {} no es válido", cp),
+                Idioma::En => format!("postal code {} is not valid", cp),
+            },
+            CausaZonaInvalida::RangoInvalido(rango) => match idioma {
+                Idioma::Es => format!("el rango de {} a {} no es válido", rango.desde, rango.hasta),
+                Idioma::En => format!(
+                    "the range from {} to {} is not valid",
+                    rango.desde, rango.hasta
+                ),
+            },
+            CausaZonaInvalida::FueraDelCatalogo(faltantes) => {
+                let mut causas = Vec::new();
+                if !faltantes.codigos_postales.is_empty() {
+                    let cps = enumerar(&faltantes.codigos_postales, |cp| format!("{:05}", cp));
+                    causas.push(match idioma {
+                        Idioma::Es => {
+                            format!("los códigos postales {} no están en el catálogo", cps)
+                        }
+                        Idioma::En => format!("postal codes {} are not in the catalog", cps),
+                    });
+                }
+                if !faltantes.rangos.is_empty() {
+                    let rangos = enumerar(&faltantes.rangos, |rango| {
+                        format!("{:05}-{:05}", rango.desde, rango.hasta)
+                    });
+                    causas.push(match idioma {
+                        Idioma::Es => format!(
+                            "los rangos {} no incluyen códigos postales del catálogo",
+                            rangos
+                        ),
+                        Idioma::En => format!(
+                            "ranges {} do not include any postal code from the catalog",
+                            rangos
+                        ),
+                    });
+                }
+                if !faltantes.municipios.is_empty() {
+                    let municipios = enumerar(&faltantes.municipios, |id| id.to_string());
+                    causas.push(match idioma {
+                        Idioma::Es => {
+                            format!("los municipios {} no están en el catálogo", municipios)
+                        }
+                        Idioma::En => {
+                            format!("municipalities {} are not in the catalog", municipios)
+                        }
+                    });
+                }
+                causas.join("; ")
+            }
+        }
+    }
+}
+
+/// Une los valores con comas, cada uno con `formato`.
+fn enumerar<T>(valores: &[T], formato: impl Fn(&T) -> String) -> String {
+    valores.iter().map(formato).collect::<Vec<_>>().join(", ")
+}
+
+/// Zona de entrega con sus miembros, ordenados.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct Zona {
+    /// Id de la zona
+    #[schema(example = 1)]
+    pub id: i32,
+    #[schema(example = "CDMX centro")]
+    pub nombre: String,
+    #[schema(example = "Entrega al día siguiente")]
+    pub descripcion: Option<String>,
+    #[schema(example = json!([3100, 3800]))]
+    pub codigos_postales: Vec<i32>,
+    pub rangos: Vec<RangoCP>,
+    #[schema(example = json!([7, 15]))]
+    pub municipios: Vec<i32>,
+}
+
+impl Zona {
+    /// Construye la zona con los datos de un payload ya validado.
+    pub fn new(id: i32, payload: ZonaPayload) -> Self {
+        Zona {
+            id,
+            nombre: payload.nombre,
+            descripcion: payload.descripcion,
+            codigos_postales: payload.codigos_postales,
+            rangos: payload.rangos,
+            municipios: payload.municipios,
+        }
+    }
+}
+
+/// Miembro de la zona que incluye al código postal.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MotivoZona {
+    /// El código postal está en `codigos_postales`
+    CodigoPostal,
+    /// El código postal está dentro de alguno de `rangos`
+    Rango,
+    /// El municipio del código postal está en `municipios`
+    Municipio,
+}
+
+/// Zona que contiene un código postal.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ZonaCP {
+    #[schema(example = 1)]
+    pub id: i32,
+    #[schema(example = "CDMX centro")]
+    pub nombre: String,
+    #[schema(example = "Entrega al día siguiente")]
+    pub descripcion: Option<String>,
+    /// Por qué el código postal pertenece a la zona
+    pub motivos: Vec<MotivoZona>,
+}
+
+/// Zonas que contienen un código postal, ordenadas por id.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ZonasCPResponse {
+    #[schema(example = 6600)]
+    pub cp: i32,
+    pub zonas: Vec<ZonaCP>,
+}
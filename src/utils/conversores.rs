@@ -1,167 +1,242 @@
 //! # Utilidades para conversión y carga de catálogos
 //!
-//! Funciones y traits genéricos para trabajar con catálogos
-//! en la aplicación, incluyendo conversión de modelos, consultas genéricas y carga
-//! automática desde archivos CSV.
+//! Estructuras y funciones genéricas para trabajar con catálogos
+//! en la aplicación, incluyendo la estructura común `{ id, value }` y la lectura
+//! de los archivos CSV.
 //!
 //! Se utiliza principalmente desde los módulos de servicio y controladores de catálogos
 //! para:
-//! - Evitar duplicación de código al convertir modelos (`Model`) en estructuras simples.
-//! - Simplificar consultas genéricas (`find`, `find_by_id`) sobre tablas de catálogos.
+//! - Representar cualquier catálogo como `{ id, value }`.
 //! - Permitir inicializar catálogos básicos desde archivos CSV cuando la base de datos está vacía.
 //!
 //! ## Componentes
 //!
 //! - Estructura id y etiqueta ([`CatalogoIdCadena`])
-//! - Consulta un registro, devolviendo el modelo ([`registro`])
-//! - Consulta un único registro, devolviendo la estructura `{ id, value }` ([`registro_estructura`])
-use crate::entities::*;
-use actix_web::error;
-use sea_orm::{
-    DatabaseConnection, FromQueryResult, Select
-};
+//!
+//! Las consultas a la BD están en `services::repositorio::base_datos`.
+use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::fmt;
 use csv::Reader;
+use flate2::read::MultiGzDecoder;
 
 // -----------------------------------------------------------------------------
-// Conversiones de modelos a estructuras genéricas
+// Estructuras genéricas
 // -----------------------------------------------------------------------------
 
-/// Define la conversión genérica de un modelo de catálogo de base de datos
-/// (`Model`) hacia una estructura común con identificador y valor ([`CatalogoIdCadena`]).
-///
-/// Este trait es implementado por cada modelo de catálogo que contiene
-/// únicamente un campo de texto descriptivo.
-///
-/// ## Ejemplo
-/// ```rust
-/// let banco_model = cat_bancos::Model { id: 1, banco: "BBVA".to_string() };
-/// let entrada = banco_model.to_id_value();
-/// ```
-pub trait CatalogoCombo {
-    fn to_id_value(&self) -> CatalogoIdCadena;
-}
-
 /// Representa un registro leído desde un archivo CSV que contiene un id y un campo de texto.
-#[derive(Debug, Deserialize, Serialize, ToSchema, PartialEq, FromQueryResult)]
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, PartialEq, FromQueryResult)]
 pub struct CatalogoIdCadena {
     pub id: i32,
     pub value: String,
 }
 
-// Implementaciones del trait para cada catálogo con estructura simple
-// (id, cadena descriptiva)
-impl CatalogoCombo for cat_estados::Model {
-    fn to_id_value(&self) -> CatalogoIdCadena {
-        CatalogoIdCadena {
-            id: self.id,
-            value: self.estado.clone(),
-        }
+/// Extensiones con las que se busca cada catálogo, en orden de preferencia.
+/// Los archivos `.gz` y `.zst` se descomprimen al leerlos.
+const EXTENSIONES_CATALOGO: [&str; 3] = ["csv", "csv.gz", "csv.zst"];
+
+/// Error al abrir el archivo de un catálogo.
+/// Conserva la ruta del archivo y la causa para poder reportarlas.
+#[derive(Debug)]
+pub struct ErrorLecturaCatalogo {
+    pub ruta: PathBuf,
+    pub causa: io::Error,
+}
+
+impl fmt::Display for ErrorLecturaCatalogo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "No se pudo leer el catálogo {}: {}",
+            self.ruta.display(),
+            self.causa
+        )
     }
 }
-impl CatalogoCombo for cat_municipios::Model {
-    fn to_id_value(&self) -> CatalogoIdCadena {
-        CatalogoIdCadena {
-            id: self.id,
-            value: self.municipio.clone(),
-        }
+
+impl std::error::Error for ErrorLecturaCatalogo {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.causa)
     }
 }
 
-/// Ejecuta una consulta para obtener **un solo registro** de una entidad.
+/// Lee un archivo CSV de catálogos y devuelve su [`csv::Reader`].
+/// Busca `{catalogo}.csv`, `{catalogo}.csv.gz` y `{catalogo}.csv.zst`, en ese orden,
+/// y descomprime el archivo de forma transparente.
 ///
 /// ## Parámetros
-/// - `db`: Conexión a la base de datos
-/// - `selector`: Estructura de selector que devuelve modelos `M`
-/// - `mensaje`: mensaje de error para NotFound
+/// - `ruta`: directorio en donde se encuentra el catálogo (sin "/" final)
+/// - `catalogo`: nombre del catálogo sin extensión
 ///
 /// ## Retorno
-/// - `Ok(modelo)` donde modelo es el resultado de `selector`
-/// - `Err(NotFound)` si el selector no arrojó resultados
-/// - `Err(InternalServerError)` si ocurre un error inesperado durante la operación con la base de datos
+/// - `Reader` sobre el contenido (ya descomprimido) si el archivo pudo abrirse
+/// - `Err(ErrorLecturaCatalogo)` con la ruta y la causa si no existe o no pudo abrirse
 ///
-/// ## Errores
-/// Devuelve [`actix_web::Error`] en los casos antes mencionados
-///
-/// ## Ejemplo de uso
+/// ## Ejemplo
 /// ```rust
-/// let ciclo = registro(db, CatCiclos::find_by_id(1), "Ciclo no encontrado".to_owned()).await?;
+/// let rdr = leer_catalogo("./catalogos", "cat_estados")?;
 /// ```
-pub async fn registro<M>(
-    db: &DatabaseConnection,
-    selector: Select<M>,
-    mensaje: &'static str,
-) -> Result<M::Model, actix_web::Error>
-where
-    M: sea_orm::EntityTrait,
-    M::Model: Send,
-{
-    match selector
-        .one(db)
-        .await
-        .map_err(|e| error::ErrorInternalServerError(e))?
-    {
-        Some(l) => Ok(l),
-        None => return Err(error::ErrorNotFound(mensaje)),
-    }
+pub fn leer_catalogo(
+    ruta: &str,
+    catalogo: &str,
+) -> Result<Reader<Box<dyn Read + Send>>, ErrorLecturaCatalogo> {
+    let catalogo_path = ruta_catalogo(ruta, catalogo)
+        .unwrap_or_else(|| PathBuf::from(format!("{}/{}.csv", ruta, catalogo)));
+    Ok(csv::Reader::from_reader(abrir_archivo(&catalogo_path)?))
 }
 
-/// Variante de [`registro`] que devuelve la estructura [`CatalogoIdCadena`]
-/// en lugar del modelo.
+/// Abre un archivo de catálogo y lo descomprime de forma transparente si termina en `.gz` o `.zst`.
 ///
 /// ## Parámetros
-/// - `db`: Conexión a la base de datos
-/// - `selector`: Estructura de selector que devuelve modelos `M`
-/// - `mensaje`: mensaje de error para NotFound
+/// - `archivo_path`: ruta completa del archivo
 ///
 /// ## Retorno
-/// - `Ok(estructura)` donde estructura es el modelo obtenido de selector convertido a [`CatalogoIdCadena`]
-/// - `Err(NotFound)` si el selector no arrojó resultados
-/// - `Err(InternalServerError)` si ocurre un error inesperado durante la operación con la base de datos
-///
-/// ## Errores
-/// Devuelve [`actix_web::Error`] en los casos antes mencionados
-///
-/// ## Ejemplo de uso
-/// ```rust
-/// let programa = registro_estructura(db, CatProgramas::find_by_id(programa_ciclo.id_programa), "Error en el catalogo de programas en la base de datos".to_owned()).await?;
-/// ```
-pub async fn registro_estructura<M>(
-    db: &DatabaseConnection,
-    selector: Select<M>,
-    mensaje: &'static str,
-) -> Result<CatalogoIdCadena, actix_web::Error>
-where
-    M: sea_orm::EntityTrait,
-    M::Model: CatalogoCombo + Send,
-{
-    match registro(db, selector, mensaje).await {
-        Ok(l) => Ok(l.to_id_value()),
-        Err(e) => Err(e),
-    }
+/// - Lector sobre el contenido (ya descomprimido)
+/// - `Err(ErrorLecturaCatalogo)` con la ruta y la causa si no existe o no pudo abrirse
+pub fn abrir_archivo(archivo_path: &Path) -> Result<Box<dyn Read + Send>, ErrorLecturaCatalogo> {
+    let error = |causa| ErrorLecturaCatalogo {
+        ruta: archivo_path.to_path_buf(),
+        causa,
+    };
+
+    let archivo = File::open(archivo_path).map_err(error)?;
+    let nombre = archivo_path.to_string_lossy();
+    Ok(if nombre.ends_with(".gz") {
+        Box::new(MultiGzDecoder::new(archivo))
+    } else if nombre.ends_with(".zst") {
+        Box::new(zstd::Decoder::new(archivo).map_err(error)?)
+    } else {
+        Box::new(archivo)
+    })
 }
 
-/// Lee un archivo CSV de catálogos y devuelve su [`csv::Reader`].
+/// Indica si existe el archivo del catálogo, comprimido o no.
 ///
 /// ## Parámetros
 /// - `ruta`: directorio en donde se encuentra el catálogo (sin "/" final)
 /// - `catalogo`: nombre del catálogo sin extensión
+pub fn existe_catalogo(ruta: &str, catalogo: &str) -> bool {
+    ruta_catalogo(ruta, catalogo).is_some()
+}
+
+/// Devuelve la ruta del primer archivo existente del catálogo según [`EXTENSIONES_CATALOGO`].
+fn ruta_catalogo(ruta: &str, catalogo: &str) -> Option<PathBuf> {
+    buscar_archivo(ruta, catalogo, &EXTENSIONES_CATALOGO)
+}
+
+/// Devuelve la ruta del primer archivo `{ruta}/{nombre}.{extension}` que exista, probando las
+/// extensiones en el orden indicado.
+pub fn buscar_archivo(ruta: &str, nombre: &str, extensiones: &[&str]) -> Option<PathBuf> {
+    extensiones
+        .iter()
+        .map(|extension| PathBuf::from(format!("{}/{}.{}", ruta, nombre, extension)))
+        .find(|path| path.exists())
+}
+
+/// Número de línea del renglón `indice` (desde 0) de un CSV con encabezado.
 ///
-/// ## Retorno
-/// - `Reader<File>` si el csv pudo leerse correctamente
-///
-/// ## Panics
-/// Si el archivo no puede abrirse o el nombre es inválido.
-///
-/// ## Ejemplo
-/// ```rust
-/// leer_catalogo("./catalogos", "cat_estados");
-/// ```
-pub fn leer_catalogo(ruta: &str, catalogo: &str) -> Reader<File> {
-    let catalogo_path = format!("{}/{}.csv", ruta, catalogo);
-    let archivo = File::open(catalogo_path)
-        .unwrap_or_else(|err| panic!("No se pudo leer el catálogo de {}: {}", catalogo, err));
-    csv::Reader::from_reader(archivo)
+/// Se calcula a partir del índice porque `csv::Position::line` cuenta mal las líneas en
+/// archivos con fin de línea `\r\n`, como los catálogos de SEPOMEX. Supone que ningún
+/// campo contiene saltos de línea.
+pub fn linea_renglon(indice: usize) -> u64 {
+    indice as u64 + 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    const ESTADOS: &str = "id_estado,estado\n9,CIUDAD DE MEXICO\n14,JALISCO\n";
+
+    /// Directorio temporal vacío para los archivos de una prueba.
+    fn directorio(nombre: &str) -> PathBuf {
+        let directorio =
+            std::env::temp_dir().join(format!("conversores_{}_{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(&directorio).unwrap();
+        directorio
+    }
+
+    /// Renglones del catálogo, sin el encabezado.
+    fn renglones(ruta: &Path, catalogo: &str) -> Vec<Vec<String>> {
+        leer_catalogo(ruta.to_str().unwrap(), catalogo)
+            .unwrap()
+            .records()
+            .map(|renglon| renglon.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    fn estados() -> Vec<Vec<String>> {
+        vec![
+            vec!["9".to_string(), "CIUDAD DE MEXICO".to_string()],
+            vec!["14".to_string(), "JALISCO".to_string()],
+        ]
+    }
+
+    #[test]
+    fn lee_catalogos_comprimidos() {
+        let ruta = directorio("comprimidos");
+        let mut gzip = GzEncoder::new(
+            File::create(ruta.join("cat_estados.csv.gz")).unwrap(),
+            Compression::default(),
+        );
+        gzip.write_all(ESTADOS.as_bytes()).unwrap();
+        gzip.finish().unwrap();
+        std::fs::write(
+            ruta.join("cat_municipios.csv.zst"),
+            zstd::encode_all(ESTADOS.as_bytes(), 0).unwrap(),
+        )
+        .unwrap();
+
+        assert!(existe_catalogo(ruta.to_str().unwrap(), "cat_estados"));
+        assert!(existe_catalogo(ruta.to_str().unwrap(), "cat_municipios"));
+        assert_eq!(renglones(&ruta, "cat_estados"), estados());
+        assert_eq!(renglones(&ruta, "cat_municipios"), estados());
+    }
+
+    #[test]
+    fn prefiere_el_csv_sin_comprimir() {
+        let ruta = directorio("preferencia");
+        std::fs::write(ruta.join("cat_estados.csv"), ESTADOS).unwrap();
+        // Un `.gz` inválido no se abre porque el `.csv` tiene preferencia
+        std::fs::write(ruta.join("cat_estados.csv.gz"), "no es gzip").unwrap();
+        assert_eq!(renglones(&ruta, "cat_estados"), estados());
+    }
+
+    #[test]
+    fn catalogo_inexistente_indica_la_ruta() {
+        let ruta = directorio("inexistente");
+        assert!(!existe_catalogo(ruta.to_str().unwrap(), "cat_estados"));
+
+        let Err(error) = leer_catalogo(ruta.to_str().unwrap(), "cat_estados") else {
+            panic!("el catálogo no existe");
+        };
+        assert_eq!(error.ruta, ruta.join("cat_estados.csv"));
+        assert_eq!(error.causa.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().starts_with(&format!(
+            "No se pudo leer el catálogo {}: ",
+            error.ruta.display()
+        )));
+    }
+
+    #[test]
+    fn archivo_ilegible_indica_la_ruta() {
+        let ruta = directorio("ilegible");
+        std::fs::write(ruta.join("cat_estados.csv"), ESTADOS).unwrap();
+        // Una ruta que pasa por un archivo como si fuera directorio no se puede abrir
+        let archivo = ruta.join("cat_estados.csv").join("cat_estados.csv.zst");
+
+        let Err(error) = abrir_archivo(&archivo) else {
+            panic!("el archivo no se puede abrir");
+        };
+        assert_eq!(error.ruta, archivo);
+        assert_ne!(error.causa.kind(), io::ErrorKind::NotFound);
+        assert!(std::error::Error::source(&error).is_some());
+    }
 }
\ No newline at end of file
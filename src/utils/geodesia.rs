@@ -0,0 +1,59 @@
+//! # Utilidades de distancias sobre la superficie terrestre
+//!
+//! Las coordenadas se convierten en vectores unitarios en 3D (sobre la esfera). La distancia
+//! euclidiana entre dos vectores (la cuerda) crece igual que la distancia sobre la superficie,
+//! por lo que sirve para comparar distancias y buscar vecinos, y se convierte a metros con
+//! [`metros_cuerda`]. El resultado es el mismo que la fórmula de haversine.
+//!
+//! ## Componentes
+//!
+//! - Posición de unas coordenadas sobre la esfera ([`posicion`])
+//! - Distancia en metros entre dos coordenadas ([`distancia_metros`])
+
+/// Radio medio de la Tierra en metros
+const RADIO_TIERRA_METROS: f64 = 6_371_008.8;
+
+/// Posición sobre la esfera unitaria de unas coordenadas en grados.
+pub fn posicion(latitud: f64, longitud: f64) -> [f64; 3] {
+    let (latitud, longitud) = (latitud.to_radians(), longitud.to_radians());
+    [
+        latitud.cos() * longitud.cos(),
+        latitud.cos() * longitud.sin(),
+        latitud.sin(),
+    ]
+}
+
+/// Cuadrado de la distancia euclidiana entre dos posiciones de [`posicion`].
+pub fn cuerda2(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+/// Distancia sobre la superficie, en metros y redondeada a decímetros, que corresponde a una
+/// cuerda de la esfera unitaria (dada al cuadrado, como la devuelve [`cuerda2`]).
+pub fn metros_cuerda(cuerda2: f64) -> f64 {
+    let metros = 2.0 * RADIO_TIERRA_METROS * (cuerda2.sqrt() / 2.0).min(1.0).asin();
+    (metros * 10.0).round() / 10.0
+}
+
+/// Cuerda de la esfera unitaria (al cuadrado, como [`cuerda2`]) que corresponde a una distancia
+/// sobre la superficie en metros. Inversa de [`metros_cuerda`], sin redondeo.
+pub fn cuerda2_metros(metros: f64) -> f64 {
+    let angulo = (metros / RADIO_TIERRA_METROS).min(std::f64::consts::PI);
+    (2.0 * (angulo / 2.0).sin()).powi(2)
+}
+
+/// Distancia de círculo máximo, en metros y redondeada a decímetros, entre dos coordenadas en grados.
+///
+/// ## Parámetros
+/// - `origen`, `destino`: latitud y longitud en grados decimales
+///
+/// ## Ejemplo
+/// ```rust
+/// let metros = distancia_metros((19.4326, -99.1332), (20.6597, -103.3496));
+/// ```
+pub fn distancia_metros(origen: (f64, f64), destino: (f64, f64)) -> f64 {
+    metros_cuerda(cuerda2(
+        posicion(origen.0, origen.1),
+        posicion(destino.0, destino.1),
+    ))
+}
@@ -0,0 +1,118 @@
+//! # Idioma de las respuestas
+//!
+//! Los mensajes para el usuario (errores y documentación) están en español y en inglés.
+//! El idioma se elige con el encabezado `Accept-Language`; si no se envía o no incluye
+//! ninguno de los dos, se responde en español.
+
+/// Idiomas en los que se responden los mensajes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Idioma {
+    #[default]
+    Es,
+    En,
+}
+
+impl Idioma {
+    /// Elige el idioma a partir del valor de `Accept-Language`, respetando los pesos `q`.
+    /// Los idiomas con `q=0` y el comodín `*` no eligen ninguno; si no queda ni español ni
+    /// inglés se responde en español.
+    pub fn desde_accept_language(valor: &str) -> Idioma {
+        let mut elegido = (Idioma::default(), 0.0_f32);
+        for rango in valor.split(',') {
+            let mut partes = rango.split(';');
+            let etiqueta = partes.next().unwrap_or_default().trim();
+            let peso = partes
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let idioma = match etiqueta.split('-').next().unwrap_or_default() {
+                i if i.eq_ignore_ascii_case("es") => Idioma::Es,
+                i if i.eq_ignore_ascii_case("en") => Idioma::En,
+                _ => continue,
+            };
+            // Ante el mismo peso gana el que aparece primero
+            if peso > elegido.1 {
+                elegido = (idioma, peso);
+            }
+        }
+        elegido.0
+    }
+
+    /// Etiqueta del idioma para el encabezado `Content-Language`.
+    pub fn etiqueta(self) -> &'static str {
+        match self {
+            Idioma::Es => "es",
+            Idioma::En => "en",
+        }
+    }
+
+    /// Devuelve el texto correspondiente al idioma.
+    pub fn elegir<T>(self, es: T, en: T) -> T {
+        match self {
+            Idioma::Es => es,
+            Idioma::En => en,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respeta_los_pesos() {
+        assert_eq!(
+            Idioma::desde_accept_language("en-US,en;q=0.9,es;q=0.8"),
+            Idioma::En
+        );
+        assert_eq!(
+            Idioma::desde_accept_language("en;q=0.5, es;q=0.7"),
+            Idioma::Es
+        );
+        assert_eq!(
+            Idioma::desde_accept_language("es;q=0.2,en;q=0.3"),
+            Idioma::En
+        );
+        // Sin `q` el peso es 1, y ante el mismo peso gana el primero
+        assert_eq!(Idioma::desde_accept_language("fr, en"), Idioma::En);
+        assert_eq!(
+            Idioma::desde_accept_language("en;q=0.8, es;q=0.8"),
+            Idioma::En
+        );
+        // Un peso inválido cuenta como 1
+        assert_eq!(
+            Idioma::desde_accept_language("es;q=0.5, en;q=x"),
+            Idioma::En
+        );
+    }
+
+    #[test]
+    fn ignora_idiomas_rechazados() {
+        assert_eq!(Idioma::desde_accept_language("en;q=0"), Idioma::Es);
+        assert_eq!(Idioma::desde_accept_language("en;q=0, es;q=0"), Idioma::Es);
+    }
+
+    #[test]
+    fn acepta_etiquetas_regionales() {
+        assert_eq!(Idioma::desde_accept_language("en-US"), Idioma::En);
+        assert_eq!(Idioma::desde_accept_language("EN-gb"), Idioma::En);
+        assert_eq!(Idioma::desde_accept_language("es-MX,en;q=0.9"), Idioma::Es);
+        // Sólo cuenta el idioma principal de la etiqueta
+        assert_eq!(Idioma::desde_accept_language("fr-EN"), Idioma::Es);
+    }
+
+    #[test]
+    fn comodin_no_elige_idioma() {
+        assert_eq!(Idioma::desde_accept_language("*"), Idioma::Es);
+        assert_eq!(Idioma::desde_accept_language("*, en;q=0.1"), Idioma::En);
+        assert_eq!(Idioma::desde_accept_language("fr, *;q=0.5"), Idioma::Es);
+    }
+
+    #[test]
+    fn sin_espanol_ni_ingles_responde_en_espanol() {
+        assert_eq!(Idioma::desde_accept_language(""), Idioma::Es);
+        assert_eq!(Idioma::desde_accept_language("fr"), Idioma::Es);
+        assert_eq!(Idioma::desde_accept_language("de-DE, pt;q=0.9"), Idioma::Es);
+        assert_eq!(Idioma::desde_accept_language(" , ;q=1"), Idioma::Es);
+    }
+}
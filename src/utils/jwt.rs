@@ -1,3 +1,8 @@
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::types::errores::UbicacionError;
+
 pub fn validar_token(token: String) -> Result<(), jsonwebtoken::errors::Error> {
     if token.trim().is_empty() {
         return Err(jsonwebtoken::errors::Error::from(
@@ -7,3 +12,112 @@ pub fn validar_token(token: String) -> Result<(), jsonwebtoken::errors::Error> {
 
     Ok(())
 }
+
+// Claims que se leen del token para las rutas administrativas.
+// `scope` sigue la convención de OAuth2: scopes separados por espacios.
+#[derive(Debug, Deserialize)]
+struct ClaimsAdmin {
+    #[serde(default)]
+    scope: String,
+}
+
+// A diferencia de `validar_token`, verifica la firma con la variable JWT_SECRET
+// y que el token incluya el scope `admin`
+pub fn validar_token_admin(token: String) -> Result<(), UbicacionError> {
+    verificar_token_admin(&token, std::env::var("JWT_SECRET").ok().as_deref())
+}
+
+// Un token mal formado, con firma inválida o expirado (o sin JWT_SECRET para verificarlo) es
+// `TokenInvalido`; sólo un token válido sin el scope `admin` es `ScopeAdminRequerido`
+fn verificar_token_admin(token: &str, secreto: Option<&str>) -> Result<(), UbicacionError> {
+    validar_token(token.to_string()).map_err(|_| UbicacionError::TokenInvalido)?;
+
+    let secreto = secreto.ok_or(UbicacionError::TokenInvalido)?;
+    let datos = decode::<ClaimsAdmin>(
+        token,
+        &DecodingKey::from_secret(secreto.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| UbicacionError::TokenInvalido)?;
+
+    if datos.claims.scope.split_whitespace().any(|s| s == "admin") {
+        Ok(())
+    } else {
+        Err(UbicacionError::ScopeAdminRequerido)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRETO: &str = "secreto de prueba";
+
+    // Token firmado con `secreto` que expira en `vigencia` segundos (negativa si ya expiró)
+    fn token(secreto: &str, scope: &str, vigencia: i64) -> String {
+        let ahora = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        encode(
+            &Header::default(),
+            &json!({ "sub": "prueba", "scope": scope, "exp": ahora + vigencia }),
+            &EncodingKey::from_secret(secreto.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn acepta_token_con_scope_admin() {
+        let token = token(SECRETO, "lectura admin", 3600);
+        assert!(verificar_token_admin(&token, Some(SECRETO)).is_ok());
+    }
+
+    #[test]
+    fn token_valido_sin_scope_admin() {
+        let token = token(SECRETO, "lectura", 3600);
+        assert!(matches!(
+            verificar_token_admin(&token, Some(SECRETO)),
+            Err(UbicacionError::ScopeAdminRequerido)
+        ));
+    }
+
+    #[test]
+    fn firma_invalida() {
+        let token = token("otro secreto", "admin", 3600);
+        assert!(matches!(
+            verificar_token_admin(&token, Some(SECRETO)),
+            Err(UbicacionError::TokenInvalido)
+        ));
+    }
+
+    #[test]
+    fn token_expirado() {
+        let token = token(SECRETO, "admin", -3600);
+        assert!(matches!(
+            verificar_token_admin(&token, Some(SECRETO)),
+            Err(UbicacionError::TokenInvalido)
+        ));
+    }
+
+    #[test]
+    fn token_mal_formado() {
+        for token in ["", "no-es-jwt"] {
+            assert!(matches!(
+                verificar_token_admin(token, Some(SECRETO)),
+                Err(UbicacionError::TokenInvalido)
+            ));
+        }
+    }
+
+    #[test]
+    fn sin_jwt_secret() {
+        let token = token(SECRETO, "admin", 3600);
+        assert!(matches!(
+            verificar_token_admin(&token, None),
+            Err(UbicacionError::TokenInvalido)
+        ));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod conversores;
+pub mod geodesia;
+pub mod idioma;
 pub mod jwt;
\ No newline at end of file